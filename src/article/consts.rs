@@ -0,0 +1,2 @@
+pub const VIEW_INFO: &str = "https://api.bilibili.com/x/article/viewinfo";
+pub const LIST_ARTICLES: &str = "https://api.bilibili.com/x/article/list/web/articles";
@@ -0,0 +1,98 @@
+//! Article (column, `cv`) APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Result};
+
+pub mod consts;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Metadata of an article.
+pub struct ArticleInfo {
+    pub title: String,
+    pub mid: u64,
+    pub author_name: String,
+    #[serde(default)]
+    pub banner_url: String,
+    #[serde(default)]
+    pub image_urls: Vec<String>,
+    pub stats: ArticleStats,
+    /// Previous article in the same list, `0` if none.
+    #[serde(default)]
+    pub pre: u64,
+    /// Next article in the same list, `0` if none.
+    #[serde(default)]
+    pub next: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+/// Statistics of an article.
+pub struct ArticleStats {
+    pub view: u64,
+    pub favorite: u64,
+    pub like: u64,
+    pub reply: u64,
+    pub share: u64,
+    pub coin: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An article list (`rl`) with its articles.
+pub struct ArticleList {
+    pub list: ArticleListInfo,
+    pub articles: Vec<ArticleSummary>,
+    pub author: ArticleAuthor,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Metadata of an article list.
+pub struct ArticleListInfo {
+    pub id: u64,
+    pub mid: u64,
+    pub name: String,
+    #[serde(default)]
+    pub image_url: String,
+    #[serde(default)]
+    pub summary: String,
+    pub update_time: i64,
+    pub articles_count: u64,
+    #[serde(default)]
+    pub words: u64,
+    #[serde(default)]
+    pub read: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An article in a list.
+pub struct ArticleSummary {
+    /// The `cv` id.
+    pub id: u64,
+    pub title: String,
+    pub publish_time: i64,
+    #[serde(default)]
+    pub words: u64,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub image_urls: Vec<String>,
+    #[serde(default)]
+    pub stats: ArticleStats,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Author of an article list.
+pub struct ArticleAuthor {
+    pub mid: u64,
+    pub name: String,
+    #[serde(default)]
+    pub face: String,
+}
+
+/// Get metadata of the article `cv{cvid}`.
+pub async fn get_article_info(client: &BiliClient, cvid: u64) -> Result<ArticleInfo> {
+    client.get(consts::VIEW_INFO, &[("id", cvid)]).await
+}
+
+/// Get the article list `rl{rlid}` and all its articles.
+pub async fn get_article_list(client: &BiliClient, rlid: u64) -> Result<ArticleList> {
+    client.get(consts::LIST_ARTICLES, &[("id", rlid)]).await
+}
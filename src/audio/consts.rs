@@ -0,0 +1,4 @@
+pub const SONG_INFO: &str = "https://www.bilibili.com/audio/music-service-c/web/song/info";
+pub const SONG_URL: &str = "https://api.bilibili.com/audio/music-service-c/url";
+pub const MENU_INFO: &str = "https://www.bilibili.com/audio/music-service-c/web/menu/info";
+pub const MENU_SONGS: &str = "https://www.bilibili.com/audio/music-service-c/web/song/of-menu";
@@ -0,0 +1,143 @@
+//! Audio (`au`) APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Result};
+
+pub mod consts;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Information of a song.
+pub struct SongInfo {
+    /// The `au` id.
+    pub id: u64,
+    pub uid: u64,
+    pub uname: String,
+    #[serde(default)]
+    pub author: String,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub intro: String,
+    /// Url of the lrc lyric, may be empty.
+    #[serde(default)]
+    pub lyric: String,
+    /// Seconds.
+    pub duration: u64,
+    pub passtime: i64,
+    pub statistic: SongStatistic,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+/// Statistics of a song or a menu.
+pub struct SongStatistic {
+    pub play: u64,
+    pub collect: u64,
+    pub comment: u64,
+    pub share: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Audio quality, higher ones require VIP.
+pub enum AudioQuality {
+    /// 128K
+    Low,
+    /// 192K
+    Standard,
+    /// 320K
+    High,
+    /// FLAC
+    Lossless,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Stream urls of a song.
+pub struct SongUrl {
+    pub sid: u64,
+    /// Actual quality, may be lower than requested.
+    pub r#type: i32,
+    /// Seconds the urls stay valid.
+    pub timeout: u64,
+    /// Bytes.
+    pub size: u64,
+    /// Urls from different CDNs.
+    pub cdns: Vec<String>,
+    #[serde(default)]
+    pub qualities: Option<Vec<SongQuality>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A quality the song is available in.
+pub struct SongQuality {
+    pub r#type: i32,
+    pub desc: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A menu (playlist), `am` id.
+pub struct MenuInfo {
+    #[serde(rename = "menuId")]
+    pub menu_id: u64,
+    pub uid: u64,
+    pub uname: String,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub intro: String,
+    pub ctime: i64,
+    pub statistic: SongStatistic,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of songs in a menu.
+pub struct MenuSongs {
+    #[serde(rename = "curPage")]
+    pub cur_page: u64,
+    #[serde(rename = "pageCount")]
+    pub page_count: u64,
+    #[serde(rename = "totalSize")]
+    pub total_size: u64,
+    pub data: Vec<SongInfo>,
+}
+
+/// Get information of the song `au{sid}`.
+pub async fn get_song_info(client: &BiliClient, sid: u64) -> Result<SongInfo> {
+    client.get(consts::SONG_INFO, &[("sid", sid)]).await
+}
+
+/// Get stream urls of the song, they need `Referer` set to bilibili to download.
+pub async fn get_song_url(client: &BiliClient, sid: u64, quality: AudioQuality) -> Result<SongUrl> {
+    let quality = match quality {
+        AudioQuality::Low => "0",
+        AudioQuality::Standard => "1",
+        AudioQuality::High => "2",
+        AudioQuality::Lossless => "3",
+    };
+    client
+        .get(
+            consts::SONG_URL,
+            &[
+                ("songid", sid.to_string().as_str()),
+                ("quality", quality),
+                ("privilege", "2"),
+                ("mid", "0"),
+                ("platform", "web"),
+            ],
+        )
+        .await
+}
+
+/// Get information of the menu `am{sid}`.
+pub async fn get_menu_info(client: &BiliClient, sid: u64) -> Result<MenuInfo> {
+    client.get(consts::MENU_INFO, &[("sid", sid)]).await
+}
+
+/// Get a page, starting from `1`, of songs in the menu.
+pub async fn get_menu_songs(client: &BiliClient, sid: u64, page: u64) -> Result<MenuSongs> {
+    client
+        .get(
+            consts::MENU_SONGS,
+            &[("sid", sid), ("pn", page), ("ps", 100)],
+        )
+        .await
+}
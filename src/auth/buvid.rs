@@ -0,0 +1,94 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Device identifiers bilibili expects from a browser, without them many apis fail with `-352`.
+pub struct Fingerprint {
+    pub buvid3: String,
+    pub buvid4: String,
+    /// Unix timestamp in seconds the device was first seen.
+    pub b_nut: i64,
+    pub uuid: String,
+    pub buvid_fp: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Spi {
+    b_3: String,
+    b_4: String,
+}
+
+fn random_hex(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| std::char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Random `XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX` string.
+fn random_uuid() -> String {
+    [8, 4, 4, 4, 12]
+        .iter()
+        .map(|len| random_hex(*len))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn now_secs() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+impl Fingerprint {
+    /// Generate identifiers locally in the same format the web player does.
+    pub fn generate() -> Self {
+        let suffix = format!("{:05}infoc", rand::thread_rng().gen_range(0..100000));
+        Self {
+            buvid3: format!("{}{}", random_uuid(), suffix),
+            buvid4: format!("{}{}-{}", random_uuid(), suffix, now_secs()),
+            b_nut: now_secs(),
+            uuid: format!("{}{}", random_uuid(), suffix),
+            buvid_fp: random_hex(32).to_lowercase(),
+        }
+    }
+
+    /// Get `buvid3` and `buvid4` issued by bilibili, the rest is generated locally.
+    pub async fn fetch(client: &BiliClient) -> Result<Self> {
+        let spi: Spi = client.get(consts::FINGER_SPI, &()).await?;
+        Ok(Self {
+            buvid3: spi.b_3,
+            buvid4: spi.b_4,
+            ..Self::generate()
+        })
+    }
+
+    /// Cookies carrying the fingerprint.
+    pub fn cookies(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("buvid3", self.buvid3.clone()),
+            ("buvid4", self.buvid4.clone()),
+            ("b_nut", self.b_nut.to_string()),
+            ("_uuid", self.uuid.clone()),
+            ("buvid_fp", self.buvid_fp.clone()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let fingerprint = Fingerprint::generate();
+        assert_eq!(fingerprint.buvid3.len(), 46);
+        assert!(fingerprint.buvid3.ends_with("infoc"));
+        assert_eq!(fingerprint.buvid_fp.len(), 32);
+    }
+}
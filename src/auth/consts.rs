@@ -0,0 +1,23 @@
+pub const NAV: &str = "https://api.bilibili.com/x/web-interface/nav";
+pub const FINGER_SPI: &str = "https://api.bilibili.com/x/frontend/finger/spi";
+pub const COOKIE_INFO: &str = "https://passport.bilibili.com/x/passport-login/web/cookie/info";
+pub const CORRESPOND: &str = "https://www.bilibili.com/correspond/1";
+pub const COOKIE_REFRESH: &str =
+    "https://passport.bilibili.com/x/passport-login/web/cookie/refresh";
+pub const CONFIRM_REFRESH: &str =
+    "https://passport.bilibili.com/x/passport-login/web/confirm/refresh";
+/// Public key used to encrypt the correspond path.
+pub const CORRESPOND_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQDLgd2OAkcGVtoE3ThUREbio0Eg
+Uc/prcajMKXvkCKFCWhJYJcLkcM2DKKcSeFpD/j6Boy538YXnR6VhcuUJOhH2x71
+nzPjfdTcqMz7djHum0qSZA0AyCBDABUqCrfNgCiJ00Ra7GmRj+YCK1NJEuewlb40
+JNrRuoEUXpabUzGB8QIDAQAB
+-----END PUBLIC KEY-----";
+pub const CAPTCHA: &str = "https://passport.bilibili.com/x/passport-login/captcha";
+pub const LOGIN_KEY: &str = "https://passport.bilibili.com/x/passport-login/web/key";
+pub const LOGIN_PASSWORD: &str = "https://passport.bilibili.com/x/passport-login/web/login";
+pub const SMS_SEND: &str = "https://passport.bilibili.com/x/passport-login/web/sms/send";
+pub const LOGIN_SMS: &str = "https://passport.bilibili.com/x/passport-login/web/login/sms";
+pub const LOGOUT: &str = "https://passport.bilibili.com/login/exit/v2";
+pub const GAIA_REGISTER: &str = "https://api.bilibili.com/x/gaia-vgate/v1/register";
+pub const GAIA_VALIDATE: &str = "https://api.bilibili.com/x/gaia-vgate/v1/validate";
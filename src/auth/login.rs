@@ -0,0 +1,355 @@
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::Method;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{consts, Session};
+use crate::client::api_response;
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+/// Cookies carried by the cross domain url of a login, in case they are not set by headers.
+const LOGIN_COOKIES: [&str; 5] = [
+    "DedeUserID",
+    "DedeUserID__ckMd5",
+    "SESSDATA",
+    "bili_jct",
+    "sid",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A geetest captcha to pass before logging in, show it with `gt` and `challenge`.
+pub struct CaptchaChallenge {
+    /// Token of the login attempt.
+    pub token: String,
+    pub gt: String,
+    pub challenge: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A passed geetest captcha.
+pub struct CaptchaSolution {
+    pub token: String,
+    pub challenge: String,
+    /// `geetest_validate` of the passed captcha.
+    pub validate: String,
+    /// `geetest_seccode` of the passed captcha.
+    pub seccode: String,
+}
+
+impl CaptchaChallenge {
+    /// The solution with the `validate` and `seccode` given by geetest.
+    pub fn solve(self, validate: &str, seccode: &str) -> CaptchaSolution {
+        CaptchaSolution {
+            token: self.token,
+            challenge: self.challenge,
+            validate: validate.to_string(),
+            seccode: seccode.to_string(),
+        }
+    }
+}
+
+impl CaptchaSolution {
+    fn form(&self) -> [(&'static str, String); 4] {
+        [
+            ("token", self.token.clone()),
+            ("challenge", self.challenge.clone()),
+            ("validate", self.validate.clone()),
+            ("seccode", self.seccode.clone()),
+        ]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A login step which may require a captcha first.
+pub enum LoginStep<T> {
+    /// Pass the captcha, then retry the step with its solution.
+    Captcha(CaptchaChallenge),
+    Done(T),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A sent SMS code.
+pub struct SmsCode {
+    /// Key to log in with the received code.
+    pub captcha_key: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Captcha {
+    token: String,
+    geetest: Geetest,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Geetest {
+    gt: String,
+    challenge: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct LoginKey {
+    /// Salt prepended to the password.
+    hash: String,
+    /// Public key in PEM.
+    key: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct LoginData {
+    /// `0` logged in, others require a verification at `url`.
+    status: i64,
+    message: String,
+    url: String,
+    refresh_token: String,
+}
+
+/// Get a geetest captcha to pass before sending an SMS code or logging in by password.
+pub async fn get_captcha(client: &BiliClient) -> Result<CaptchaChallenge> {
+    let captcha: Captcha = client
+        .get(consts::CAPTCHA, &[("source", "main_web")])
+        .await?;
+    Ok(CaptchaChallenge {
+        token: captcha.token,
+        gt: captcha.geetest.gt,
+        challenge: captcha.geetest.challenge,
+    })
+}
+
+/// Encrypt the salted password with the public key of the login.
+fn encrypt_password(key: &LoginKey, password: &str) -> Result<String> {
+    let public_key = RsaPublicKey::from_public_key_pem(&key.key)
+        .map_err(|e| Error::UnexpectedResponse(format!("invalid public key: {}", e)))?;
+    let encrypted = public_key
+        .encrypt(
+            &mut rand::thread_rng(),
+            Pkcs1v15Encrypt,
+            format!("{}{}", key.hash, password).as_bytes(),
+        )
+        .map_err(|e| Error::UnexpectedResponse(format!("failed to encrypt: {}", e)))?;
+    Ok(STANDARD.encode(encrypted))
+}
+
+/// Add the cookies of the cross domain url, in case they were not set by headers.
+fn add_url_cookies(cookies: &mut BTreeMap<String, String>, url: &str) {
+    if let Ok(url) = reqwest::Url::parse(url) {
+        for (name, value) in url.query_pairs() {
+            if LOGIN_COOKIES.contains(&name.as_ref()) {
+                cookies
+                    .entry(name.into_owned())
+                    .or_insert_with(|| value.into_owned());
+            }
+        }
+    }
+}
+
+/// POST a login form and unwrap its [`ApiResponse`](crate::ApiResponse), with the cookies set.
+async fn post_login<T: DeserializeOwned>(
+    client: &BiliClient,
+    url: &str,
+    form: &[(&str, String)],
+) -> Result<(T, BTreeMap<String, String>)> {
+    debug!("POST {}", url);
+    let response = client
+        .send(client.request(Method::POST, url).form(form))
+        .await?;
+    let cookies = super::response_cookies(&response);
+    let data = api_response(response).await?.into_result()?;
+    Ok((data, cookies))
+}
+
+/// Turn a login result into a session and log in the client with it.
+fn finish_login(
+    client: &BiliClient,
+    data: LoginData,
+    mut cookies: BTreeMap<String, String>,
+) -> Result<Session> {
+    if data.status != 0 {
+        return Err(Error::UnexpectedResponse(format!(
+            "login requires verification ({}): {} {}",
+            data.status, data.message, data.url
+        )));
+    }
+    let refresh_token = Some(data.refresh_token).filter(|token| !token.is_empty());
+    add_url_cookies(&mut cookies, &data.url);
+    let session = Session::new(cookies, refresh_token);
+    if session.sessdata().is_none() {
+        return Err(Error::UnexpectedResponse(
+            "login succeeded without SESSDATA".to_string(),
+        ));
+    }
+    info!("logged in as {:?}", session.uid());
+    client.set_session(Some(session.clone()));
+    Ok(session)
+}
+
+/// Log in by password, the client is logged in on success.
+///
+/// Without `captcha` a [`CaptchaChallenge`] is returned, pass it and retry with the solution.
+pub async fn login_with_password(
+    client: &BiliClient,
+    username: &str,
+    password: &str,
+    captcha: Option<&CaptchaSolution>,
+) -> Result<LoginStep<Session>> {
+    let captcha = match captcha {
+        Some(captcha) => captcha,
+        None => return Ok(LoginStep::Captcha(get_captcha(client).await?)),
+    };
+    let key: LoginKey = client.get(consts::LOGIN_KEY, &()).await?;
+    let mut form = vec![
+        ("username", username.to_string()),
+        ("password", encrypt_password(&key, password)?),
+        ("keep", "0".to_string()),
+        ("source", "main_web".to_string()),
+        ("go_url", "https://www.bilibili.com".to_string()),
+    ];
+    form.extend(captcha.form());
+    let (data, cookies) = post_login(client, consts::LOGIN_PASSWORD, &form).await?;
+    finish_login(client, data, cookies).map(LoginStep::Done)
+}
+
+/// Send a login code to the phone `tel` with the country code `cid`, e.g. `86`.
+///
+/// Without `captcha` a [`CaptchaChallenge`] is returned, pass it and retry with the solution.
+pub async fn send_sms_code(
+    client: &BiliClient,
+    cid: u32,
+    tel: &str,
+    captcha: Option<&CaptchaSolution>,
+) -> Result<LoginStep<SmsCode>> {
+    let captcha = match captcha {
+        Some(captcha) => captcha,
+        None => return Ok(LoginStep::Captcha(get_captcha(client).await?)),
+    };
+    let mut form = vec![
+        ("cid", cid.to_string()),
+        ("tel", tel.to_string()),
+        ("source", "main_web".to_string()),
+    ];
+    form.extend(captcha.form());
+    let (code, _) = post_login(client, consts::SMS_SEND, &form).await?;
+    Ok(LoginStep::Done(code))
+}
+
+/// Log in with the `code` received after [`send_sms_code`], the client is logged in on success.
+pub async fn login_with_sms(
+    client: &BiliClient,
+    cid: u32,
+    tel: &str,
+    sms: &SmsCode,
+    code: &str,
+) -> Result<Session> {
+    let form = [
+        ("cid", cid.to_string()),
+        ("tel", tel.to_string()),
+        ("code", code.to_string()),
+        ("source", "main_web".to_string()),
+        ("captcha_key", sms.captcha_key.clone()),
+        ("keep", "true".to_string()),
+        ("go_url", "https://www.bilibili.com".to_string()),
+    ];
+    let (data, cookies) = post_login(client, consts::LOGIN_SMS, &form).await?;
+    finish_login(client, data, cookies)
+}
+
+/// Log out, invalidating the cookies of the session, and clear the session of the client.
+pub async fn logout(client: &BiliClient) -> Result<()> {
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::LOGOUT,
+            &[
+                ("biliCSRF", csrf.as_str()),
+                ("gourl", "https://www.bilibili.com"),
+            ],
+        )
+        .await?;
+    client.set_session(None);
+    info!("logged out");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[test]
+    fn test_encrypt_password() {
+        let key = LoginKey {
+            hash: "salt".to_string(),
+            key: consts::CORRESPOND_PUBLIC_KEY.to_string(),
+        };
+        // PKCS#1 v1.5 is randomized, so only the length of a 1024-bit block can be checked
+        let encrypted = encrypt_password(&key, "password").unwrap();
+        assert_eq!(STANDARD.decode(encrypted).unwrap().len(), 128);
+    }
+
+    #[tokio::test]
+    async fn test_login_with_password() {
+        let transport = MockTransport::new()
+            .json(
+                consts::CAPTCHA,
+                json!({"code": 0, "data": {
+                    "type": "geetest", "token": "token",
+                    "geetest": {"gt": "gt", "challenge": "challenge"},
+                }}),
+            )
+            .json(
+                consts::LOGIN_KEY,
+                json!({"code": 0, "data": {"hash": "salt", "key": consts::CORRESPOND_PUBLIC_KEY}}),
+            )
+            .json(
+                consts::LOGIN_PASSWORD,
+                json!({"code": 0, "data": {
+                    "status": 0, "message": "", "refresh_token": "refresh",
+                    "url": "https://passport.biligame.com/crossDomain?DedeUserID=10086&SESSDATA=sess&bili_jct=csrf&gourl=https%3A%2F%2Fwww.bilibili.com",
+                }}),
+            );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let challenge = match login_with_password(&client, "user", "password", None)
+            .await
+            .unwrap()
+        {
+            LoginStep::Captcha(challenge) => challenge,
+            other => panic!("unexpected step: {:?}", other),
+        };
+        assert_eq!(challenge.gt, "gt");
+        let solution = challenge.solve("validate", "validate|jordan");
+        let session = match login_with_password(&client, "user", "password", Some(&solution))
+            .await
+            .unwrap()
+        {
+            LoginStep::Done(session) => session,
+            other => panic!("unexpected step: {:?}", other),
+        };
+        assert_eq!(session.uid(), Some(10086));
+        assert_eq!(session.csrf(), Some("csrf"));
+        assert_eq!(session.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(client.csrf().unwrap(), "csrf");
+    }
+
+    #[tokio::test]
+    async fn test_logout() {
+        let transport = MockTransport::new().json(
+            consts::LOGOUT,
+            json!({"code": 0, "status": true, "data": {"redirectUrl": "https://www.bilibili.com"}}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport)
+            .session(Session::from_cookie_str("SESSDATA=sess; bili_jct=csrf"))
+            .build()
+            .unwrap();
+        logout(&client).await.unwrap();
+        assert!(client.session().is_none());
+        assert!(logout(&client).await.is_err());
+    }
+}
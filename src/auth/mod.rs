@@ -0,0 +1,170 @@
+//! Login state and account related APIs.
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use reqwest::header::SET_COOKIE;
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+mod buvid;
+pub mod consts;
+mod login;
+mod nav;
+mod refresh;
+mod risk;
+
+pub use buvid::Fingerprint;
+pub use login::{
+    get_captcha, login_with_password, login_with_sms, logout, send_sms_code, CaptchaChallenge,
+    CaptchaSolution, LoginStep, SmsCode,
+};
+pub use nav::{get_nav, LevelInfo, Nav, Wallet, WbiImg};
+pub use refresh::{correspond_path, CookieInfo};
+pub use risk::{register_risk_control, validate_risk_control};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+/// Credentials of a logged-in account.
+pub struct Session {
+    /// Cookies set by bilibili, e.g. `SESSDATA`, `bili_jct` and `DedeUserID`.
+    pub cookies: BTreeMap<String, String>,
+    /// Token used to refresh the cookies, only available after a login flow.
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Token of the app APIs, only available after an app login flow.
+    #[serde(default)]
+    pub access_key: Option<String>,
+}
+
+impl Session {
+    pub fn new(cookies: BTreeMap<String, String>, refresh_token: Option<String>) -> Self {
+        Self {
+            cookies,
+            refresh_token,
+            access_key: None,
+        }
+    }
+
+    /// Parse a `Cookie` header, e.g. one copied from the browser.
+    pub fn from_cookie_str(cookie: &str) -> Self {
+        let cookies = cookie
+            .split(';')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                Some((name.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+        Self::new(cookies, None)
+    }
+
+    /// Get a cookie by name.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies.get(name).map(String::as_str)
+    }
+
+    /// Get the `SESSDATA` cookie.
+    pub fn sessdata(&self) -> Option<&str> {
+        self.cookie("SESSDATA")
+    }
+
+    /// Get the csrf token, which is the `bili_jct` cookie.
+    pub fn csrf(&self) -> Option<&str> {
+        self.cookie("bili_jct")
+    }
+
+    /// Get the uid of the account.
+    pub fn uid(&self) -> Option<u64> {
+        self.cookie("DedeUserID")?.parse().ok()
+    }
+
+    /// Render the cookies as a `Cookie` header value.
+    pub fn cookie_header(&self) -> String {
+        self.cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Save the session as json into a file.
+    ///
+    /// On unix the file is only readable by its owner, the cookies grant full account access.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = self.to_json()?;
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path)?;
+        // the mode only applies to new files
+        #[cfg(unix)]
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Load a session saved by [`Session::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Cookies set by a response with `Set-Cookie`.
+pub(crate) fn response_cookies(response: &Response) -> BTreeMap<String, String> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| {
+            let (name, value) = cookie.to_str().ok()?.split(';').next()?.split_once('=')?;
+            Some((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_json_round_trip() {
+        let mut session =
+            Session::from_cookie_str("SESSDATA=abc%2C123; bili_jct=csrf; DedeUserID=10086");
+        session.refresh_token = Some("token".to_string());
+        assert_eq!(session.csrf(), Some("csrf"));
+        assert_eq!(session.uid(), Some(10086));
+        assert_eq!(
+            session.cookie_header(),
+            "DedeUserID=10086; SESSDATA=abc%2C123; bili_jct=csrf"
+        );
+        let json = session.to_json().unwrap();
+        assert_eq!(Session::from_json(&json).unwrap(), session);
+    }
+
+    #[test]
+    fn test_session_save() {
+        let path = std::env::temp_dir().join(format!("bili-session-{}.json", std::process::id()));
+        std::fs::write(&path, "stale and much longer than the session").unwrap();
+        let session = Session::from_cookie_str("SESSDATA=abc; bili_jct=csrf");
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
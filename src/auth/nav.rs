@@ -0,0 +1,126 @@
+use reqwest::header::COOKIE;
+use serde::{Deserialize, Serialize};
+
+use super::{consts, Session};
+use crate::client::api_response;
+use crate::error::ErrorCode;
+use crate::{ApiResponse, BiliClient, Result};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Account state returned by the nav api, most fields are empty if not logged in.
+pub struct Nav {
+    #[serde(rename = "isLogin")]
+    pub is_login: bool,
+    #[serde(default)]
+    pub mid: u64,
+    #[serde(default)]
+    pub uname: String,
+    #[serde(default)]
+    pub face: String,
+    #[serde(default)]
+    pub level_info: LevelInfo,
+    /// Coins.
+    #[serde(default)]
+    pub money: f64,
+    #[serde(default, rename = "vipStatus")]
+    pub vip_status: u8,
+    #[serde(default, rename = "vipType")]
+    pub vip_type: u8,
+    #[serde(default)]
+    pub wallet: Wallet,
+    pub wbi_img: WbiImg,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Account level.
+pub struct LevelInfo {
+    pub current_level: u8,
+    #[serde(default)]
+    pub current_exp: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// B-coin wallet.
+pub struct Wallet {
+    pub bcoin_balance: f64,
+    pub coupon_balance: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Images whose file names are the WBI keys.
+pub struct WbiImg {
+    pub img_url: String,
+    pub sub_url: String,
+}
+
+impl WbiImg {
+    /// Extract `(img_key, sub_key)` from the urls.
+    pub fn keys(&self) -> (String, String) {
+        fn stem(url: &str) -> String {
+            let name = url.rsplit('/').next().unwrap_or_default();
+            name.split('.').next().unwrap_or_default().to_string()
+        }
+        (stem(&self.img_url), stem(&self.sub_url))
+    }
+}
+
+/// Get the account state, works without login as well.
+pub async fn get_nav(client: &BiliClient) -> Result<Nav> {
+    // code `-101` comes with the data when not logged in
+    let response = client.get_response::<Nav, _>(consts::NAV, &()).await?;
+    match response.data() {
+        Some(nav) if !nav.is_login => Ok(nav.clone()),
+        _ => response.into_result(),
+    }
+}
+
+impl Session {
+    /// Check with nav whether the cookies are still logged in, e.g. before a long job starts.
+    pub async fn is_valid(&self, client: &BiliClient) -> Result<bool> {
+        let request = client
+            .http()
+            .get(consts::NAV)
+            .header(COOKIE, self.cookie_header());
+        let response: ApiResponse<Nav> = api_response(client.send(request).await?).await?;
+        if ErrorCode::from_i64(response.code()) == ErrorCode::NotLoggedIn {
+            return Ok(false);
+        }
+        Ok(response.into_result()?.is_login)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wbi_keys() {
+        let img = WbiImg {
+            img_url: "https://i0.hdslb.com/bfs/wbi/7cd084941338484aae1ad9425b84077c.png"
+                .to_string(),
+            sub_url: "https://i0.hdslb.com/bfs/wbi/4932caff0ff746eab6f01bf08b70ac45.png"
+                .to_string(),
+        };
+        assert_eq!(
+            img.keys(),
+            (
+                "7cd084941338484aae1ad9425b84077c".to_string(),
+                "4932caff0ff746eab6f01bf08b70ac45".to_string()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_is_valid() {
+        let transport = crate::MockTransport::new().json(
+            consts::NAV,
+            serde_json::json!({"code": -101, "message": "账号未登录", "data": {
+                "isLogin": false,
+                "wbi_img": {"img_url": "", "sub_url": ""},
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let session = Session::from_cookie_str("SESSDATA=expired");
+        assert!(!session.is_valid(&client).await.unwrap());
+    }
+}
@@ -0,0 +1,142 @@
+use reqwest::header::COOKIE;
+use reqwest::RequestBuilder;
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Oaep, RsaPublicKey};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use super::{consts, Session};
+use crate::client::api_response;
+use crate::error::Error;
+use crate::{ApiResponse, BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// Whether the cookies should be refreshed.
+pub struct CookieInfo {
+    pub refresh: bool,
+    /// Current timestamp in milliseconds, used to compute the correspond path.
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RefreshData {
+    refresh_token: String,
+}
+
+async fn send<T: DeserializeOwned>(client: &BiliClient, request: RequestBuilder) -> Result<T> {
+    api_response(client.send(request).await?)
+        .await?
+        .into_result()
+}
+
+/// Encrypt `refresh_{timestamp}` with the bilibili public key.
+pub fn correspond_path(timestamp: i64) -> Result<String> {
+    let key = RsaPublicKey::from_public_key_pem(consts::CORRESPOND_PUBLIC_KEY)
+        .map_err(|e| Error::UnexpectedResponse(format!("invalid public key: {}", e)))?;
+    let encrypted = key
+        .encrypt(
+            &mut rand::thread_rng(),
+            Oaep::new::<Sha256>(),
+            format!("refresh_{}", timestamp).as_bytes(),
+        )
+        .map_err(|e| Error::UnexpectedResponse(format!("failed to encrypt: {}", e)))?;
+    Ok(hex::encode(encrypted))
+}
+
+impl Session {
+    fn require_csrf(&self) -> Result<String> {
+        self.csrf()
+            .map(str::to_string)
+            .ok_or(Error::MissingCredential("bili_jct"))
+    }
+
+    /// Ask bilibili whether the cookies should be refreshed.
+    pub async fn cookie_info(&self, client: &BiliClient) -> Result<CookieInfo> {
+        let request = client
+            .http()
+            .get(consts::COOKIE_INFO)
+            .query(&[("csrf", self.require_csrf()?)])
+            .header(COOKIE, self.cookie_header());
+        send(client, request).await
+    }
+
+    /// Refresh the cookies and the refresh token in place.
+    ///
+    /// The old session is invalidated once this succeeds.
+    pub async fn refresh(&mut self, client: &BiliClient) -> Result<()> {
+        let csrf = self.require_csrf()?;
+        let refresh_token = self
+            .refresh_token
+            .clone()
+            .ok_or(Error::MissingCredential("refresh_token"))?;
+        let info = self.cookie_info(client).await?;
+
+        let url = format!(
+            "{}/{}",
+            consts::CORRESPOND,
+            correspond_path(info.timestamp)?
+        );
+        let html = client
+            .send(client.http().get(url).header(COOKIE, self.cookie_header()))
+            .await?
+            .text()
+            .await?;
+        let refresh_csrf = extract_refresh_csrf(&html).ok_or_else(|| {
+            Error::UnexpectedResponse("refresh_csrf not found in correspond page".to_string())
+        })?;
+
+        let request = client
+            .http()
+            .post(consts::COOKIE_REFRESH)
+            .header(COOKIE, self.cookie_header())
+            .form(&[
+                ("csrf", csrf.as_str()),
+                ("refresh_csrf", refresh_csrf),
+                ("source", "main_web"),
+                ("refresh_token", refresh_token.as_str()),
+            ]);
+        let response = client.send(request).await?;
+        self.cookies.extend(super::response_cookies(&response));
+        let response: ApiResponse<RefreshData> = api_response(response).await?;
+        self.refresh_token = Some(response.into_result()?.refresh_token);
+        debug!("cookies refreshed for {:?}", self.uid());
+
+        let request = client
+            .http()
+            .post(consts::CONFIRM_REFRESH)
+            .header(COOKIE, self.cookie_header())
+            .form(&[
+                ("csrf", self.require_csrf()?),
+                ("refresh_token", refresh_token),
+            ]);
+        let response: ApiResponse<IgnoredAny> = api_response(client.send(request).await?).await?;
+        response.into_unit()
+    }
+}
+
+fn extract_refresh_csrf(html: &str) -> Option<&str> {
+    let start = html.find("<div id=\"1-name\">")? + "<div id=\"1-name\">".len();
+    let end = html[start..].find("</div>")?;
+    Some(html[start..start + end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correspond_path() {
+        // OAEP is randomized, so only the length of a 1024-bit block can be checked
+        assert_eq!(correspond_path(1684466082142).unwrap().len(), 256);
+    }
+
+    #[test]
+    fn test_extract_refresh_csrf() {
+        let html = "<div id=\"1-name\">b0cc8411ded2f9db2cff2edb3123acac</div>";
+        assert_eq!(
+            extract_refresh_csrf(html),
+            Some("b0cc8411ded2f9db2cff2edb3123acac")
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Deserialize;
+
+use super::consts;
+use super::login::{CaptchaChallenge, CaptchaSolution};
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Deserialize)]
+struct Register {
+    token: String,
+    /// Missing if no captcha can pass the risk control.
+    geetest: Option<Geetest>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Geetest {
+    gt: String,
+    challenge: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Validate {
+    is_valid: u8,
+    grisk_id: String,
+}
+
+/// Get the captcha to pass for the `v_voucher` of an [`Error::RiskControl`].
+pub async fn register_risk_control(
+    client: &BiliClient,
+    v_voucher: &str,
+) -> Result<CaptchaChallenge> {
+    let csrf = client.csrf().unwrap_or_default();
+    let register: Register = client
+        .post_form(
+            consts::GAIA_REGISTER,
+            &[("v_voucher", v_voucher), ("csrf", csrf.as_str())],
+        )
+        .await?;
+    let geetest = register
+        .geetest
+        .ok_or_else(|| Error::UnexpectedResponse("no captcha for the risk control".to_string()))?;
+    Ok(CaptchaChallenge {
+        token: register.token,
+        gt: geetest.gt,
+        challenge: geetest.challenge,
+    })
+}
+
+/// Submit the passed captcha, the client then sends its token with every request so the
+/// rejected request can be retried.
+pub async fn validate_risk_control(client: &BiliClient, captcha: &CaptchaSolution) -> Result<()> {
+    let csrf = client.csrf().unwrap_or_default();
+    let validate: Validate = client
+        .post_form(
+            consts::GAIA_VALIDATE,
+            &[
+                ("token", captcha.token.as_str()),
+                ("challenge", captcha.challenge.as_str()),
+                ("validate", captcha.validate.as_str()),
+                ("seccode", captcha.seccode.as_str()),
+                ("csrf", csrf.as_str()),
+            ],
+        )
+        .await?;
+    if validate.is_valid != 1 {
+        return Err(Error::UnexpectedResponse(
+            "risk control captcha was not accepted".to_string(),
+        ));
+    }
+    client.set_gaia_vtoken(Some(validate.grisk_id));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_risk_control() {
+        let transport = MockTransport::new()
+            .json(
+                consts::GAIA_REGISTER,
+                json!({"code": 0, "data": {
+                    "type": "geetest", "token": "token",
+                    "geetest": {"gt": "gt", "challenge": "challenge"},
+                }}),
+            )
+            .json(
+                consts::GAIA_VALIDATE,
+                json!({"code": 0, "data": {"is_valid": 1, "grisk_id": "grisk"}}),
+            );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let challenge = register_risk_control(&client, "voucher_1").await.unwrap();
+        assert_eq!(challenge.challenge, "challenge");
+        validate_risk_control(&client, &challenge.solve("validate", "validate|jordan"))
+            .await
+            .unwrap();
+        assert_eq!(
+            client.cookie_header().as_deref(),
+            Some("x-bili-gaia-vtoken=grisk")
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub const SEASON: &str = "https://api.bilibili.com/pgc/view/web/season";
+pub const SECTION: &str = "https://api.bilibili.com/pgc/web/season/section";
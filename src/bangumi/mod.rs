@@ -0,0 +1,117 @@
+//! Bangumi (PGC season) APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Result};
+
+pub mod consts;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Identify a season by itself or by any of its episodes.
+pub enum SeasonQuery {
+    /// `ss` id.
+    Season(u64),
+    /// `ep` id.
+    Episode(u64),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Information of a season.
+pub struct SeasonInfo {
+    pub season_id: u64,
+    pub media_id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub season_title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub evaluate: String,
+    /// Main episodes.
+    pub episodes: Vec<Episode>,
+    /// Extra sections, e.g. PVs.
+    #[serde(default)]
+    pub section: Vec<Section>,
+    #[serde(default)]
+    pub stat: SeasonStat,
+    #[serde(default)]
+    pub rating: Option<Rating>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A section of episodes.
+pub struct Section {
+    pub id: u64,
+    pub title: String,
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An episode.
+pub struct Episode {
+    /// The `ep` id.
+    pub id: u64,
+    pub aid: u64,
+    #[serde(default)]
+    pub bvid: String,
+    pub cid: u64,
+    /// Index, e.g. `1`.
+    pub title: String,
+    #[serde(default)]
+    pub long_title: String,
+    pub cover: String,
+    /// Milliseconds.
+    #[serde(default)]
+    pub duration: u64,
+    /// Badge text, e.g. `会员`.
+    #[serde(default)]
+    pub badge: String,
+    #[serde(default)]
+    pub badge_info: BadgeInfo,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Style of an episode badge.
+pub struct BadgeInfo {
+    pub text: String,
+    pub bg_color: String,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+/// Statistics of a season.
+pub struct SeasonStat {
+    pub views: u64,
+    pub favorites: u64,
+    pub coins: u64,
+    pub danmakus: u64,
+    pub likes: u64,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// Rating of a season.
+pub struct Rating {
+    pub score: f64,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Episodes of a season grouped by section.
+pub struct SeasonSections {
+    pub main_section: Section,
+    #[serde(default)]
+    pub section: Vec<Section>,
+}
+
+/// Get the season information including its episodes.
+pub async fn get_season_info(client: &BiliClient, query: SeasonQuery) -> Result<SeasonInfo> {
+    let query = match query {
+        SeasonQuery::Season(id) => [("season_id", id)],
+        SeasonQuery::Episode(id) => [("ep_id", id)],
+    };
+    client.get(consts::SEASON, &query).await
+}
+
+/// Get episodes of the season grouped by section.
+pub async fn get_section_episodes(client: &BiliClient, season_id: u64) -> Result<SeasonSections> {
+    client
+        .get(consts::SECTION, &[("season_id", season_id)])
+        .await
+}
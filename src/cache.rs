@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{Duration, Instant};
+
+use crate::live::consts as live;
+
+/// A store of api responses, keyed by the request url with query.
+///
+/// Keys do not include the cookies, only cache endpoints whose responses do not depend on
+/// the account.
+pub trait Cache: Send + Sync {
+    /// The body stored for `key`, if not expired.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Store `body` for `key`, expiring after `ttl`.
+    fn insert(&self, key: &str, body: String, ttl: Duration);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// Which endpoints, by url without query, are cached for how long.
+pub struct CacheConfig {
+    ttls: HashMap<String, Duration>,
+}
+
+impl Default for CacheConfig {
+    /// Room info for 10 seconds, the gift config and area list for an hour.
+    fn default() -> Self {
+        Self::empty()
+            .ttl(live::ROOM_INIT, Duration::from_secs(10))
+            .ttl(live::GIFT_CONFIG, Duration::from_secs(3600))
+            .ttl(live::AREA_LIST, Duration::from_secs(3600))
+    }
+}
+
+impl CacheConfig {
+    /// Cache nothing.
+    pub fn empty() -> Self {
+        Self {
+            ttls: HashMap::new(),
+        }
+    }
+
+    /// Cache successful GET responses of `endpoint` for `ttl`.
+    pub fn ttl(mut self, endpoint: &str, ttl: Duration) -> Self {
+        self.ttls.insert(endpoint.to_string(), ttl);
+        self
+    }
+
+    /// How long responses of `endpoint` are cached, if at all.
+    pub fn get(&self, endpoint: &str) -> Option<Duration> {
+        self.ttls.get(endpoint).copied()
+    }
+}
+
+/// A cache set on a client.
+#[derive(Clone)]
+pub(crate) struct ResponseCache {
+    pub(crate) store: Arc<dyn Cache>,
+    pub(crate) config: CacheConfig,
+}
+
+impl std::fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Entries kept before expired ones are purged on insert.
+const PURGE_THRESHOLD: usize = 1024;
+
+#[derive(Debug, Default)]
+/// A [`Cache`] in memory.
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl MemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((body, expires)) if Instant::now() < *expires => Some(body.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(&self, key: &str, body: String, ttl: Duration) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= PURGE_THRESHOLD {
+            entries.retain(|_, (_, expires)| now < *expires);
+        }
+        entries.insert(key.to_string(), (body, now + ttl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BiliClient, MockTransport};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_cache() {
+        let transport = MockTransport::new().json(
+            live::AREA_LIST,
+            json!({"code": 0, "data": [{"id": 1, "name": "娱乐", "list": []}]}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        client.set_cache(MemoryCache::new(), CacheConfig::default());
+        let areas = crate::live::get_area_list(&client).await.unwrap();
+        assert_eq!(
+            crate::live::get_area_list(&client).await.unwrap()[0].name,
+            areas[0].name
+        );
+        assert_eq!(transport.requests().len(), 1);
+
+        client.disable_cache();
+        crate::live::get_area_list(&client).await.unwrap();
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
@@ -0,0 +1,70 @@
+//! Serde helpers for inconsistent api responses.
+
+/// Some apis return numbers as strings, e.g. area ids.
+pub mod string_or_number {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(u64),
+    }
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(s) => s.parse().map_err(serde::de::Error::custom),
+            StringOrNumber::Number(n) => Ok(n),
+        }
+    }
+}
+
+/// Some apis return `null` instead of an empty list or object.
+pub fn null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de> + Default,
+{
+    use serde::Deserialize;
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Counters which are `"--"` or another placeholder when hidden, as `0`.
+pub fn number_or_hidden<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrHidden {
+        Number(u64),
+        Hidden(serde::de::IgnoredAny),
+    }
+
+    Ok(match NumberOrHidden::deserialize(deserializer)? {
+        NumberOrHidden::Number(n) => n,
+        NumberOrHidden::Hidden(_) => 0,
+    })
+}
+
+/// Decimals sometimes given as strings, e.g. `"1.5"`.
+pub fn string_or_f64<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrFloat {
+        String(String),
+        Float(f64),
+    }
+
+    match StringOrFloat::deserialize(deserializer)? {
+        StringOrFloat::String(s) => s.parse().map_err(serde::de::Error::custom),
+        StringOrFloat::Float(f) => Ok(f),
+    }
+}
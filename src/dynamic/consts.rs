@@ -0,0 +1,6 @@
+pub const SPACE_HISTORY: &str =
+    "https://api.vc.bilibili.com/dynamic_svr/v1/dynamic_svr/space_history";
+pub const DYNAMIC_NEW: &str = "https://api.vc.bilibili.com/dynamic_svr/v1/dynamic_svr/dynamic_new";
+pub const CREATE: &str = "https://api.vc.bilibili.com/dynamic_svr/v1/dynamic_svr/create";
+pub const CREATE_DRAW: &str = "https://api.vc.bilibili.com/dynamic_svr/v1/dynamic_svr/create_draw";
+pub const UPLOAD_BFS: &str = "https://api.bilibili.com/x/dynamic/feed/draw/upload_bfs";
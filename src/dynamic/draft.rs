@@ -0,0 +1,173 @@
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// An image uploaded by [`upload_image`].
+pub struct UploadedImage {
+    pub image_url: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    /// Size in KB.
+    #[serde(default)]
+    pub img_size: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+/// Content of a dynamic to publish, built piece by piece.
+pub struct DynamicDraft {
+    content: String,
+    /// `(uid, offset, length)` of mentions, in UTF-16 units as the web client counts.
+    mentions: Vec<(u64, usize, usize)>,
+    images: Vec<UploadedImage>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Result of publishing a dynamic.
+pub struct CreatedDynamic {
+    pub dynamic_id: u64,
+}
+
+impl DynamicDraft {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append plain text.
+    pub fn text(mut self, text: &str) -> Self {
+        self.content.push_str(text);
+        self
+    }
+
+    /// Append a mention of the user, which notifies them.
+    pub fn at(mut self, uid: u64, name: &str) -> Self {
+        let mention = format!("@{} ", name);
+        let offset = self.content.encode_utf16().count();
+        self.mentions
+            .push((uid, offset, mention.encode_utf16().count()));
+        self.content.push_str(&mention);
+        self
+    }
+
+    /// Append a topic, e.g. `#topic#`.
+    pub fn topic(mut self, topic: &str) -> Self {
+        self.content.push('#');
+        self.content.push_str(topic);
+        self.content.push('#');
+        self
+    }
+
+    /// Attach an image, turning the dynamic into one with images.
+    pub fn image(mut self, image: UploadedImage) -> Self {
+        self.images.push(image);
+        self
+    }
+
+    fn at_uids(&self) -> String {
+        self.mentions
+            .iter()
+            .map(|(uid, _, _)| uid.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn ctrl(&self) -> String {
+        let ctrl: Vec<_> = self
+            .mentions
+            .iter()
+            .map(|(uid, location, length)| {
+                json!({
+                    "location": location,
+                    "type": 1,
+                    "length": length,
+                    "data": uid.to_string(),
+                })
+            })
+            .collect();
+        serde_json::Value::from(ctrl).to_string()
+    }
+}
+
+/// Upload an image to be attached to a dynamic.
+pub async fn upload_image(
+    client: &BiliClient,
+    image: Vec<u8>,
+    file_name: &str,
+) -> Result<UploadedImage> {
+    let form = Form::new()
+        .part(
+            "file_up",
+            Part::bytes(image).file_name(file_name.to_string()),
+        )
+        .text("biz", "new_dyn")
+        .text("category", "daily")
+        .text("csrf", client.csrf()?);
+    client.post_multipart(consts::UPLOAD_BFS, form).await
+}
+
+/// Publish a dynamic.
+pub async fn create(client: &BiliClient, draft: DynamicDraft) -> Result<CreatedDynamic> {
+    let csrf = client.csrf()?;
+    if draft.images.is_empty() {
+        let form = [
+            ("dynamic_id", "0".to_string()),
+            ("type", "4".to_string()),
+            ("rid", "0".to_string()),
+            ("content", draft.content.clone()),
+            ("at_uids", draft.at_uids()),
+            ("ctrl", draft.ctrl()),
+            ("csrf_token", csrf.clone()),
+            ("csrf", csrf),
+        ];
+        client.post_form(consts::CREATE, &form).await
+    } else {
+        let pictures: Vec<_> = draft
+            .images
+            .iter()
+            .map(|image| {
+                json!({
+                    "img_src": image.image_url,
+                    "img_width": image.image_width,
+                    "img_height": image.image_height,
+                    "img_size": image.img_size,
+                })
+            })
+            .collect();
+        let form = [
+            ("biz", "3".to_string()),
+            ("category", "3".to_string()),
+            ("type", "0".to_string()),
+            ("pictures", serde_json::Value::from(pictures).to_string()),
+            ("description", draft.content.clone()),
+            ("content", draft.content.clone()),
+            ("at_uids", draft.at_uids()),
+            ("at_control", draft.ctrl()),
+            ("setting", r#"{"copy_forbidden":0}"#.to_string()),
+            ("csrf_token", csrf.clone()),
+            ("csrf", csrf),
+        ];
+        client.post_form(consts::CREATE_DRAW, &form).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draft_mentions() {
+        let draft = DynamicDraft::new()
+            .text("你好 ")
+            .at(10086, "someone")
+            .topic("话题");
+        assert_eq!(draft.content, "你好 @someone #话题#");
+        assert_eq!(draft.at_uids(), "10086");
+        assert_eq!(
+            draft.ctrl(),
+            r#"[{"data":"10086","length":9,"location":3,"type":1}]"#
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Dynamic (feed) APIs.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{BiliClient, Paginated, Result};
+
+pub mod consts;
+mod draft;
+
+pub use draft::{create, upload_image, CreatedDynamic, DynamicDraft, UploadedImage};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A dynamic with its decoded card.
+pub struct Dynamic {
+    pub desc: DynamicDesc,
+    pub card: DynamicCard,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Metadata of a dynamic.
+pub struct DynamicDesc {
+    pub dynamic_id: u64,
+    /// Type of the card, see [`DynamicCard`].
+    pub r#type: u32,
+    pub uid: u64,
+    pub timestamp: i64,
+    #[serde(default)]
+    pub view: u64,
+    #[serde(default)]
+    pub like: u64,
+    #[serde(default)]
+    pub repost: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Content of a dynamic, decoded from the stringified `card` by its type.
+pub enum DynamicCard {
+    /// Type `1`.
+    Forward(ForwardCard),
+    /// Type `2`.
+    Draw(DrawCard),
+    /// Type `4`.
+    Text(TextCard),
+    /// Type `8`.
+    Video(VideoCard),
+    /// Type `64`.
+    Article(ArticleCard),
+    /// Type `4200` and `4308`.
+    LiveShare(LiveShareCard),
+    /// Other types, kept as json.
+    Other { r#type: u32, card: Value },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Author info embedded in cards.
+pub struct CardUser {
+    pub uid: u64,
+    #[serde(alias = "uname")]
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A forwarded dynamic.
+pub struct ForwardCard {
+    pub user: CardUser,
+    pub content: String,
+    pub orig_dy_id: u64,
+    /// The forwarded card, `None` if it was deleted.
+    pub origin: Option<Box<DynamicCard>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A dynamic with images.
+pub struct DrawCard {
+    pub user: CardUser,
+    pub description: String,
+    pub pictures: Vec<Picture>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An image of a dynamic.
+pub struct Picture {
+    pub img_src: String,
+    #[serde(default)]
+    pub img_width: u32,
+    #[serde(default)]
+    pub img_height: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A plain text dynamic.
+pub struct TextCard {
+    pub user: CardUser,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A video upload.
+pub struct VideoCard {
+    pub aid: u64,
+    #[serde(default)]
+    pub bvid: String,
+    pub title: String,
+    #[serde(default)]
+    pub desc: String,
+    pub pic: String,
+    /// Seconds.
+    pub duration: u64,
+    pub owner: VideoOwner,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Uploader of a video.
+pub struct VideoOwner {
+    pub mid: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An article.
+pub struct ArticleCard {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub image_urls: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A shared living room.
+pub struct LiveShareCard {
+    pub room_id: u64,
+    pub title: String,
+    pub cover: String,
+    pub live_status: u8,
+}
+
+#[derive(Deserialize)]
+struct ItemCard<T> {
+    item: T,
+    user: CardUser,
+}
+
+#[derive(Deserialize)]
+struct ForwardItem {
+    content: String,
+    orig_dy_id: u64,
+    orig_type: u32,
+}
+
+#[derive(Deserialize)]
+struct DrawItem {
+    description: String,
+    pictures: Vec<Picture>,
+}
+
+#[derive(Deserialize)]
+struct TextItem {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct RawForwardCard {
+    item: ForwardItem,
+    user: CardUser,
+    #[serde(default)]
+    origin: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawLiveCard {
+    #[serde(alias = "room_id")]
+    roomid: u64,
+    title: String,
+    cover: String,
+    live_status: u8,
+}
+
+#[derive(Deserialize)]
+struct LivePlayCard {
+    live_play_info: RawLiveCard,
+}
+
+impl DynamicCard {
+    /// Decode the stringified card by its type.
+    pub fn parse(r#type: u32, card: &str) -> Result<Self> {
+        Ok(match r#type {
+            1 => {
+                let raw: RawForwardCard = serde_json::from_str(card)?;
+                let origin = match raw.origin {
+                    Some(origin) => Some(Box::new(Self::parse(raw.item.orig_type, &origin)?)),
+                    None => None,
+                };
+                DynamicCard::Forward(ForwardCard {
+                    user: raw.user,
+                    content: raw.item.content,
+                    orig_dy_id: raw.item.orig_dy_id,
+                    origin,
+                })
+            }
+            2 => {
+                let raw: ItemCard<DrawItem> = serde_json::from_str(card)?;
+                DynamicCard::Draw(DrawCard {
+                    user: raw.user,
+                    description: raw.item.description,
+                    pictures: raw.item.pictures,
+                })
+            }
+            4 => {
+                let raw: ItemCard<TextItem> = serde_json::from_str(card)?;
+                DynamicCard::Text(TextCard {
+                    user: raw.user,
+                    content: raw.item.content,
+                })
+            }
+            8 => DynamicCard::Video(serde_json::from_str(card)?),
+            64 => DynamicCard::Article(serde_json::from_str(card)?),
+            4200 | 4308 => {
+                let raw = if r#type == 4200 {
+                    serde_json::from_str::<RawLiveCard>(card)?
+                } else {
+                    serde_json::from_str::<LivePlayCard>(card)?.live_play_info
+                };
+                DynamicCard::LiveShare(LiveShareCard {
+                    room_id: raw.roomid,
+                    title: raw.title,
+                    cover: raw.cover,
+                    live_status: raw.live_status,
+                })
+            }
+            r#type => DynamicCard::Other {
+                r#type,
+                card: serde_json::from_str(card)?,
+            },
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct RawDynamic {
+    desc: DynamicDesc,
+    card: String,
+}
+
+#[derive(Deserialize)]
+struct RawDynamicPage {
+    #[serde(default)]
+    cards: Vec<RawDynamic>,
+    #[serde(default)]
+    has_more: u8,
+    #[serde(default)]
+    next_offset: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of dynamics.
+pub struct DynamicPage {
+    pub cards: Vec<Dynamic>,
+    pub has_more: bool,
+    /// Pass to the next call to get the next page.
+    pub next_offset: u64,
+}
+
+fn decode_cards(cards: Vec<RawDynamic>) -> Result<Vec<Dynamic>> {
+    cards
+        .into_iter()
+        .map(|raw| {
+            Ok(Dynamic {
+                card: DynamicCard::parse(raw.desc.r#type, &raw.card)?,
+                desc: raw.desc,
+            })
+        })
+        .collect()
+}
+
+/// Get dynamics posted by the user, `offset` `0` for the latest.
+pub async fn get_user_dynamics(client: &BiliClient, uid: u64, offset: u64) -> Result<DynamicPage> {
+    let raw: RawDynamicPage = client
+        .get(
+            consts::SPACE_HISTORY,
+            &[("host_uid", uid), ("offset_dynamic_id", offset)],
+        )
+        .await?;
+    Ok(DynamicPage {
+        cards: decode_cards(raw.cards)?,
+        has_more: raw.has_more == 1,
+        next_offset: raw.next_offset,
+    })
+}
+
+/// Walk the dynamics posted by the user from the latest, see [`get_user_dynamics`].
+pub fn paginate_user_dynamics(client: &BiliClient, uid: u64) -> Paginated<Dynamic> {
+    let client = client.clone();
+    Paginated::by_offset(0, move |offset| {
+        let client = client.clone();
+        async move {
+            let page = get_user_dynamics(&client, uid, offset).await?;
+            let next = page.has_more.then_some(page.next_offset);
+            Ok((page.cards, next))
+        }
+    })
+}
+
+/// Get the latest dynamics of users the account follows.
+pub async fn get_timeline(client: &BiliClient) -> Result<Vec<Dynamic>> {
+    let raw: RawDynamicPage = client
+        .get(consts::DYNAMIC_NEW, &[("type_list", 268435455)])
+        .await?;
+    decode_cards(raw.cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forward() {
+        let origin = serde_json::json!({
+            "item": {"content": "hello"},
+            "user": {"uid": 2, "uname": "origin"},
+        })
+        .to_string();
+        let card = serde_json::json!({
+            "item": {"content": "forwarded", "orig_dy_id": 100, "orig_type": 4},
+            "user": {"uid": 1, "uname": "someone"},
+            "origin": origin,
+        })
+        .to_string();
+        match DynamicCard::parse(1, &card).unwrap() {
+            DynamicCard::Forward(forward) => {
+                assert_eq!(forward.content, "forwarded");
+                match *forward.origin.unwrap() {
+                    DynamicCard::Text(text) => assert_eq!(text.content, "hello"),
+                    other => panic!("unexpected origin: {:?}", other),
+                }
+            }
+            other => panic!("unexpected card: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_paginate_user_dynamics() {
+        let card = serde_json::json!({"item": {"content": "hello"}, "user": {"uid": 1, "uname": "someone"}});
+        let transport = crate::MockTransport::new().json(
+            consts::SPACE_HISTORY,
+            serde_json::json!({"code": 0, "data": {
+                "cards": [{
+                    "desc": {"dynamic_id": 100, "type": 4, "uid": 1, "timestamp": 1600000000},
+                    "card": card.to_string(),
+                }],
+                "has_more": 1,
+                "next_offset": 42,
+            }}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let mut dynamics = paginate_user_dynamics(&client, 1);
+        let page = dynamics.next_page().await.unwrap().unwrap();
+        assert_eq!(page[0].desc.dynamic_id, 100);
+        assert_eq!(dynamics.next_cursor(), Some(42));
+        dynamics.next_page().await.unwrap().unwrap();
+        let requests = transport.requests();
+        assert!(requests[1]
+            .query()
+            .unwrap()
+            .contains("offset_dynamic_id=42"));
+    }
+}
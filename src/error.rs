@@ -1,20 +1,211 @@
-use crate::live::ws::WsPacket;
+use crate::live::ws::{ProtoVer, WsPacket};
 use thiserror::Error;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug, Error)]
+/// Variants depend on the enabled features, e.g. `WebSocket` on `native`, so the
+/// enum is non-exhaustive.
+#[non_exhaustive]
 pub enum Error {
     #[error("error occurred while make HTTP request: {0:?}")]
     Reqwest(#[from] reqwest::Error),
     #[error("error occurred when serializing/deserializing json: {0:?}")]
     SerdeJson(#[from] serde_json::Error),
+    #[cfg(feature = "native")]
     #[error("error occurred in WebSocket: {0:?}")]
-    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
     #[error("error occurred while decoding ws packet: {0:?}")]
     WsDecode(#[from] deku::DekuError),
     #[error("error occurred while uncompressing ws packet: {0:?}")]
     Zlib(std::io::Error),
+    #[error("packet body of protocol version {proto_ver:?} is not json")]
+    UnsupportedBody { proto_ver: ProtoVer },
+    #[error("io error: {0:?}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to decode response of {endpoint}: {source}")]
+    Decode {
+        endpoint: String,
+        /// The response text, truncated to [`Error::DECODE_BODY_LIMIT`] bytes.
+        body: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("server responded with HTTP status {status}")]
+    Status {
+        status: u16,
+        /// Wait the server asked for with `Retry-After`.
+        retry_after: Option<std::time::Duration>,
+    },
+    #[error("bilibili api returned error code {raw} ({code:?}): {message}")]
+    Api {
+        code: ErrorCode,
+        /// The code as returned, which `code` may not tell apart, e.g. `-799` from `-509`.
+        raw: i64,
+        message: String,
+    },
+    /// Rejected by risk control (`-352`), pass the captcha of
+    /// [`register_risk_control`](crate::auth::register_risk_control) and retry.
+    ///
+    /// Only a `-352` without a voucher is an [`Error::Api`], check both with
+    /// [`Error::code`] or [`Error::is_rate_limited`].
+    #[error("rejected by risk control, captcha of voucher {v_voucher} required")]
+    RiskControl { v_voucher: String },
+    #[error("session has no {0}, login required")]
+    MissingCredential(&'static str),
+    #[cfg(feature = "grpc")]
+    #[error("gRPC call failed with status {status}: {message}")]
+    Grpc { status: i32, message: String },
+    #[error("unexpected response: {0}")]
+    UnexpectedResponse(String),
     #[error("no available packet consumer")]
     Consumer(#[from] tokio::sync::broadcast::error::SendError<WsPacket>),
 }
+
+impl Error {
+    /// Bytes of the response body kept in [`Error::Decode`].
+    pub const DECODE_BODY_LIMIT: usize = 1024;
+
+    /// The code of an error returned by the api, including [`Error::RiskControl`].
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Api { code, .. } => Some(*code),
+            Error::RiskControl { .. } => Some(ErrorCode::RiskControl),
+            _ => None,
+        }
+    }
+
+    /// Whether the api rejected the request by risk control or rate limiting, see
+    /// [`ErrorCode::is_rate_limited`].
+    pub fn is_rate_limited(&self) -> bool {
+        self.code().is_some_and(|code| code.is_rate_limited())
+    }
+
+    pub(crate) fn decode(endpoint: &str, body: &str, source: serde_json::Error) -> Self {
+        let mut end = body.len().min(Self::DECODE_BODY_LIMIT);
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        Error::Decode {
+            endpoint: endpoint.to_string(),
+            body: body[..end].to_string(),
+            source,
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Known bilibili API error codes.
+pub enum ErrorCode {
+    /// `-1`
+    AppNotExist,
+    /// `-101`
+    NotLoggedIn,
+    /// `-102`
+    AccountBanned,
+    /// `-111`
+    CsrfFailed,
+    /// `-352`
+    RiskControl,
+    /// `-400`
+    BadRequest,
+    /// `-403`
+    AccessDenied,
+    /// `-404`
+    NotFound,
+    /// `-412`
+    RateLimited,
+    /// `-500`
+    ServerError,
+    /// `-503`
+    Overloaded,
+    /// `-504`
+    ServiceTimeout,
+    /// `-509`, `-799`
+    TooFrequent,
+    /// `60004`, `19002003`
+    RoomNotExist,
+    /// Any other code.
+    Other(i64),
+}
+
+impl ErrorCode {
+    pub fn from_i64(code: i64) -> Self {
+        match code {
+            -1 => ErrorCode::AppNotExist,
+            -101 => ErrorCode::NotLoggedIn,
+            -102 => ErrorCode::AccountBanned,
+            -111 => ErrorCode::CsrfFailed,
+            -352 => ErrorCode::RiskControl,
+            -400 => ErrorCode::BadRequest,
+            -403 => ErrorCode::AccessDenied,
+            -404 => ErrorCode::NotFound,
+            -412 => ErrorCode::RateLimited,
+            -500 => ErrorCode::ServerError,
+            -503 => ErrorCode::Overloaded,
+            -504 => ErrorCode::ServiceTimeout,
+            -509 | -799 => ErrorCode::TooFrequent,
+            60004 | 19002003 => ErrorCode::RoomNotExist,
+            code => ErrorCode::Other(code),
+        }
+    }
+
+    /// Get the raw code, codes sharing a variant map to the first one listed, see
+    /// [`Error::Api`] for the code as returned.
+    pub fn code(&self) -> i64 {
+        match self {
+            ErrorCode::AppNotExist => -1,
+            ErrorCode::NotLoggedIn => -101,
+            ErrorCode::AccountBanned => -102,
+            ErrorCode::CsrfFailed => -111,
+            ErrorCode::RiskControl => -352,
+            ErrorCode::BadRequest => -400,
+            ErrorCode::AccessDenied => -403,
+            ErrorCode::NotFound => -404,
+            ErrorCode::RateLimited => -412,
+            ErrorCode::ServerError => -500,
+            ErrorCode::Overloaded => -503,
+            ErrorCode::ServiceTimeout => -504,
+            ErrorCode::TooFrequent => -509,
+            ErrorCode::RoomNotExist => 60004,
+            ErrorCode::Other(code) => *code,
+        }
+    }
+
+    /// The credentials are missing, expired or invalid.
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::NotLoggedIn | ErrorCode::AccountBanned | ErrorCode::CsrfFailed
+        )
+    }
+
+    /// The request was rejected by risk control or rate limiting, retry later.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::RiskControl | ErrorCode::RateLimited | ErrorCode::TooFrequent
+        )
+    }
+
+    /// The server failed temporarily.
+    pub fn is_server_error(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::ServerError | ErrorCode::Overloaded | ErrorCode::ServiceTimeout
+        )
+    }
+}
+
+impl From<i64> for ErrorCode {
+    fn from(code: i64) -> Self {
+        ErrorCode::from_i64(code)
+    }
+}
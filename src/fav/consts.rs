@@ -0,0 +1,3 @@
+pub const FOLDERS: &str = "https://api.bilibili.com/x/v3/fav/folder/created/list-all";
+pub const FOLDER_CONTENT: &str = "https://api.bilibili.com/x/v3/fav/resource/list";
+pub const DEAL: &str = "https://api.bilibili.com/x/v3/fav/resource/deal";
@@ -0,0 +1,125 @@
+//! Favorites (favlist) APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Paginated, Result};
+
+pub mod consts;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Folders created by a user.
+pub struct FolderList {
+    pub count: u64,
+    pub list: Vec<Folder>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A favorites folder.
+pub struct Folder {
+    /// The `media_id` of the folder.
+    pub id: u64,
+    pub fid: u64,
+    pub mid: u64,
+    pub title: String,
+    pub media_count: u64,
+    /// Bit `0` set means private.
+    #[serde(default)]
+    pub attr: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of resources in a folder.
+pub struct FolderContent {
+    pub info: FolderInfo,
+    /// `None` if the folder is empty.
+    #[serde(default)]
+    pub medias: Option<Vec<FavMedia>>,
+    pub has_more: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Detail of a folder.
+pub struct FolderInfo {
+    pub id: u64,
+    pub title: String,
+    #[serde(default)]
+    pub cover: String,
+    pub media_count: u64,
+    pub upper: FavUpper,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Owner of a folder or uploader of a resource.
+pub struct FavUpper {
+    pub mid: u64,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A resource in a folder, usually a video.
+pub struct FavMedia {
+    /// The aid for videos.
+    pub id: u64,
+    /// `2` for videos.
+    pub r#type: u32,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub intro: String,
+    /// Seconds.
+    pub duration: u64,
+    pub upper: FavUpper,
+    #[serde(default)]
+    pub bvid: String,
+    pub fav_time: i64,
+    pub pubtime: i64,
+}
+
+/// Get all folders created by the user.
+pub async fn get_folders(client: &BiliClient, uid: u64) -> Result<FolderList> {
+    client.get(consts::FOLDERS, &[("up_mid", uid)]).await
+}
+
+/// Get a page, starting from `1`, of resources in the folder.
+pub async fn get_folder_content(
+    client: &BiliClient,
+    media_id: u64,
+    page: u64,
+) -> Result<FolderContent> {
+    client
+        .get(
+            consts::FOLDER_CONTENT,
+            &[("media_id", media_id), ("pn", page), ("ps", 20)],
+        )
+        .await
+}
+
+/// Walk the resources in the folder page by page, see [`get_folder_content`].
+pub fn paginate_folder_content(client: &BiliClient, media_id: u64) -> Paginated<FavMedia> {
+    let client = client.clone();
+    Paginated::by_page(move |page| {
+        let client = client.clone();
+        async move {
+            let content = get_folder_content(&client, media_id, page).await?;
+            Ok((content.medias.unwrap_or_default(), content.has_more))
+        }
+    })
+}
+
+/// Add the video `rid` (aid) into folders `add_ids` and remove it from folders `del_ids`.
+pub async fn deal(client: &BiliClient, rid: u64, add_ids: &[u64], del_ids: &[u64]) -> Result<()> {
+    fn join(ids: &[u64]) -> String {
+        ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",")
+    }
+    client
+        .post_action(
+            consts::DEAL,
+            &[
+                ("rid", rid.to_string()),
+                ("type", "2".to_string()),
+                ("add_media_ids", join(add_ids)),
+                ("del_media_ids", join(del_ids)),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
@@ -0,0 +1,4 @@
+pub const HOST: &str = "https://grpc.biliapi.net";
+pub const VIEW: &str = "/bilibili.app.view.v1.View/View";
+pub const PLAY_VIEW: &str = "/bilibili.app.playurl.v1.PlayURL/PlayView";
+pub const DM_SEG_MOBILE: &str = "/bilibili.community.service.dm.v1.DM/DmSegMobile";
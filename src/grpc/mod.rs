@@ -0,0 +1,209 @@
+//! Calls to the gRPC APIs of the mobile app, see [`ClientBackend::App`].
+//!
+//! Messages are sent through reqwest, which negotiates HTTP/2 by ALPN; as reqwest can not
+//! read trailers, a call is only known to fail when the status comes with the headers.
+//!
+//! [`ClientBackend::App`]: crate::ClientBackend::App
+use std::io::Read;
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use flate2::read::GzDecoder;
+use prost::Message;
+use reqwest::Method;
+
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+pub(crate) mod consts;
+
+const MOBI_APP: &str = "android";
+const PLATFORM: &str = "android";
+const CHANNEL: &str = "bili";
+/// Build number of the app, 7.38.0.
+const BUILD: i32 = 7380300;
+/// The app id of the Android app.
+const APP_ID: i32 = 1;
+const USER_AGENT: &str = "Dalvik/2.1.0 (Linux; U; Android 12) 7.38.0 os/android \
+    mobi_app/android build/7380300 channel/bili innerVer/7380300 osVer/12 network/2 \
+    grpc-java-cronet/1.36.1";
+
+#[derive(Clone, PartialEq, Message)]
+/// `bilibili.metadata.Metadata`, sent as `x-bili-metadata-bin`.
+struct Metadata {
+    #[prost(string, tag = "1")]
+    access_key: String,
+    #[prost(string, tag = "2")]
+    mobi_app: String,
+    #[prost(string, tag = "3")]
+    device: String,
+    #[prost(int32, tag = "4")]
+    build: i32,
+    #[prost(string, tag = "5")]
+    channel: String,
+    #[prost(string, tag = "6")]
+    buvid: String,
+    #[prost(string, tag = "7")]
+    platform: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+/// `bilibili.metadata.device.Device`, sent as `x-bili-device-bin`.
+struct Device {
+    #[prost(int32, tag = "1")]
+    app_id: i32,
+    #[prost(int32, tag = "2")]
+    build: i32,
+    #[prost(string, tag = "3")]
+    buvid: String,
+    #[prost(string, tag = "4")]
+    mobi_app: String,
+    #[prost(string, tag = "5")]
+    platform: String,
+    #[prost(string, tag = "7")]
+    channel: String,
+}
+
+/// Headers identifying the app and the account, if logged in by the app.
+fn metadata_headers(client: &BiliClient) -> Vec<(&'static str, String)> {
+    let access_key = client
+        .session()
+        .and_then(|session| session.access_key)
+        .unwrap_or_default();
+    let buvid = client
+        .fingerprint()
+        .map(|fingerprint| fingerprint.buvid3)
+        .unwrap_or_default();
+    let metadata = Metadata {
+        access_key: access_key.clone(),
+        mobi_app: MOBI_APP.to_string(),
+        device: String::new(),
+        build: BUILD,
+        channel: CHANNEL.to_string(),
+        buvid: buvid.clone(),
+        platform: PLATFORM.to_string(),
+    };
+    let device = Device {
+        app_id: APP_ID,
+        build: BUILD,
+        buvid: buvid.clone(),
+        mobi_app: MOBI_APP.to_string(),
+        platform: PLATFORM.to_string(),
+        channel: CHANNEL.to_string(),
+    };
+    let mut headers = vec![
+        (
+            "x-bili-metadata-bin",
+            STANDARD_NO_PAD.encode(metadata.encode_to_vec()),
+        ),
+        (
+            "x-bili-device-bin",
+            STANDARD_NO_PAD.encode(device.encode_to_vec()),
+        ),
+        ("buvid", buvid),
+    ];
+    if !access_key.is_empty() {
+        headers.push(("authorization", format!("identify_v1 {}", access_key)));
+    }
+    headers
+}
+
+/// Prefix a message with the uncompressed flag and its length.
+fn encode_frame<M: Message>(message: &M) -> Vec<u8> {
+    let len = message.encoded_len();
+    let mut frame = Vec::with_capacity(5 + len);
+    frame.push(0);
+    frame.extend_from_slice(&(len as u32).to_be_bytes());
+    message.encode(&mut frame).expect("a vec grows as needed");
+    frame
+}
+
+/// The message of the first frame, inflated if compressed with gzip.
+fn decode_frame(body: &[u8]) -> Result<Vec<u8>> {
+    let invalid = || Error::UnexpectedResponse("invalid gRPC frame".to_string());
+    if body.len() < 5 {
+        return Err(invalid());
+    }
+    let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+    let message = body.get(5..5 + len).ok_or_else(invalid)?;
+    match body[0] {
+        0 => Ok(message.to_vec()),
+        1 => {
+            let mut inflated = Vec::new();
+            GzDecoder::new(message)
+                .read_to_end(&mut inflated)
+                .map_err(Error::Zlib)?;
+            Ok(inflated)
+        }
+        flag => Err(Error::UnexpectedResponse(format!(
+            "unknown gRPC compression flag {}",
+            flag
+        ))),
+    }
+}
+
+/// Call the unary `method`, e.g. [`consts::VIEW`].
+pub(crate) async fn call<Req, Reply>(
+    client: &BiliClient,
+    method: &str,
+    request: &Req,
+) -> Result<Reply>
+where
+    Req: Message,
+    Reply: Message + Default,
+{
+    let url = format!("{}{}", consts::HOST, method);
+    // no cookies, the app authenticates by the access key
+    let mut builder = client
+        .http()
+        .request(Method::POST, &url)
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .header("grpc-accept-encoding", "identity,gzip")
+        .header("user-agent", USER_AGENT);
+    for (name, value) in metadata_headers(client) {
+        builder = builder.header(name, value);
+    }
+    debug!("gRPC {}", method);
+    let response = client.send(builder.body(encode_frame(request))).await?;
+    let status = response
+        .headers()
+        .get("grpc-status")
+        .and_then(|status| status.to_str().ok()?.parse::<i32>().ok());
+    if let Some(status) = status.filter(|status| *status != 0) {
+        let message = response
+            .headers()
+            .get("grpc-message")
+            .and_then(|message| message.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        return Err(Error::Grpc { status, message });
+    }
+    let response = crate::retry::check_status(response)?;
+    let message = decode_frame(&response.bytes().await?)?;
+    Reply::decode(message.as_slice())
+        .map_err(|e| Error::UnexpectedResponse(format!("invalid reply of {}: {}", method, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame() {
+        let device = Device {
+            app_id: APP_ID,
+            build: BUILD,
+            buvid: "XY".to_string(),
+            mobi_app: MOBI_APP.to_string(),
+            platform: PLATFORM.to_string(),
+            channel: CHANNEL.to_string(),
+        };
+        let frame = encode_frame(&device);
+        assert_eq!(frame[0], 0);
+        assert_eq!(frame.len(), 5 + device.encoded_len());
+        let decoded = Device::decode(decode_frame(&frame).unwrap().as_slice()).unwrap();
+        assert_eq!(decoded, device);
+        assert!(decode_frame(&frame[..frame.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub const HISTORY: &str = "https://api.bilibili.com/x/web-interface/history/cursor";
+pub const TOVIEW: &str = "https://api.bilibili.com/x/v2/history/toview";
+pub const TOVIEW_ADD: &str = "https://api.bilibili.com/x/v2/history/toview/add";
+pub const TOVIEW_DEL: &str = "https://api.bilibili.com/x/v2/history/toview/del";
@@ -0,0 +1,75 @@
+//! Watch history and watch-later APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Result};
+
+pub mod consts;
+pub mod toview;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+/// Position in the history, returned with each page.
+pub struct HistoryCursor {
+    pub max: u64,
+    pub view_at: i64,
+    #[serde(default)]
+    pub business: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of watch history.
+pub struct HistoryPage {
+    /// Pass to [`get`] to get the next page.
+    pub cursor: HistoryCursor,
+    #[serde(default)]
+    pub list: Vec<HistoryEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A watched item.
+pub struct HistoryEntry {
+    pub title: String,
+    #[serde(default)]
+    pub cover: String,
+    pub history: HistoryTarget,
+    #[serde(default)]
+    pub author_name: String,
+    #[serde(default)]
+    pub author_mid: u64,
+    /// Unix timestamp in seconds.
+    pub view_at: i64,
+    /// Seconds watched, `-1` if finished.
+    pub progress: i64,
+    /// Seconds.
+    #[serde(default)]
+    pub duration: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// What was watched.
+pub struct HistoryTarget {
+    pub oid: u64,
+    #[serde(default)]
+    pub epid: u64,
+    #[serde(default)]
+    pub bvid: String,
+    #[serde(default)]
+    pub cid: u64,
+    /// `archive`, `pgc`, `live`, `article` etc.
+    pub business: String,
+}
+
+/// Get a page of watch history, `None` for the latest.
+pub async fn get(client: &BiliClient, cursor: Option<&HistoryCursor>) -> Result<HistoryPage> {
+    let cursor = cursor.cloned().unwrap_or_default();
+    client
+        .get(
+            consts::HISTORY,
+            &[
+                ("max", cursor.max.to_string()),
+                ("view_at", cursor.view_at.to_string()),
+                ("business", cursor.business),
+                ("ps", "20".to_string()),
+            ],
+        )
+        .await
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// The watch-later list.
+pub struct ToViewList {
+    pub count: u64,
+    #[serde(default)]
+    pub list: Vec<ToViewEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A video in the watch-later list.
+pub struct ToViewEntry {
+    pub aid: u64,
+    pub bvid: String,
+    pub cid: u64,
+    pub title: String,
+    pub pic: String,
+    /// Seconds.
+    pub duration: u64,
+    pub owner: ToViewOwner,
+    /// Seconds watched, `-1` if finished.
+    pub progress: i64,
+    pub add_at: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Uploader of a video.
+pub struct ToViewOwner {
+    pub mid: u64,
+    pub name: String,
+}
+
+/// Get the watch-later list.
+pub async fn list(client: &BiliClient) -> Result<ToViewList> {
+    client.get(consts::TOVIEW, &()).await
+}
+
+/// Add a video into the watch-later list.
+pub async fn add(client: &BiliClient, aid: u64) -> Result<()> {
+    client
+        .post_action(
+            consts::TOVIEW_ADD,
+            &[("aid", aid.to_string()), ("csrf", client.csrf()?)],
+        )
+        .await
+}
+
+/// Remove videos from the watch-later list.
+pub async fn remove(client: &BiliClient, aids: &[u64]) -> Result<()> {
+    let aids = aids
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    client
+        .post_action(
+            consts::TOVIEW_DEL,
+            &[("aid", aids), ("csrf", client.csrf()?)],
+        )
+        .await
+}
+
+/// Remove all finished videos from the watch-later list.
+pub async fn remove_viewed(client: &BiliClient) -> Result<()> {
+    client
+        .post_action(
+            consts::TOVIEW_DEL,
+            &[("viewed", "true".to_string()), ("csrf", client.csrf()?)],
+        )
+        .await
+}
@@ -1,14 +1,58 @@
 //! `bili` is a library for interacting
 //! with [bilibili](https://bilibili.com).
 #![doc(html_logo_url = "https://raw.githubusercontent.com/RedCircleProject/bili/master/bili.png")]
+#[cfg(not(feature = "tracing"))]
 #[macro_use]
 extern crate log;
+#[cfg(feature = "tracing")]
+#[macro_use]
+extern crate tracing;
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+pub mod appsign;
+pub mod article;
+pub mod audio;
+pub mod auth;
+pub mod bangumi;
+mod cache;
+pub(crate) mod client;
+mod de;
+pub mod dynamic;
 mod error;
+pub mod fav;
+#[cfg(feature = "grpc")]
+mod grpc;
+pub mod history;
 pub mod live;
-pub use error::{Error, Result};
+pub mod member;
+mod middleware;
+mod net;
+pub mod open_live;
+mod paginate;
+mod preset;
+mod ratelimit;
+pub mod reply;
+mod retry;
+pub mod search;
+mod transport;
+pub mod upload;
+pub mod user;
+pub mod utils;
+pub mod video;
+pub mod wbi;
+pub use appsign::AppCredential;
+pub use cache::{Cache, CacheConfig, MemoryCache};
+pub use client::{BiliClient, ClientBackend, ClientBuilder};
+pub use error::{Error, ErrorCode, Result};
+pub use middleware::Middleware;
+pub use paginate::Paginated;
+pub use preset::HeaderPreset;
+pub use ratelimit::RateLimitConfig;
+pub use retry::RetryPolicy;
+pub use transport::{ApiTransport, MockTransport};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 /// Bilibili API response wrapper
@@ -18,7 +62,12 @@ pub struct ApiResponse<T> {
     code: i64,
     msg: Option<String>,
     message: Option<String>,
+    // pgc apis put the data in `result`
+    #[serde(alias = "result")]
     data: Option<T>,
+    /// Voucher of the captcha to pass when rejected by risk control.
+    #[serde(skip)]
+    v_voucher: Option<String>,
 }
 
 impl<T> ApiResponse<T> {
@@ -48,7 +97,122 @@ impl<T> ApiResponse<T> {
     }
 
     /// Unwrap into the data.
+    ///
+    /// Panics if the request failed, prefer [`ApiResponse::into_result`].
     pub fn into_data(self) -> T {
         self.data.unwrap()
     }
+
+    /// Convert into the data, or [`Error::Api`] if the code is not ok or the data is missing.
+    pub fn into_result(self) -> Result<T> {
+        match self.data {
+            Some(data) if self.code == 0 => Ok(data),
+            _ => Err(self.into_error()),
+        }
+    }
+
+    /// Check the code only, for APIs which carry no data.
+    pub fn into_unit(self) -> Result<()> {
+        if self.ok() {
+            Ok(())
+        } else {
+            Err(self.into_error())
+        }
+    }
+
+    fn into_error(self) -> Error {
+        if let Some(v_voucher) = self.v_voucher {
+            return Error::RiskControl { v_voucher };
+        }
+        Error::Api {
+            code: ErrorCode::from_i64(self.code),
+            raw: self.code,
+            message: self.message.or(self.msg).unwrap_or_default(),
+        }
+    }
+}
+
+impl ApiResponse<Value> {
+    /// Deserialize the data as `T`, failing only when the code is ok.
+    ///
+    /// Failed requests often carry `{}` or `[]` as data, which must not mask the code.
+    pub(crate) fn parse<T: DeserializeOwned>(self) -> serde_json::Result<ApiResponse<T>> {
+        let v_voucher = match &self.data {
+            Some(data) if ErrorCode::from_i64(self.code) == ErrorCode::RiskControl => data
+                .get("v_voucher")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            _ => None,
+        };
+        let data = match self.data {
+            Some(data) if data.is_null() => None,
+            Some(data) if self.code == 0 => Some(serde_json::from_value(data)?),
+            // kept only if it fits, e.g. nav carries the wbi keys with `-101`
+            Some(data) => serde_json::from_value(data).ok(),
+            None => None,
+        };
+        Ok(ApiResponse {
+            code: self.code,
+            msg: self.msg,
+            message: self.message,
+            data,
+            v_voucher,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_result() {
+        let response: ApiResponse<u64> =
+            serde_json::from_str(r#"{"code":60004,"msg":"","message":"直播间不存在","data":null}"#)
+                .unwrap();
+        match response.into_result() {
+            Err(Error::Api { code, raw, message }) => {
+                assert_eq!(code, ErrorCode::RoomNotExist);
+                assert_eq!(code.code(), 60004);
+                assert_eq!(raw, 60004);
+                assert_eq!(message, "直播间不存在");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_ignores_error_data() {
+        let response: ApiResponse<Value> =
+            serde_json::from_str(r#"{"code":-404,"message":"啥都木有","data":{}}"#).unwrap();
+        let response = response.parse::<u64>().unwrap();
+        assert_eq!(response.code(), -404);
+        assert!(response.data().is_none());
+    }
+
+    #[test]
+    fn test_parse_keeps_fitting_error_data() {
+        let response: ApiResponse<Value> = serde_json::from_str(
+            r#"{"code":-101,"message":"账号未登录","data":{"isLogin":false}}"#,
+        )
+        .unwrap();
+        let response = response.parse::<Value>().unwrap();
+        assert_eq!(response.code(), -101);
+        assert_eq!(response.data().unwrap()["isLogin"], false);
+    }
+
+    #[test]
+    fn test_risk_control() {
+        let response: ApiResponse<Value> = serde_json::from_str(
+            r#"{"code":-352,"message":"风控校验失败","data":{"v_voucher":"voucher_1"}}"#,
+        )
+        .unwrap();
+        let error = response.parse::<u64>().unwrap().into_result().unwrap_err();
+        assert_eq!(error.code(), Some(ErrorCode::RiskControl));
+        assert!(error.is_rate_limited());
+        match error {
+            Error::RiskControl { v_voucher } => assert_eq!(v_voucher, "voucher_1"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
 }
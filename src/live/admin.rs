@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How long a user stays banned.
+pub enum BanDuration {
+    /// Until the current live ends.
+    CurrentLive,
+    Hours(u32),
+    Permanent,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Who can still send danmaku in silent mode.
+pub enum SilentMode {
+    /// Users of at least the user level.
+    Level(u32),
+    /// Users wearing the room's medal of at least the level.
+    Medal(u32),
+    /// Nobody but admins.
+    All,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of banned users.
+pub struct BannedList {
+    #[serde(default)]
+    pub data: Vec<BannedUser>,
+    pub total: u64,
+    pub total_page: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A user banned in the room.
+pub struct BannedUser {
+    /// Id of the ban record.
+    pub id: u64,
+    pub tuid: u64,
+    pub tname: String,
+    /// Uid of the admin who banned them.
+    pub uid: u64,
+    /// Name of the admin who banned them.
+    pub name: String,
+    /// e.g. `2021-12-09 12:00:00`.
+    pub ctime: String,
+}
+
+impl BanDuration {
+    fn hours(&self) -> i64 {
+        match self {
+            BanDuration::CurrentLive => 0,
+            BanDuration::Hours(hours) => *hours as i64,
+            BanDuration::Permanent => -1,
+        }
+    }
+}
+
+impl SilentMode {
+    fn as_form(&self) -> (&'static str, u32) {
+        match self {
+            SilentMode::Level(level) => ("level", *level),
+            SilentMode::Medal(level) => ("medal", *level),
+            SilentMode::All => ("member", 0),
+        }
+    }
+}
+
+fn with_csrf(
+    client: &BiliClient,
+    mut form: Vec<(&'static str, String)>,
+) -> Result<Vec<(&'static str, String)>> {
+    let csrf = client.csrf()?;
+    form.push(("csrf_token", csrf.clone()));
+    form.push(("csrf", csrf));
+    Ok(form)
+}
+
+/// Ban the user from sending danmaku in the room.
+pub async fn ban_user(
+    client: &BiliClient,
+    room_id: u64,
+    uid: u64,
+    duration: BanDuration,
+) -> Result<()> {
+    let form = with_csrf(
+        client,
+        vec![
+            ("room_id", room_id.to_string()),
+            ("tuid", uid.to_string()),
+            ("hour", duration.hours().to_string()),
+            ("mobile_app", "web".to_string()),
+        ],
+    )?;
+    client.post_action(consts::ADD_SILENT_USER, &form).await
+}
+
+/// Lift the ban of the user in the room.
+pub async fn unban_user(client: &BiliClient, room_id: u64, uid: u64) -> Result<()> {
+    let form = with_csrf(
+        client,
+        vec![("roomid", room_id.to_string()), ("tuid", uid.to_string())],
+    )?;
+    client.post_action(consts::DEL_SILENT_USER, &form).await
+}
+
+/// Get the users banned in the room, `page` starts from 1.
+pub async fn get_banned_users(client: &BiliClient, room_id: u64, page: u32) -> Result<BannedList> {
+    let form = with_csrf(
+        client,
+        vec![("room_id", room_id.to_string()), ("ps", page.to_string())],
+    )?;
+    client.post_form(consts::SILENT_USER_LIST, &form).await
+}
+
+/// Make the user an admin of the own room.
+pub async fn add_admin(client: &BiliClient, uid: u64) -> Result<()> {
+    let form = with_csrf(client, vec![("admin", uid.to_string())])?;
+    client.post_action(consts::APPOINT_ADMIN, &form).await
+}
+
+/// Remove the user from the admins of the own room.
+pub async fn remove_admin(client: &BiliClient, uid: u64) -> Result<()> {
+    let form = with_csrf(client, vec![("uid", uid.to_string())])?;
+    client.post_action(consts::DISMISS_ADMIN, &form).await
+}
+
+/// Turn on silent mode for `minutes`, `0` until the current live ends.
+pub async fn set_silent(
+    client: &BiliClient,
+    room_id: u64,
+    mode: SilentMode,
+    minutes: u32,
+) -> Result<()> {
+    let (kind, level) = mode.as_form();
+    let form = with_csrf(
+        client,
+        vec![
+            ("room_id", room_id.to_string()),
+            ("type", kind.to_string()),
+            ("level", level.to_string()),
+            ("minute", minutes.to_string()),
+        ],
+    )?;
+    client.post_action(consts::ROOM_SILENT, &form).await
+}
+
+/// Turn off silent mode.
+pub async fn cancel_silent(client: &BiliClient, room_id: u64) -> Result<()> {
+    let form = with_csrf(
+        client,
+        vec![
+            ("room_id", room_id.to_string()),
+            ("type", "off".to_string()),
+            ("level", "0".to_string()),
+            ("minute", "0".to_string()),
+        ],
+    )?;
+    client.post_action(consts::ROOM_SILENT, &form).await
+}
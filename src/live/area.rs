@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A parent area, e.g. `网游`.
+pub struct ParentArea {
+    pub id: u64,
+    pub name: String,
+    pub list: Vec<Area>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A sub area, e.g. `英雄联盟`.
+pub struct Area {
+    #[serde(with = "crate::de::string_or_number")]
+    pub id: u64,
+    #[serde(with = "crate::de::string_or_number")]
+    pub parent_id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub parent_name: String,
+    #[serde(default)]
+    pub pic: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Order of rooms in an area.
+pub enum AreaSort {
+    /// Most watched first.
+    Online,
+    /// Latest started first.
+    LiveTime,
+}
+
+impl AreaSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AreaSort::Online => "online",
+            AreaSort::LiveTime => "live_time",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of rooms in an area.
+pub struct AreaRoomList {
+    pub count: u64,
+    pub list: Vec<AreaRoom>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A living room in the area listing.
+pub struct AreaRoom {
+    pub roomid: u64,
+    pub uid: u64,
+    pub title: String,
+    pub uname: String,
+    pub online: u64,
+    #[serde(default)]
+    pub user_cover: String,
+    #[serde(default)]
+    pub system_cover: String,
+    #[serde(default)]
+    pub face: String,
+    #[serde(default)]
+    pub area_id: u64,
+    #[serde(default)]
+    pub area_name: String,
+}
+
+/// Get all live areas grouped by their parent area.
+pub async fn get_area_list(client: &BiliClient) -> Result<Vec<ParentArea>> {
+    client.get(consts::AREA_LIST, &()).await
+}
+
+/// Get a page, starting from `1`, of living rooms in the area, `area` `0` for the whole parent area.
+pub async fn get_rooms_by_area(
+    client: &BiliClient,
+    parent_area: u64,
+    area: u64,
+    page: u64,
+    sort: AreaSort,
+) -> Result<AreaRoomList> {
+    client
+        .get(
+            consts::AREA_ROOM_LIST,
+            &[
+                ("platform", "web".to_string()),
+                ("parent_area_id", parent_area.to_string()),
+                ("area_id", area.to_string()),
+                ("page", page.to_string()),
+                ("page_size", "30".to_string()),
+                ("sort_type", sort.as_str().to_string()),
+            ],
+        )
+        .await
+}
@@ -0,0 +1,300 @@
+//! Record a room whenever it is living, driven by its live status events.
+use std::path::PathBuf;
+
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::event::{EventReceiver, LiveEvent};
+use super::play_info::{
+    get_room_play_info, LiveCodec, LiveFormat, LiveProtocol, QualityPreference, RoomPlayInfoOptions,
+};
+use super::probe::probe_fastest;
+use super::record::{record, FilenameTemplate, Recorder, RecorderHandle, Rotation};
+use super::state::{LiveStateTracker, RoomState, StateChange};
+use super::{get_room_info, room_init};
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug)]
+/// How an [`AutoRecorder`] records.
+pub struct AutoRecordConfig {
+    /// Where files are written.
+    pub dir: PathBuf,
+    pub template: FilenameTemplate,
+    pub rotation: Rotation,
+    /// Highest quality wanted, see [`QualityPreference::qn`].
+    pub qn: u32,
+    /// Codecs by preference, only flv over http is recorded.
+    pub codecs: Vec<LiveCodec>,
+    /// Wait before fetching the stream again after it ended or failed.
+    pub retry_delay: Duration,
+}
+
+impl AutoRecordConfig {
+    /// Record the original quality in AVC into `dir`, with the default template and no split.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            template: FilenameTemplate::default(),
+            rotation: Rotation::default(),
+            qn: 10000,
+            codecs: vec![LiveCodec::Avc],
+            retry_delay: Duration::from_secs(3),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Capture {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Vec<PathBuf>>,
+}
+
+/// Starts recording a room when it goes live and finalizes the files when it stops.
+///
+/// While living, a broken or ended stream is fetched again after
+/// [`AutoRecordConfig::retry_delay`], continuing in the same file.
+#[derive(Debug)]
+pub struct AutoRecorder {
+    client: BiliClient,
+    room_id: u64,
+    config: AutoRecordConfig,
+    tracker: LiveStateTracker,
+    capture: Option<Capture>,
+    files: Vec<PathBuf>,
+}
+
+impl AutoRecorder {
+    /// Look up the room, starting to record right away if it is living.
+    pub async fn new(client: &BiliClient, room_id: u64, config: AutoRecordConfig) -> Result<Self> {
+        let room = room_init(client, room_id).await?;
+        let mut recorder = Self {
+            client: client.clone(),
+            room_id: room.room_id,
+            config,
+            tracker: LiveStateTracker::from_room_init(&room),
+            capture: None,
+            files: Vec::new(),
+        };
+        if recorder
+            .tracker
+            .state()
+            .is_some_and(|state| state.is_live())
+        {
+            recorder.start();
+        }
+        Ok(recorder)
+    }
+
+    /// The state of the room as last known.
+    pub fn state(&self) -> Option<RoomState> {
+        self.tracker.state()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Files of finished recordings.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Apply an event of the room, starting or stopping the recording when it goes live or
+    /// stops living.
+    pub async fn handle_event(&mut self, event: &LiveEvent) -> Option<StateChange> {
+        let change = self.tracker.update(event)?;
+        if change.to.is_live() {
+            self.start();
+        } else {
+            self.stop().await;
+        }
+        Some(change)
+    }
+
+    /// Handle the events until the receiver ends, then stop, returning all files recorded.
+    pub async fn run(mut self, mut events: EventReceiver) -> Vec<PathBuf> {
+        while let Some(event) = events.recv().await {
+            self.handle_event(&event).await;
+        }
+        self.stop().await;
+        self.files
+    }
+
+    fn start(&mut self) {
+        if self.capture.is_some() {
+            return;
+        }
+        debug!("room {} is living, start recording", self.room_id);
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(capture(
+            self.client.clone(),
+            self.room_id,
+            self.config.clone(),
+            stopped,
+        ));
+        self.capture = Some(Capture { stop, task });
+    }
+
+    /// Stop recording and finalize the files, returning the files of this recording.
+    pub async fn stop(&mut self) -> Vec<PathBuf> {
+        let Some(capture) = self.capture.take() else {
+            return Vec::new();
+        };
+        debug!("stop recording room {}", self.room_id);
+        let _ = capture.stop.send(());
+        match capture.task.await {
+            Ok(files) => {
+                self.files.extend(files.iter().cloned());
+                files
+            }
+            Err(e) => {
+                error!("recording of room {} panicked: {:?}", self.room_id, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// The url of the flv stream which serves the fastest.
+async fn stream_url(
+    client: &BiliClient,
+    room_id: u64,
+    config: &AutoRecordConfig,
+) -> Result<String> {
+    let options = RoomPlayInfoOptions {
+        protocols: vec![LiveProtocol::HttpStream],
+        formats: vec![LiveFormat::Flv],
+        codecs: config.codecs.clone(),
+        qn: config.qn,
+    };
+    let info = get_room_play_info(client, room_id, &options).await?;
+    let preference = QualityPreference {
+        qn: config.qn,
+        protocols: options.protocols,
+        formats: options.formats,
+        codecs: options.codecs,
+    };
+    let urls = info
+        .select(&preference)
+        .map(|selected| selected.stream.urls())
+        .unwrap_or_default();
+    let probe = probe_fastest(client, &urls).await.ok_or_else(|| {
+        Error::UnexpectedResponse(format!("no stream of room {} is serving", room_id))
+    })?;
+    Ok(probe.url)
+}
+
+/// Record until stopped, fetching the stream again whenever it ends.
+async fn capture(
+    client: BiliClient,
+    room_id: u64,
+    config: AutoRecordConfig,
+    mut stop: oneshot::Receiver<()>,
+) -> Vec<PathBuf> {
+    let mut recorder: Option<RecorderHandle> = None;
+    loop {
+        let attempt = async {
+            let recorder = match &mut recorder {
+                Some(recorder) => recorder,
+                None => {
+                    let room = get_room_info(&client, room_id).await?;
+                    recorder.insert(RecorderHandle::spawn(Recorder::new(
+                        &config.dir,
+                        config.template.clone(),
+                        config.rotation,
+                        room,
+                    )))
+                }
+            };
+            let url = stream_url(&client, room_id, &config).await?;
+            recorder.new_stream().await?;
+            record(&client, &url, recorder).await
+        };
+        tokio::select! {
+            _ = &mut stop => break,
+            result = attempt => match result {
+                Ok(received) => debug!("stream of room {} ended after {} bytes", room_id, received),
+                Err(e) => warn!("failed to record room {}: {:?}", room_id, e),
+            },
+        }
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = tokio::time::sleep(config.retry_delay) => {}
+        }
+    }
+    let Some(recorder) = recorder else {
+        return Vec::new();
+    };
+    match recorder.finish().await {
+        Ok(files) => files,
+        Err(e) => {
+            error!("failed to finalize recording of room {}: {:?}", room_id, e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::live::consts;
+    use crate::live::flv::{FlvTag, HEADER, TAG_VIDEO};
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_auto_record() {
+        let mut flv = HEADER.to_vec();
+        for (timestamp, data) in [(0, [0x17, 0, 0, 0, 0]), (0, [0x17, 1, 0, 0, 0])] {
+            let tag = FlvTag {
+                tag_type: TAG_VIDEO,
+                timestamp,
+                data: data.to_vec(),
+            };
+            flv.extend(tag.encode());
+        }
+        let transport = MockTransport::new()
+            .json(
+                consts::ROOM_INIT,
+                json!({"code": 0, "data": {"room_id": 14507014, "live_status": 0}}),
+            )
+            .json(
+                consts::ROOM_INFO,
+                json!({"code": 0, "data": {"room_id": 14507014, "title": "title"}}),
+            )
+            .json(
+                consts::ROOM_PLAY_INFO,
+                json!({"code": 0, "data": {"room_id": 14507014, "live_status": 1, "playurl_info": {
+                    "playurl": {"stream": [{"protocol_name": "http_stream", "format": [{
+                        "format_name": "flv", "codec": [{
+                            "codec_name": "avc", "current_qn": 10000,
+                            "base_url": "/live-bvc/live.flv",
+                            "url_info": [{"host": "https://a.bilivideo.com", "extra": "?expires=1"}],
+                        }],
+                    }]}]},
+                }}}),
+            )
+            .respond("https://a.bilivideo.com/live-bvc/live.flv", 200, flv);
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let dir = std::env::temp_dir().join(format!("bili-auto-record-{}", std::process::id()));
+        let mut config = AutoRecordConfig::new(&dir);
+        config.retry_delay = Duration::from_secs(60);
+        let mut recorder = AutoRecorder::new(&client, 14507014, config).await.unwrap();
+        assert!(!recorder.is_recording());
+
+        let live = LiveEvent::from_body(json!({"cmd": "LIVE", "roomid": 14507014}));
+        assert!(recorder.handle_event(&live).await.is_some());
+        assert!(recorder.is_recording());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let preparing = LiveEvent::from_body(json!({"cmd": "PREPARING", "roomid": "14507014"}));
+        recorder.handle_event(&preparing).await;
+        assert!(!recorder.is_recording());
+        assert_eq!(recorder.files().len(), 1);
+        let file = std::fs::read(&recorder.files()[0]).unwrap();
+        assert!(file.starts_with(b"FLV"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::model::{worn_medal, FanMedal, GuardLevel};
+use super::{consts, room_init};
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// A user as shown when clicking them in the chat of a room.
+pub struct LiveUserCard {
+    pub uid: u64,
+    pub uname: String,
+    pub face: String,
+    /// Whether the user is an admin of the room.
+    #[serde(alias = "is_room_admin")]
+    pub is_admin: bool,
+    /// Guard level for the streamer of the room.
+    pub guard_level: GuardLevel,
+    /// Worn title, e.g. `title-111-1`, empty if none.
+    #[serde(alias = "wear_title")]
+    pub title: String,
+    /// Worn fan medal, `None` if none.
+    #[serde(alias = "fans_medal", deserialize_with = "worn_medal")]
+    pub medal: Option<FanMedal>,
+    /// Fields not known to this crate, kept to survive upstream changes.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Get the card of the user `uid` in the room, with the medal, title and guard shown there.
+pub async fn get_user_card_in_room(
+    client: &BiliClient,
+    room_id: u64,
+    uid: u64,
+) -> Result<LiveUserCard> {
+    let room = room_init(client, room_id).await?;
+    client
+        .get(
+            consts::USER_CARD,
+            &[("uid", uid), ("ruid", room.uid), ("room_id", room.room_id)],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_user_card_in_room() {
+        let transport = MockTransport::new()
+            .json(
+                consts::ROOM_INIT,
+                json!({"code": 0, "data": {"room_id": 14507014, "uid": 6067854}}),
+            )
+            .json(
+                consts::USER_CARD,
+                json!({"code": 0, "data": {
+                    "uid": 10086, "uname": "someone", "face": "", "is_room_admin": true,
+                    "guard_level": 3, "wear_title": "title-111-1",
+                    "fans_medal": {"medal_level": 21, "medal_name": "medal", "target_id": 6067854},
+                }}),
+            );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let card = get_user_card_in_room(&client, 1, 10086).await.unwrap();
+        assert!(card.is_admin);
+        assert_eq!(card.guard_level, GuardLevel::Captain);
+        assert_eq!(card.medal.unwrap().level, 21);
+        assert_eq!(
+            transport.requests()[1].query(),
+            Some("uid=10086&ruid=6067854&room_id=14507014")
+        );
+    }
+}
@@ -1,4 +1,45 @@
 pub const ROOM_INIT: &str = "https://api.live.bilibili.com/room/v1/Room/room_init";
+pub const ROOM_INFO: &str = "https://api.live.bilibili.com/room/v1/Room/get_info";
 pub const DANMAKU_SERVER_CONF: &str =
     "https://api.live.bilibili.com/xlive/web-room/v1/index/getDanmuInfo";
 pub const PLAY_URL: &str = "https://api.live.bilibili.com/room/v1/Room/playUrl";
+pub const ROOM_PLAY_INFO: &str =
+    "https://api.live.bilibili.com/xlive/web-room/v2/index/getRoomPlayInfo";
+pub const HISTORY_DANMAKU: &str = "https://api.live.bilibili.com/xlive/web-room/v1/dM/gethistory";
+pub const GIFT_CONFIG: &str =
+    "https://api.live.bilibili.com/xlive/web-room/v1/giftPanel/giftConfig";
+pub const GUARD_LIST: &str = "https://api.live.bilibili.com/xlive/app-room/v2/guardTab/topList";
+pub const HEARTBEAT_E: &str = "https://live-trace.bilibili.com/xlive/data-interface/v1/x25Kn/E";
+pub const HEARTBEAT_X: &str = "https://live-trace.bilibili.com/xlive/data-interface/v1/x25Kn/X";
+pub const AREA_LIST: &str = "https://api.live.bilibili.com/room/v1/Area/getList";
+pub const AREA_ROOM_LIST: &str = "https://api.live.bilibili.com/room/v3/area/getRoomList";
+pub const START_LIVE: &str = "https://api.live.bilibili.com/room/v1/Room/startLive";
+pub const STOP_LIVE: &str = "https://api.live.bilibili.com/room/v1/Room/stopLive";
+pub const UPDATE_ROOM: &str = "https://api.live.bilibili.com/room/v1/Room/update";
+pub const ADD_SILENT_USER: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/banned/AddSilentUser";
+pub const DEL_SILENT_USER: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/banned/DelSilentUser";
+pub const SILENT_USER_LIST: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/banned/GetSilentUserList";
+pub const APPOINT_ADMIN: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/roomAdmin/appoint";
+pub const DISMISS_ADMIN: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/roomAdmin/dismiss";
+pub const ROOM_SILENT: &str = "https://api.live.bilibili.com/xlive/web-room/v1/banned/RoomSilent";
+pub const FOLLOWED_LIVE: &str = "https://api.live.bilibili.com/xlive/web-ucenter/user/following";
+pub const EMOTICONS: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v2/emoticon/GetEmoticons";
+pub const SEND_DANMAKU: &str = "https://api.live.bilibili.com/msg/send";
+pub const LOTTERY_INFO: &str =
+    "https://api.live.bilibili.com/xlive/lottery-interface/v1/lottery/getLotteryInfoWeb";
+pub const LIKE_REPORT: &str =
+    "https://api.live.bilibili.com/xlive/app-ucenter/v1/like_info_v3/like/likeReportV3";
+pub const SHARE_ROOM: &str = "https://api.live.bilibili.com/xlive/app-room/v1/index/TrigerInteract";
+pub const DO_SIGN: &str = "https://api.live.bilibili.com/xlive/web-ucenter/v1/sign/DoSign";
+pub const SIGN_INFO: &str =
+    "https://api.live.bilibili.com/xlive/web-ucenter/v1/sign/WebGetSignInfo";
+pub const MY_WALLET: &str = "https://api.live.bilibili.com/xlive/revenue/v1/wallet/myWallet";
+pub const SEND_GOLD: &str = "https://api.live.bilibili.com/xlive/revenue/v2/gift/sendGold";
+pub const SEND_SILVER: &str = "https://api.live.bilibili.com/xlive/revenue/v2/gift/sendSilver";
+pub const USER_CARD: &str = "https://api.live.bilibili.com/xlive/app-ucenter/v2/card/user";
@@ -0,0 +1,380 @@
+//! Export danmaku into subtitle files, e.g. alongside a recording.
+use std::io::Write;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Duration, Instant};
+
+use super::event::{Danmaku, LiveEvent};
+use super::ws::PacketReceiver;
+use crate::Result;
+
+/// A subtitle format danmaku can be written into.
+pub trait DanmakuWriter {
+    /// Write a danmaku which appeared `offset` after the recording started.
+    fn write_danmaku(&mut self, danmaku: &Danmaku, offset: Duration) -> std::io::Result<()>;
+
+    /// Write the trailer, if any, and flush.
+    fn finish(&mut self) -> std::io::Result<()>;
+}
+
+/// Consume packets until the stream is closed, writing every danmaku timestamped against `start`.
+pub async fn export<W: DanmakuWriter>(
+    mut rx: PacketReceiver,
+    start: Instant,
+    writer: &mut W,
+) -> Result<()> {
+    loop {
+        let pkt = match rx.recv().await {
+            Ok(pkt) => pkt,
+            Err(RecvError::Lagged(n)) => {
+                warn!("danmaku exporter lagged, {} packets skipped", n);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        match LiveEvent::from_packet(&pkt) {
+            Ok(Some(LiveEvent::Danmaku(danmaku))) => {
+                writer.write_danmaku(&danmaku, start.elapsed())?
+            }
+            Ok(_) => {}
+            Err(e) => warn!("skipping undecodable danmaku packet: {}", e),
+        }
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+#[derive(Clone, Debug)]
+/// Layout of the exported ASS subtitle.
+pub struct AssConfig {
+    pub width: u32,
+    pub height: u32,
+    pub font_name: String,
+    pub font_size: u32,
+    /// Time a scrolling danmaku takes to cross the screen, i.e. the scroll speed.
+    pub scroll_duration: Duration,
+    /// Time a top/bottom danmaku stays on screen.
+    pub fixed_duration: Duration,
+    /// Part of the screen height used for lanes, from `0.0` to `1.0`.
+    pub lane_ratio: f32,
+    /// Alpha of the text, `0` is opaque and `255` is transparent.
+    pub alpha: u8,
+}
+
+impl Default for AssConfig {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            font_name: "Microsoft YaHei".to_string(),
+            font_size: 48,
+            scroll_duration: Duration::from_secs(8),
+            fixed_duration: Duration::from_secs(5),
+            lane_ratio: 1.0,
+            alpha: 0x40,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Lane {
+    start: Duration,
+    width: u32,
+}
+
+/// Write danmaku as an ASS subtitle.
+pub struct AssWriter<W: Write> {
+    inner: W,
+    config: AssConfig,
+    scroll: Vec<Option<Lane>>,
+    top: Vec<Option<Lane>>,
+    bottom: Vec<Option<Lane>>,
+}
+
+impl<W: Write> AssWriter<W> {
+    /// Create a writer and write the ASS header.
+    pub fn new(mut inner: W, mut config: AssConfig) -> std::io::Result<Self> {
+        // lanes are one font size tall
+        config.font_size = config.font_size.max(1);
+        write!(
+            inner,
+            "[Script Info]\n\
+             ScriptType: v4.00+\n\
+             PlayResX: {width}\n\
+             PlayResY: {height}\n\
+             WrapStyle: 2\n\
+             ScaledBorderAndShadow: yes\n\
+             \n\
+             [V4+ Styles]\n\
+             Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, \
+             BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, \
+             BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+             Style: Danmaku,{font},{size},&H{alpha:02X}FFFFFF,&H{alpha:02X}FFFFFF,\
+             &H{alpha:02X}000000,&H{alpha:02X}000000,0,0,0,0,100,100,0,0,1,1,0,7,0,0,0,0\n\
+             \n\
+             [Events]\n\
+             Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+            width = config.width,
+            height = config.height,
+            font = config.font_name,
+            size = config.font_size,
+            alpha = config.alpha,
+        )?;
+        let lanes = ((config.height as f32 * config.lane_ratio) as u32 / config.font_size).max(1);
+        Ok(Self {
+            inner,
+            scroll: vec![None; lanes as usize],
+            top: vec![None; lanes as usize],
+            bottom: vec![None; lanes as usize],
+            config,
+        })
+    }
+
+    /// Rough rendered width, CJK characters are twice as wide as ASCII.
+    fn text_width(&self, text: &str) -> u32 {
+        let half = self.config.font_size / 2;
+        text.chars()
+            .map(|c| {
+                if c.is_ascii() {
+                    half
+                } else {
+                    self.config.font_size
+                }
+            })
+            .sum()
+    }
+
+    /// Whether a scrolling danmaku of `width` starting at `at` never catches up with `lane`.
+    fn scroll_fits(&self, lane: &Lane, at: Duration, width: u32) -> bool {
+        let screen = self.config.width as f64;
+        let duration = self.config.scroll_duration.as_secs_f64();
+        let prev_speed = (screen + lane.width as f64) / duration;
+        let speed = (screen + width as f64) / duration;
+        let prev_start = lane.start.as_secs_f64();
+        let at = at.as_secs_f64();
+        // the previous one has fully entered the screen,
+        // and it has left the screen before the new one reaches the left edge.
+        at >= prev_start + lane.width as f64 / prev_speed
+            && at + screen / speed >= prev_start + duration
+    }
+
+    fn allocate(&mut self, mode: u8, at: Duration, width: u32) -> usize {
+        let fixed = self.config.fixed_duration;
+        let free = |index: usize, lane: &Option<Lane>| match lane {
+            None => Some(index),
+            Some(lane) if mode != 4 && mode != 5 => {
+                if self.scroll_fits(lane, at, width) {
+                    Some(index)
+                } else {
+                    None
+                }
+            }
+            Some(lane) if lane.start + fixed <= at => Some(index),
+            _ => None,
+        };
+        let lanes = match mode {
+            4 => &self.bottom,
+            5 => &self.top,
+            _ => &self.scroll,
+        };
+        // fall back to the lane which was taken earliest when all lanes are busy.
+        let index = lanes
+            .iter()
+            .enumerate()
+            .find_map(|(index, lane)| free(index, lane))
+            .unwrap_or_else(|| {
+                lanes
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, lane)| lane.map(|lane| lane.start))
+                    .map(|(index, _)| index)
+                    .unwrap_or_default()
+            });
+        let lanes = match mode {
+            4 => &mut self.bottom,
+            5 => &mut self.top,
+            _ => &mut self.scroll,
+        };
+        lanes[index] = Some(Lane { start: at, width });
+        index
+    }
+}
+
+impl<W: Write> DanmakuWriter for AssWriter<W> {
+    fn write_danmaku(&mut self, danmaku: &Danmaku, offset: Duration) -> std::io::Result<()> {
+        let width = self.text_width(&danmaku.content);
+        let lane = self.allocate(danmaku.mode, offset, width) as u32;
+        let size = self.config.font_size;
+        let (position, end) = match danmaku.mode {
+            4 => (
+                format!(
+                    "\\an2\\pos({},{})",
+                    self.config.width / 2,
+                    self.config.height - lane * size
+                ),
+                offset + self.config.fixed_duration,
+            ),
+            5 => (
+                format!("\\an8\\pos({},{})", self.config.width / 2, lane * size),
+                offset + self.config.fixed_duration,
+            ),
+            _ => (
+                format!(
+                    "\\move({},{y},-{},{y})",
+                    self.config.width,
+                    width,
+                    y = lane * size
+                ),
+                offset + self.config.scroll_duration,
+            ),
+        };
+        // ASS colors are in BGR order.
+        let color = danmaku.color;
+        let bgr = ((color & 0xff) << 16) | (color & 0xff00) | ((color >> 16) & 0xff);
+        writeln!(
+            self.inner,
+            "Dialogue: 2,{},{},Danmaku,,0000,0000,0000,,{{{}\\c&H{:06X}&}}{}",
+            ass_time(offset),
+            ass_time(end),
+            position,
+            bgr,
+            escape_ass(&danmaku.content),
+        )
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn ass_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Keep the text from forming override blocks or escapes such as `\N`.
+fn escape_ass(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace('\n', "\\N")
+}
+
+/// Write danmaku in the bilibili XML format, which most players can load.
+pub struct XmlWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> XmlWriter<W> {
+    /// Create a writer and write the XML header.
+    pub fn new(mut inner: W) -> std::io::Result<Self> {
+        writeln!(inner, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(inner, "<i>")?;
+        writeln!(inner, "<chatserver>chat.bilibili.com</chatserver>")?;
+        Ok(Self { inner })
+    }
+}
+
+impl<W: Write> DanmakuWriter for XmlWriter<W> {
+    fn write_danmaku(&mut self, danmaku: &Danmaku, offset: Duration) -> std::io::Result<()> {
+        writeln!(
+            self.inner,
+            "<d p=\"{:.3},{},{},{},{},0,{},0\">{}</d>",
+            offset.as_secs_f64(),
+            danmaku.mode,
+            danmaku.font_size,
+            danmaku.color,
+            danmaku.timestamp / 1000,
+            uid_hash(danmaku.uid),
+            escape_xml(&danmaku.content),
+        )
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        writeln!(self.inner, "</i>")?;
+        self.inner.flush()
+    }
+}
+
+/// The `midHash` of the XML format, the uid is not given away.
+fn uid_hash(uid: u64) -> String {
+    format!("{:x}", crc32fast::hash(uid.to_string().as_bytes()))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn danmaku(mode: u8, content: &str) -> Danmaku {
+        Danmaku {
+            mode,
+            font_size: 25,
+            color: 0xff0000,
+            timestamp: 1639000000000,
+            content: content.to_string(),
+            uid: 1,
+            uname: "<someone>".to_string(),
+            is_admin: false,
+            user_level: 0,
+            medal: None,
+        }
+    }
+
+    #[test]
+    fn test_ass_lanes() {
+        let mut buf = Vec::new();
+        let mut writer = AssWriter::new(&mut buf, AssConfig::default()).unwrap();
+        let at = Duration::from_secs(1);
+        writer.write_danmaku(&danmaku(1, "first"), at).unwrap();
+        writer.write_danmaku(&danmaku(1, "second"), at).unwrap();
+        writer.write_danmaku(&danmaku(5, "top"), at).unwrap();
+        writer.finish().unwrap();
+        let ass = String::from_utf8(buf).unwrap();
+        assert!(ass.contains("0:00:01.00,0:00:09.00"));
+        assert!(ass.contains("\\move(1920,0,-120,0)\\c&H0000FF&}first"));
+        assert!(ass.contains("\\move(1920,48,-144,48)"));
+        assert!(ass.contains("\\an8\\pos(960,0)"));
+    }
+
+    #[test]
+    fn test_ass_escape() {
+        assert_eq!(escape_ass("{\\b1}a\\Nb\nc"), "\\{\\\\b1\\}a\\\\Nb\\Nc");
+
+        let config = AssConfig {
+            font_size: 0,
+            ..AssConfig::default()
+        };
+        let mut buf = Vec::new();
+        let mut writer = AssWriter::new(&mut buf, config).unwrap();
+        writer
+            .write_danmaku(&danmaku(1, "tiny"), Duration::ZERO)
+            .unwrap();
+        assert_eq!(writer.scroll.len(), 1080);
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        let mut buf = Vec::new();
+        let mut writer = XmlWriter::new(&mut buf).unwrap();
+        writer
+            .write_danmaku(&danmaku(1, "a<b"), Duration::from_millis(1500))
+            .unwrap();
+        writer.finish().unwrap();
+        let xml = String::from_utf8(buf).unwrap();
+        assert!(xml.contains("<d p=\"1.500,1,25,16711680,1639000000,0,83dcefb7,0\">a&lt;b</d>"));
+        assert!(xml.ends_with("</i>\n"));
+    }
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+use super::{consts, LiveDanmakuDraft};
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct EmoticonPacks {
+    #[serde(default)]
+    data: Vec<EmoticonPack>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A pack of emoticons, e.g. the general one or the UP emotes of a room.
+pub struct EmoticonPack {
+    pub pkg_id: u64,
+    pub pkg_name: String,
+    /// `1` general, `2` room, `3` UP emotes.
+    pub pkg_type: u8,
+    #[serde(default)]
+    pub pkg_descript: String,
+    /// `1` usable by the account.
+    #[serde(default)]
+    pub pkg_perm: u8,
+    #[serde(default)]
+    pub current_cover: String,
+    #[serde(default)]
+    pub emoticons: Vec<Emoticon>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An emoticon, sent as a danmaku by its `emoticon_unique`.
+pub struct Emoticon {
+    pub emoticon_id: u64,
+    /// e.g. `official_147`, or `room_{room_id}_{id}` for UP emotes.
+    pub emoticon_unique: String,
+    /// e.g. `[dog]`.
+    pub emoji: String,
+    #[serde(default)]
+    pub descript: String,
+    pub url: String,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    /// `1` usable by the account, else see the unlock rules.
+    #[serde(default)]
+    pub perm: u8,
+    /// Gift needed to unlock, `0` for none.
+    #[serde(default)]
+    pub unlock_need_gift: u64,
+    /// Fan medal level needed to unlock, `0` for none.
+    #[serde(default)]
+    pub unlock_need_level: u32,
+    /// e.g. `粉丝团1级解锁`.
+    #[serde(default)]
+    pub unlock_show_text: String,
+}
+
+impl Emoticon {
+    pub fn is_usable(&self) -> bool {
+        self.perm == 1
+    }
+
+    /// A danmaku sending this emoticon.
+    pub fn draft(&self) -> LiveDanmakuDraft {
+        LiveDanmakuDraft::emoticon(&self.emoticon_unique)
+    }
+}
+
+/// Get the emoticon packs available in the room, including its UP emotes.
+pub async fn get_emoticons(client: &BiliClient, room_id: u64) -> Result<Vec<EmoticonPack>> {
+    let packs: EmoticonPacks = client
+        .get(
+            consts::EMOTICONS,
+            &[
+                ("platform", "pc".to_string()),
+                ("room_id", room_id.to_string()),
+            ],
+        )
+        .await?;
+    Ok(packs.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_emoticons() {
+        let transport = MockTransport::new().json(
+            consts::EMOTICONS,
+            json!({"code": 0, "data": {"data": [{
+                "pkg_id": 1, "pkg_name": "UP主大表情", "pkg_type": 3, "pkg_perm": 1,
+                "emoticons": [{
+                    "emoticon_id": 100, "emoticon_unique": "room_1_100", "emoji": "[打call]",
+                    "url": "https://i0.hdslb.com/bfs/live/a.png", "perm": 0,
+                    "unlock_need_level": 1, "unlock_show_text": "粉丝团1级解锁",
+                }],
+            }]}}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let packs = get_emoticons(&client, 1).await.unwrap();
+        let emoticon = &packs[0].emoticons[0];
+        assert!(!emoticon.is_usable());
+        assert_eq!(emoticon.draft().msg, "room_1_100");
+        assert_eq!(emoticon.draft().dm_type, 1);
+    }
+}
@@ -0,0 +1,471 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+use super::model::{worn_medal, FanMedal, GuardLevel};
+use super::ws::{Operation, PacketReceiver, ProtoVer, WsPacket};
+use super::{AnchorLot, AnchorLotAward, RedPocket};
+use crate::Result;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Decoded event from the danmaku stream.
+pub enum LiveEvent {
+    /// A danmaku (`DANMU_MSG`) sent by a viewer.
+    Danmaku(Danmaku),
+    /// A viewer entered, followed or shared the room (`INTERACT_WORD`).
+    Interact(InteractWord),
+    /// Entering effect of a guard or a high level user (`ENTRY_EFFECT`).
+    EntryEffect(EntryEffect),
+    /// A guard entered the room (`WELCOME_GUARD`).
+    WelcomeGuard(WelcomeGuard),
+    /// The streamer started a giveaway (`ANCHOR_LOT_START`).
+    AnchorLotStart(AnchorLot),
+    /// Winners of a giveaway were drawn (`ANCHOR_LOT_AWARD`).
+    AnchorLotAward(AnchorLotAward),
+    /// A viewer sent a red envelope (`POPULARITY_RED_POCKET_START`).
+    RedPocketStart(RedPocket),
+    /// The room started living (`LIVE`), often sent more than once.
+    LiveStart(LiveStart),
+    /// The room stopped living (`PREPARING`).
+    Preparing(Preparing),
+    /// The live was cut off by an admin (`CUT_OFF`), the room stops living.
+    CutOff(AdminNotice),
+    /// An admin warned the streamer (`WARNING`).
+    Warning(AdminNotice),
+    /// Title or area of the room changed (`ROOM_CHANGE`).
+    RoomChange(RoomChange),
+    /// Popularity carried by a heartbeat reply.
+    Popularity(i32),
+    /// The server accepted the entering packet.
+    EnteringReply,
+    /// Notification which has no typed model yet.
+    Other { cmd: String, body: Value },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A danmaku message in a living room.
+pub struct Danmaku {
+    /// Display mode, `1` scroll, `4` bottom, `5` top.
+    pub mode: u8,
+    pub font_size: u32,
+    /// RGB color, e.g. `0xffffff`.
+    pub color: u32,
+    /// Unix timestamp in milliseconds.
+    pub timestamp: i64,
+    pub content: String,
+    pub uid: u64,
+    pub uname: String,
+    pub is_admin: bool,
+    pub user_level: u32,
+    pub medal: Option<FanMedal>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A viewer interacting with the room.
+pub struct InteractWord {
+    pub uid: u64,
+    pub uname: String,
+    /// `1` entered, `2` followed, `3` shared, `4` specially followed, `5` followed back.
+    pub msg_type: u8,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+    pub roomid: u64,
+    /// Worn fan medal, `None` if none.
+    #[serde(deserialize_with = "worn_medal")]
+    pub fans_medal: Option<FanMedal>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Effect shown when a guard or a high level user enters.
+pub struct EntryEffect {
+    pub id: u64,
+    pub uid: u64,
+    /// Uid of the streamer.
+    pub target_id: u64,
+    /// Guard level of the user, [`GuardLevel::None`] for other effects.
+    pub privilege_type: GuardLevel,
+    /// e.g. `欢迎舰长 <%someone%> 进入直播间`.
+    pub copy_writing: String,
+    pub face: String,
+    /// Unix timestamp in nanoseconds.
+    pub trigger_time: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A guard entering the room.
+pub struct WelcomeGuard {
+    pub uid: u64,
+    pub username: String,
+    pub guard_level: GuardLevel,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LiveStart {
+    #[serde(with = "crate::de::string_or_number")]
+    pub roomid: u64,
+    /// Unix timestamp in seconds, `0` if not given.
+    pub live_time: i64,
+    /// e.g. `pc_link`.
+    pub live_platform: String,
+    pub live_key: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preparing {
+    #[serde(with = "crate::de::string_or_number")]
+    pub roomid: u64,
+    /// `1` if the room switched to replaying recordings (轮播).
+    pub round: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Intervention of a platform admin.
+pub struct AdminNotice {
+    #[serde(with = "crate::de::string_or_number")]
+    pub roomid: u64,
+    /// The reason, e.g. `违反直播规范`.
+    pub msg: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoomChange {
+    pub title: String,
+    pub area_id: u64,
+    pub parent_area_id: u64,
+    pub area_name: String,
+    pub parent_area_name: String,
+}
+
+impl EntryEffect {
+    /// Name of the user, taken from the `<%name%>` in `copy_writing`.
+    pub fn uname(&self) -> Option<&str> {
+        let start = self.copy_writing.find("<%")? + 2;
+        let len = self.copy_writing[start..].find("%>")?;
+        Some(&self.copy_writing[start..start + len])
+    }
+}
+
+impl LiveEvent {
+    /// Decode a packet into an event.
+    ///
+    /// Returns `None` for packets that carry no event, e.g. heartbeats sent by ourselves.
+    pub fn from_packet(pkt: &WsPacket) -> Result<Option<Self>> {
+        match pkt.operation {
+            Operation::HeartBeatReply => Ok(pkt.popularity().map(LiveEvent::Popularity)),
+            Operation::EnteringReply => Ok(Some(LiveEvent::EnteringReply)),
+            Operation::Notification if pkt.proto_ver == ProtoVer::Json => {
+                Ok(Some(Self::from_body(pkt.decode_body()?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Decode a notification body, which is always a json object with a `cmd` field.
+    pub fn from_body(body: Value) -> Self {
+        let cmd = body["cmd"].as_str().unwrap_or_default().to_string();
+        // some commands come with a suffix, e.g. `DANMU_MSG:4:0:2:2:2:0`
+        let event = match cmd.split(':').next().unwrap_or_default() {
+            "DANMU_MSG" => Danmaku::from_info(&body["info"]).map(LiveEvent::Danmaku),
+            "INTERACT_WORD" => data(&body).map(LiveEvent::Interact),
+            "ENTRY_EFFECT" => data(&body).map(LiveEvent::EntryEffect),
+            "WELCOME_GUARD" => data(&body).map(LiveEvent::WelcomeGuard),
+            "LIVE" => LiveStart::deserialize(&body).ok().map(LiveEvent::LiveStart),
+            "PREPARING" => Preparing::deserialize(&body).ok().map(LiveEvent::Preparing),
+            "CUT_OFF" => AdminNotice::deserialize(&body).ok().map(LiveEvent::CutOff),
+            "WARNING" => AdminNotice::deserialize(&body).ok().map(LiveEvent::Warning),
+            "ROOM_CHANGE" => data(&body).map(LiveEvent::RoomChange),
+            "ANCHOR_LOT_START" => data(&body).map(LiveEvent::AnchorLotStart),
+            "ANCHOR_LOT_AWARD" => data(&body).map(LiveEvent::AnchorLotAward),
+            "POPULARITY_RED_POCKET_START" => data(&body).map(LiveEvent::RedPocketStart),
+            _ => None,
+        };
+        event.unwrap_or(LiveEvent::Other { cmd, body })
+    }
+}
+
+/// Deserialize the `data` field of a notification.
+fn data<T: serde::de::DeserializeOwned>(body: &Value) -> Option<T> {
+    T::deserialize(body.get("data")?).ok()
+}
+
+impl Danmaku {
+    /// Parse the `info` array of a `DANMU_MSG` notification.
+    pub fn from_info(info: &Value) -> Option<Self> {
+        let meta = info.get(0)?;
+        let user = info.get(2)?;
+        Some(Self {
+            mode: meta.get(1)?.as_u64()? as u8,
+            font_size: meta.get(2)?.as_u64()? as u32,
+            color: meta.get(3)?.as_u64()? as u32,
+            timestamp: meta.get(4)?.as_i64()?,
+            content: info.get(1)?.as_str()?.to_string(),
+            uid: user.get(0)?.as_u64()?,
+            uname: user.get(1)?.as_str()?.to_string(),
+            is_admin: user.get(2).and_then(Value::as_u64).unwrap_or_default() == 1,
+            user_level: info
+                .get(4)
+                .and_then(|level| level.get(0))
+                .and_then(Value::as_u64)
+                .unwrap_or_default() as u32,
+            medal: info.get(3).and_then(FanMedal::from_info),
+        })
+    }
+}
+
+/// A subscriber receiving decoded events, one of many fed by the same [`DanmakuStream`].
+///
+/// [`DanmakuStream`]: super::ws::DanmakuStream
+#[derive(Debug)]
+pub struct EventReceiver {
+    rx: PacketReceiver,
+    lagged: u64,
+}
+
+impl From<PacketReceiver> for EventReceiver {
+    fn from(rx: PacketReceiver) -> Self {
+        Self { rx, lagged: 0 }
+    }
+}
+
+impl From<broadcast::Receiver<WsPacket>> for EventReceiver {
+    fn from(rx: broadcast::Receiver<WsPacket>) -> Self {
+        PacketReceiver::from(rx).into()
+    }
+}
+
+impl EventReceiver {
+    /// Receive the next event, `None` once the stream is closed.
+    ///
+    /// Packets which fail to decode are skipped, so are packets missed by lagging behind.
+    pub async fn recv(&mut self) -> Option<LiveEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(pkt) => match LiveEvent::from_packet(&pkt) {
+                    Ok(Some(event)) => return Some(event),
+                    Ok(None) => {}
+                    Err(e) => warn!("failed to decode packet: {:?}", e),
+                },
+                Err(RecvError::Lagged(n)) => {
+                    warn!("event receiver lagged, {} packets skipped", n);
+                    self.lagged += n;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Packets skipped so far because this receiver lagged behind.
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+
+    /// Create another receiver starting from the next packet.
+    pub fn resubscribe(&self) -> Self {
+        self.rx.resubscribe().into()
+    }
+
+    /// Turn into a [`Stream`] to use [`StreamExt`] combinators, e.g. `filter_map` or `chunks`.
+    pub fn into_stream(self) -> EventStream {
+        EventStream {
+            inner: stream::unfold(self, |mut rx| async move {
+                rx.recv().await.map(|event| (event, rx))
+            })
+            .boxed(),
+        }
+    }
+}
+
+/// Decoded events as a [`Stream`], ends when the danmaku stream is closed.
+pub struct EventStream {
+    inner: BoxStream<'static, LiveEvent>,
+}
+
+impl Stream for EventStream {
+    type Item = LiveEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<LiveEvent>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_receivers() {
+        let (tx, rx) = broadcast::channel(4);
+        let mut overlay = EventReceiver::from(rx);
+        let mut logger = overlay.resubscribe();
+        tx.send(WsPacket::new_heartbeat()).unwrap();
+        let reply = WsPacket::new(
+            ProtoVer::Json,
+            Operation::HeartBeatReply,
+            42i32.to_be_bytes().to_vec(),
+        );
+        tx.send(reply).unwrap();
+        drop(tx);
+        assert_eq!(overlay.recv().await, Some(LiveEvent::Popularity(42)));
+        assert_eq!(logger.recv().await, Some(LiveEvent::Popularity(42)));
+        assert_eq!(overlay.recv().await, None);
+        let events = logger.into_stream().collect::<Vec<_>>().await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let (tx, rx) = broadcast::channel(4);
+        let stream = EventReceiver::from(rx).into_stream();
+        for popularity in 1..=3i32 {
+            let reply = WsPacket::new(
+                ProtoVer::Json,
+                Operation::HeartBeatReply,
+                popularity.to_be_bytes().to_vec(),
+            );
+            tx.send(reply).unwrap();
+        }
+        drop(tx);
+        let popularity = stream
+            .filter_map(|event| async move {
+                match event {
+                    LiveEvent::Popularity(popularity) => Some(popularity),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(popularity, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_decode_danmaku() {
+        let body = serde_json::json!({
+            "cmd": "DANMU_MSG:4:0:2:2:2:0",
+            "info": [
+                [0, 1, 25, 16777215, 1639000000000i64, 0, 0, "abcd", 0, 0, 0, ""],
+                "hello",
+                [10086, "someone", 1, 0, 0, 10000, 1, ""],
+                [12, "medal", "anchor", 14507014, 6067854, "", 0],
+                [20, 0, 6406234, ">50000"],
+            ]
+        });
+        match LiveEvent::from_body(body) {
+            LiveEvent::Danmaku(danmaku) => {
+                assert_eq!(danmaku.content, "hello");
+                assert_eq!(danmaku.uid, 10086);
+                assert!(danmaku.is_admin);
+                assert_eq!(danmaku.user_level, 20);
+                assert_eq!(danmaku.medal.unwrap().level, 12);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_welcome() {
+        let body = serde_json::json!({
+            "cmd": "INTERACT_WORD",
+            "data": {
+                "uid": 10086, "uname": "someone", "msg_type": 1, "timestamp": 1639000000,
+                "roomid": 14507014, "fans_medal": {
+                    "medal_level": 21, "medal_name": "medal", "target_id": 6067854,
+                    "anchor_roomid": 14507014, "guard_level": 3, "medal_color": 1725515,
+                },
+                "score": 1639000000000i64,
+            },
+        });
+        match LiveEvent::from_body(body) {
+            LiveEvent::Interact(interact) => {
+                assert_eq!(interact.uname, "someone");
+                let medal = interact.fans_medal.unwrap();
+                assert_eq!(medal.level, 21);
+                assert_eq!(medal.guard_level, GuardLevel::Captain);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let body = serde_json::json!({
+            "cmd": "ENTRY_EFFECT",
+            "data": {
+                "id": 4, "uid": 10086, "target_id": 6067854, "privilege_type": 3,
+                "copy_writing": "欢迎舰长 <%someone%> 进入直播间", "face": "",
+                "trigger_time": 1639000000000000000i64,
+            },
+        });
+        match LiveEvent::from_body(body) {
+            LiveEvent::EntryEffect(effect) => {
+                assert_eq!(effect.privilege_type, GuardLevel::Captain);
+                assert_eq!(effect.uname(), Some("someone"));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let body = serde_json::json!({
+            "cmd": "WELCOME_GUARD",
+            "data": {"uid": 10086, "username": "someone", "guard_level": 3},
+        });
+        assert_eq!(
+            LiveEvent::from_body(body),
+            LiveEvent::WelcomeGuard(WelcomeGuard {
+                uid: 10086,
+                username: "someone".to_string(),
+                guard_level: GuardLevel::Captain,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_lottery() {
+        let body = serde_json::json!({
+            "cmd": "ANCHOR_LOT_AWARD",
+            "data": {
+                "id": 1, "award_name": "手办", "award_num": 1, "lot_status": 2,
+                "award_users": [{"uid": 10086, "uname": "someone", "face": "", "num": 1}],
+            },
+        });
+        match LiveEvent::from_body(body) {
+            LiveEvent::AnchorLotAward(award) => assert_eq!(award.award_users[0].uid, 10086),
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let body = serde_json::json!({
+            "cmd": "POPULARITY_RED_POCKET_START",
+            "data": {
+                "lot_id": 2, "sender_uid": 10086, "sender_name": "someone", "danmu": "老板大气",
+                "last_time": 180, "total_price": 1600,
+                "awards": [{"gift_id": 31212, "gift_name": "打call", "num": 2}],
+            },
+        });
+        match LiveEvent::from_body(body) {
+            LiveEvent::RedPocketStart(pocket) => {
+                assert_eq!(pocket.danmu, "老板大气");
+                assert_eq!(pocket.awards[0].num, 2);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_cut_off() {
+        let body = serde_json::json!({"cmd": "CUT_OFF", "msg": "违反直播规范", "roomid": 14507014});
+        assert_eq!(
+            LiveEvent::from_body(body),
+            LiveEvent::CutOff(AdminNotice {
+                roomid: 14507014,
+                msg: "违反直播规范".to_string(),
+            })
+        );
+    }
+}
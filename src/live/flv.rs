@@ -0,0 +1,244 @@
+//! Just enough of FLV to cut a live stream into playable files.
+use std::io::{Error, ErrorKind};
+
+/// Signature, version, audio and video flags, header size and the first previous tag size.
+pub const HEADER: [u8; 13] = [b'F', b'L', b'V', 1, 5, 0, 0, 0, 9, 0, 0, 0, 0];
+
+pub const TAG_AUDIO: u8 = 8;
+pub const TAG_VIDEO: u8 = 9;
+pub const TAG_SCRIPT: u8 = 18;
+
+/// Largest file header accepted, it is 9 bytes in practice and only grows with new versions.
+const MAX_HEADER_SIZE: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A tag, without the stream id which is always `0`.
+pub struct FlvTag {
+    pub tag_type: u8,
+    /// Milliseconds.
+    pub timestamp: u32,
+    pub data: Vec<u8>,
+}
+
+impl FlvTag {
+    /// A video key frame, where a file can start.
+    pub fn is_keyframe(&self) -> bool {
+        self.tag_type == TAG_VIDEO && self.data.first().is_some_and(|b| b >> 4 == 1)
+    }
+
+    /// The decoder configuration of AVC/HEVC or AAC, needed at the start of every file.
+    pub fn is_sequence_header(&self) -> bool {
+        match self.tag_type {
+            // AVC and the enhanced HEVC of bilibili both use packet type 0
+            TAG_VIDEO => self.data.len() > 1 && self.data[1] == 0,
+            TAG_AUDIO => self.data.len() > 1 && self.data[0] >> 4 == 10 && self.data[1] == 0,
+            _ => false,
+        }
+    }
+
+    /// The tag followed by its previous tag size.
+    pub fn encode(&self) -> Vec<u8> {
+        let size = self.data.len() as u32;
+        let ts = self.timestamp;
+        let mut bytes = Vec::with_capacity(15 + self.data.len());
+        bytes.push(self.tag_type);
+        bytes.extend_from_slice(&size.to_be_bytes()[1..]);
+        bytes.extend_from_slice(&ts.to_be_bytes()[1..]);
+        bytes.push((ts >> 24) as u8);
+        bytes.extend_from_slice(&[0, 0, 0]);
+        bytes.extend_from_slice(&self.data);
+        bytes.extend_from_slice(&(size + 11).to_be_bytes());
+        bytes
+    }
+}
+
+#[derive(Debug, Default)]
+/// Splits the bytes of an FLV stream into tags, however they are chunked.
+pub struct FlvReader {
+    buf: Vec<u8>,
+    /// Start of the unread bytes in `buf`.
+    pos: usize,
+    header_read: bool,
+}
+
+impl FlvReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        // compact once the read bytes outweigh the unread ones, not on every tag
+        if self.pos > self.buf.len() - self.pos {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// The next complete tag, `None` until more bytes are pushed.
+    ///
+    /// The file header is checked and skipped.
+    pub fn next_tag(&mut self) -> std::io::Result<Option<FlvTag>> {
+        if !self.header_read {
+            let buf = &self.buf[self.pos..];
+            if buf.len() < HEADER.len() {
+                return Ok(None);
+            }
+            if &buf[..3] != b"FLV" {
+                return Err(Error::new(ErrorKind::InvalidData, "not an flv stream"));
+            }
+            let header_size = u32::from_be_bytes([buf[5], buf[6], buf[7], buf[8]]) as usize;
+            if !(9..=MAX_HEADER_SIZE).contains(&header_size) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("bad flv header size {}", header_size),
+                ));
+            }
+            if buf.len() < header_size + 4 {
+                return Ok(None);
+            }
+            self.pos += header_size + 4;
+            self.header_read = true;
+        }
+        let buf = &self.buf[self.pos..];
+        if buf.len() < 11 {
+            return Ok(None);
+        }
+        let size = u32::from_be_bytes([0, buf[1], buf[2], buf[3]]) as usize;
+        if buf.len() < 11 + size + 4 {
+            return Ok(None);
+        }
+        let timestamp = u32::from_be_bytes([buf[7], buf[4], buf[5], buf[6]]);
+        let tag = FlvTag {
+            tag_type: buf[0] & 0x1f,
+            timestamp,
+            data: buf[11..11 + size].to_vec(),
+        };
+        self.pos += 11 + size + 4;
+        Ok(Some(tag))
+    }
+}
+
+/// Timestamps going back further than this are a discontinuity, less is the interleaving
+/// of audio and video.
+const MAX_BACKWARD: i64 = 1000;
+/// Timestamps going forward further than this are a discontinuity.
+const MAX_FORWARD: i64 = 10_000;
+/// Gap put after the last tag at a discontinuity, about a frame.
+const FRAME_GAP: i64 = 33;
+
+#[derive(Debug, Default)]
+/// Rewrites tag timestamps so they start at `0` and keep growing across reconnects,
+/// which restart them, and jumps of the server.
+pub struct TimestampFixer {
+    /// Added to incoming timestamps.
+    offset: i64,
+    /// Highest timestamp given out.
+    last: Option<i64>,
+    /// Whether the next tag comes from a new connection.
+    new_stream: bool,
+}
+
+impl TimestampFixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue right after the last tag, whatever the next timestamp is.
+    pub fn new_stream(&mut self) {
+        self.new_stream = true;
+    }
+
+    /// The rewritten timestamp of a tag.
+    pub fn fix(&mut self, timestamp: u32) -> u32 {
+        let timestamp = i64::from(timestamp);
+        match self.last {
+            None => self.offset = -timestamp,
+            Some(last) => {
+                let delta = timestamp + self.offset - last;
+                if self.new_stream || !(-MAX_BACKWARD..=MAX_FORWARD).contains(&delta) {
+                    debug!("flv timestamp jumps by {} ms, rebased", delta);
+                    self.offset = last + FRAME_GAP - timestamp;
+                }
+            }
+        }
+        self.new_stream = false;
+        let fixed = (timestamp + self.offset).max(0);
+        self.last = Some(self.last.map_or(fixed, |last| last.max(fixed)));
+        fixed as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_tags() {
+        let key = FlvTag {
+            tag_type: TAG_VIDEO,
+            timestamp: 0x0123_4567,
+            data: vec![0x17, 1, 0, 0, 0],
+        };
+        let mut bytes = HEADER.to_vec();
+        bytes.extend(key.encode());
+        let mut reader = FlvReader::new();
+        // fed byte by byte, as a network would in the worst case
+        let mut tags = Vec::new();
+        for b in bytes {
+            reader.push(&[b]);
+            while let Some(tag) = reader.next_tag().unwrap() {
+                tags.push(tag);
+            }
+        }
+        assert_eq!(tags, std::slice::from_ref(&key));
+        assert!(tags[0].is_keyframe() && !tags[0].is_sequence_header());
+
+        let mut reader = FlvReader::new();
+        reader.push(b"<html>not a stream</html>");
+        assert!(reader.next_tag().is_err());
+
+        // a header size which would make the reader wait forever, or skip into the tags
+        for size in [0u32, 8, u32::MAX] {
+            let mut header = HEADER;
+            header[5..9].copy_from_slice(&size.to_be_bytes());
+            let mut reader = FlvReader::new();
+            reader.push(&header);
+            let err = reader.next_tag().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_read_compacts() {
+        let tag = FlvTag {
+            tag_type: TAG_AUDIO,
+            timestamp: 0,
+            data: vec![0xaf, 1, 2, 3],
+        };
+        let mut reader = FlvReader::new();
+        reader.push(&HEADER);
+        for _ in 0..100 {
+            reader.push(&tag.encode());
+            assert_eq!(reader.next_tag().unwrap(), Some(tag.clone()));
+        }
+        assert_eq!(reader.next_tag().unwrap(), None);
+        // read tags are dropped from time to time instead of piling up
+        assert!(reader.buf.len() <= 2 * tag.encode().len());
+    }
+
+    #[test]
+    fn test_fix_timestamps() {
+        let mut fixer = TimestampFixer::new();
+        assert_eq!(fixer.fix(5000), 0);
+        assert_eq!(fixer.fix(5040), 40);
+        // audio slightly behind video is kept as is
+        assert_eq!(fixer.fix(5020), 20);
+        fixer.new_stream();
+        assert_eq!(fixer.fix(0), 73);
+        assert_eq!(fixer.fix(40), 113);
+        // a jump of the server
+        assert_eq!(fixer.fix(900_000), 146);
+    }
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of rooms of the followed streamers, living ones first.
+pub struct FollowedLiveList {
+    #[serde(rename = "totalPage", default)]
+    pub total_page: u64,
+    /// Followed streamers which have a room.
+    #[serde(default)]
+    pub count: u64,
+    /// Followed streamers living now.
+    #[serde(default)]
+    pub live_count: u64,
+    #[serde(default)]
+    pub list: Vec<FollowedRoom>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A room of a followed streamer.
+pub struct FollowedRoom {
+    pub roomid: u64,
+    pub uid: u64,
+    pub uname: String,
+    pub title: String,
+    /// `1` living.
+    pub live_status: u8,
+    #[serde(default)]
+    pub face: String,
+    #[serde(default)]
+    pub room_cover: String,
+    #[serde(default)]
+    pub area_name_v2: String,
+    /// e.g. `2.4万人看过`.
+    #[serde(default)]
+    pub text_small: String,
+}
+
+impl FollowedRoom {
+    pub fn is_living(&self) -> bool {
+        self.live_status == 1
+    }
+}
+
+/// Get a page, starting from `1`, of rooms of the streamers the account follows.
+///
+/// Living rooms come first, so stop paging at the first one not living to get all living rooms.
+pub async fn get_followed_live(client: &BiliClient, page: u64) -> Result<FollowedLiveList> {
+    client
+        .get(consts::FOLLOWED_LIVE, &[("page", page), ("page_size", 10)])
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_followed_live() {
+        let transport = MockTransport::new().json(
+            consts::FOLLOWED_LIVE,
+            json!({"code": 0, "data": {
+                "title": "哔哩哔哩直播 - 我的关注", "pageSize": 10, "totalPage": 2,
+                "count": 12, "live_count": 1, "never_lived_count": 3,
+                "list": [
+                    {"roomid": 1, "uid": 11, "uname": "a", "title": "t", "live_status": 1},
+                    {"roomid": 2, "uid": 22, "uname": "b", "title": "t", "live_status": 0},
+                ],
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let followed = get_followed_live(&client, 1).await.unwrap();
+        assert_eq!(followed.live_count, 1);
+        let living = followed.list.iter().filter(|room| room.is_living());
+        assert_eq!(living.map(|room| room.uid).collect::<Vec<_>>(), vec![11]);
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Gifts available in a living room.
+pub struct GiftConfig {
+    pub list: Vec<Gift>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Gift information.
+pub struct Gift {
+    pub id: u64,
+    pub name: String,
+    /// Price in `coin_type`, 1000 gold is 1 CNY.
+    pub price: u64,
+    /// `gold` or `silver`.
+    pub coin_type: String,
+    #[serde(default)]
+    pub img_basic: String,
+    #[serde(default)]
+    pub webp: String,
+    #[serde(default)]
+    pub gif: String,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Currency a gift is paid with.
+pub enum CoinType {
+    Gold,
+    Silver,
+}
+
+impl CoinType {
+    /// Value of `coin_type` in gifts.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoinType::Gold => "gold",
+            CoinType::Silver => "silver",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Result of sending a gift.
+pub struct SentGift {
+    /// Transaction id.
+    pub tid: String,
+    pub gift_id: u64,
+    pub gift_name: String,
+    pub gift_num: u64,
+    /// Price of one gift.
+    pub price: u64,
+    /// Price of all gifts.
+    pub total_coin: u64,
+    pub coin_type: String,
+    /// Id grouping gifts sent in a row into a combo.
+    #[serde(alias = "batch_combo_id")]
+    pub combo_id: String,
+    /// e.g. `赠送成功`.
+    pub send_tips: String,
+}
+
+impl GiftConfig {
+    /// Find a gift by id, e.g. the `giftId` of a `SEND_GIFT` notification.
+    pub fn get(&self, gift_id: u64) -> Option<&Gift> {
+        self.list.iter().find(|gift| gift.id == gift_id)
+    }
+}
+
+impl Gift {
+    /// Whether the gift is paid with gold, i.e. real money.
+    pub fn is_paid(&self) -> bool {
+        self.coin_type == "gold"
+    }
+
+    /// Price in CNY, `0` for silver gifts.
+    pub fn rmb(&self) -> f64 {
+        if self.is_paid() {
+            self.price as f64 / 1000.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Get the gift catalog of the living room.
+pub async fn get_gift_config(client: &BiliClient, room_id: u64) -> Result<GiftConfig> {
+    client
+        .get(
+            consts::GIFT_CONFIG,
+            &[
+                ("platform", "pc".to_string()),
+                ("room_id", room_id.to_string()),
+            ],
+        )
+        .await
+}
+
+/// Send `num` of a gift to the streamer `ruid` in the room.
+///
+/// The gift and its price are looked up in the room's [`GiftConfig`] first,
+/// failing if it is not there or not paid with `coin_type`.
+pub async fn send_gift(
+    client: &BiliClient,
+    room_id: u64,
+    ruid: u64,
+    gift_id: u64,
+    num: u64,
+    coin_type: CoinType,
+) -> Result<SentGift> {
+    let uid = client
+        .session()
+        .and_then(|session| session.uid())
+        .ok_or(Error::MissingCredential("DedeUserID"))?;
+    let csrf = client.csrf()?;
+    let config = get_gift_config(client, room_id).await?;
+    let gift = config.get(gift_id).ok_or_else(|| {
+        Error::UnexpectedResponse(format!("gift {} is not in room {}", gift_id, room_id))
+    })?;
+    if gift.coin_type != coin_type.as_str() {
+        return Err(Error::UnexpectedResponse(format!(
+            "gift {} is paid with {}, not {}",
+            gift_id,
+            gift.coin_type,
+            coin_type.as_str()
+        )));
+    }
+    let url = match coin_type {
+        CoinType::Gold => consts::SEND_GOLD,
+        CoinType::Silver => consts::SEND_SILVER,
+    };
+    client
+        .post_form(
+            url,
+            &[
+                ("uid", uid.to_string()),
+                ("gift_id", gift_id.to_string()),
+                ("ruid", ruid.to_string()),
+                ("send_ruid", "0".to_string()),
+                ("gift_num", num.to_string()),
+                ("coin_type", coin_type.as_str().to_string()),
+                ("bag_id", "0".to_string()),
+                ("platform", "pc".to_string()),
+                ("biz_code", "Live".to_string()),
+                ("biz_id", room_id.to_string()),
+                ("storm_beat_id", "0".to_string()),
+                ("price", gift.price.to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Session;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_send_gift() {
+        let transport = MockTransport::new()
+            .json(
+                consts::GIFT_CONFIG,
+                json!({"code": 0, "data": {"list": [
+                    {"id": 31036, "name": "小花花", "price": 100, "coin_type": "gold"},
+                ]}}),
+            )
+            .json(
+                consts::SEND_GOLD,
+                json!({"code": 0, "data": {
+                    "tid": "1", "gift_id": 31036, "gift_name": "小花花", "gift_num": 2,
+                    "price": 100, "total_coin": 200, "coin_type": "gold",
+                    "batch_combo_id": "batch:gift:combo_id:1", "send_tips": "赠送成功",
+                }}),
+            );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .session(Session::from_cookie_str("DedeUserID=10086; bili_jct=csrf"))
+            .build()
+            .unwrap();
+        let sent = send_gift(&client, 1, 2, 31036, 2, CoinType::Gold)
+            .await
+            .unwrap();
+        assert_eq!(sent.total_coin, 200);
+        assert_eq!(sent.combo_id, "batch:gift:combo_id:1");
+        assert!(send_gift(&client, 1, 2, 31036, 1, CoinType::Silver)
+            .await
+            .is_err());
+        assert!(send_gift(&client, 1, 2, 1, 1, CoinType::Gold)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use super::model::{FanMedal, GuardLevel};
+use crate::{BiliClient, Paginated, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of guards of a living room.
+pub struct GuardList {
+    pub info: GuardListInfo,
+    /// Guards on this page.
+    pub list: Vec<Guard>,
+    /// Top 3 guards, only filled on the first page.
+    #[serde(default)]
+    pub top3: Vec<Guard>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// Pagination of the guard list.
+pub struct GuardListInfo {
+    /// Total number of guards.
+    pub num: u64,
+    /// Total number of pages.
+    pub page: u64,
+    /// Current page.
+    pub now: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A guard member.
+pub struct Guard {
+    pub uid: u64,
+    pub ruid: u64,
+    pub rank: u64,
+    pub username: String,
+    pub face: String,
+    /// Whether the guard is in the room now.
+    pub is_alive: u8,
+    pub guard_level: GuardLevel,
+    #[serde(default)]
+    pub medal_info: FanMedal,
+}
+
+/// Get a page, starting from `1`, of guards of the living room owned by `ruid`.
+pub async fn get_guard_list(
+    client: &BiliClient,
+    room_id: u64,
+    ruid: u64,
+    page: u64,
+) -> Result<GuardList> {
+    client
+        .get(
+            consts::GUARD_LIST,
+            &[
+                ("roomid", room_id),
+                ("ruid", ruid),
+                ("page", page),
+                ("page_size", 29),
+            ],
+        )
+        .await
+}
+
+/// Walk all guards page by page, the top 3 first, see [`get_guard_list`].
+pub fn paginate_guard_list(client: &BiliClient, room_id: u64, ruid: u64) -> Paginated<Guard> {
+    let client = client.clone();
+    Paginated::by_page(move |page| {
+        let client = client.clone();
+        async move {
+            let mut guards = get_guard_list(&client, room_id, ruid, page).await?;
+            let more = guards.info.now < guards.info.page && !guards.list.is_empty();
+            guards.top3.append(&mut guards.list);
+            Ok((guards.top3, more))
+        }
+    })
+}
@@ -0,0 +1,201 @@
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::consts;
+use crate::auth::Fingerprint;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Parameters for the next heartbeat, returned by both E and X.
+struct HeartbeatState {
+    /// Unix timestamp in seconds.
+    timestamp: i64,
+    /// Seconds until the next heartbeat.
+    heartbeat_interval: u64,
+    secret_key: String,
+    /// Chain of hash algorithms used to sign the next heartbeat.
+    secret_rule: Vec<u8>,
+}
+
+/// Watch-time reporting of a living room, which is how the web player accumulates
+/// watch time and medal intimacy.
+#[derive(Debug)]
+pub struct WebHeartbeat {
+    client: BiliClient,
+    room_id: u64,
+    parent_area_id: u64,
+    area_id: u64,
+    seq: u64,
+    fingerprint: Fingerprint,
+    state: Option<HeartbeatState>,
+}
+
+/// Sign `data` by applying HMAC with each algorithm in `rule` in turn,
+/// the hex output of a step is the input of the next.
+fn sign(data: String, key: &str, rule: &[u8]) -> String {
+    fn hmac<M: Mac + hmac::digest::KeyInit>(key: &str, data: &str) -> String {
+        let mut mac = <M as Mac>::new_from_slice(key.as_bytes()).expect("hmac accepts any key");
+        mac.update(data.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+    rule.iter().fold(data, |data, algorithm| match algorithm {
+        0 => hmac::<Hmac<Md5>>(key, &data),
+        1 => hmac::<Hmac<Sha1>>(key, &data),
+        2 => hmac::<Hmac<Sha256>>(key, &data),
+        3 => hmac::<Hmac<Sha224>>(key, &data),
+        4 => hmac::<Hmac<Sha512>>(key, &data),
+        5 => hmac::<Hmac<Sha384>>(key, &data),
+        _ => {
+            warn!(
+                "(PLEASE REPORT THIS) unknown heartbeat sign rule: {}",
+                algorithm
+            );
+            data
+        }
+    })
+}
+
+fn now_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+impl WebHeartbeat {
+    /// Area ids can be found in the room info, they are part of the signed payload.
+    pub fn new(client: BiliClient, room_id: u64, parent_area_id: u64, area_id: u64) -> Self {
+        let fingerprint = client.fingerprint().unwrap_or_else(Fingerprint::generate);
+        Self {
+            client,
+            room_id,
+            parent_area_id,
+            area_id,
+            seq: 0,
+            fingerprint,
+            state: None,
+        }
+    }
+
+    fn form(&self, csrf: &str, ts: i64) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "id",
+                format!(
+                    "[{},{},{},{}]",
+                    self.parent_area_id, self.area_id, self.seq, self.room_id
+                ),
+            ),
+            (
+                "device",
+                format!(
+                    "[\"{}\",\"{}\"]",
+                    self.fingerprint.buvid3, self.fingerprint.uuid
+                ),
+            ),
+            ("ts", ts.to_string()),
+            ("ua", self.client.user_agent().to_string()),
+            ("csrf_token", csrf.to_string()),
+            ("csrf", csrf.to_string()),
+            ("visit_id", String::new()),
+        ]
+    }
+
+    /// Send the entering heartbeat (E), returns the interval until the next heartbeat.
+    pub async fn enter(&mut self) -> Result<Duration> {
+        self.seq = 0;
+        let mut form = self.form(&self.client.csrf()?, now_millis());
+        form.push(("is_patch", "0".to_string()));
+        form.push(("heart_beat", "[]".to_string()));
+        let state: HeartbeatState = self.client.post_form(consts::HEARTBEAT_E, &form).await?;
+        debug!("web heartbeat entered room {}", self.room_id);
+        let interval = Duration::from_secs(state.heartbeat_interval);
+        self.state = Some(state);
+        Ok(interval)
+    }
+
+    /// Send a watching heartbeat (X), entering first if not yet.
+    pub async fn beat(&mut self) -> Result<Duration> {
+        let state = match &self.state {
+            Some(state) => state.clone(),
+            None => return self.enter().await,
+        };
+        self.seq += 1;
+        let ts = now_millis();
+        let mut form = self.form(&self.client.csrf()?, ts);
+        let payload = format!(
+            "{{\"platform\":\"web\",\"parent_id\":{},\"area_id\":{},\"seq_id\":{},\"room_id\":{},\
+             \"buvid\":\"{}\",\"uuid\":\"{}\",\"ets\":{},\"time\":{},\"ts\":{}}}",
+            self.parent_area_id,
+            self.area_id,
+            self.seq,
+            self.room_id,
+            self.fingerprint.buvid3,
+            self.fingerprint.uuid,
+            state.timestamp,
+            state.heartbeat_interval,
+            ts
+        );
+        form.push(("s", sign(payload, &state.secret_key, &state.secret_rule)));
+        form.push(("ets", state.timestamp.to_string()));
+        form.push(("benchmark", state.secret_key.clone()));
+        form.push(("time", state.heartbeat_interval.to_string()));
+        let state: HeartbeatState = self.client.post_form(consts::HEARTBEAT_X, &form).await?;
+        trace!("web heartbeat {} sent for room {}", self.seq, self.room_id);
+        let interval = Duration::from_secs(state.heartbeat_interval);
+        self.state = Some(state);
+        Ok(interval)
+    }
+
+    /// Keep beating until an error occurs, re-entering once on failure.
+    pub async fn run(mut self) -> Result<()> {
+        let mut retried = false;
+        loop {
+            match self.beat().await {
+                Ok(interval) => {
+                    retried = false;
+                    tokio::time::sleep(interval).await;
+                }
+                Err(e) if !retried => {
+                    warn!("web heartbeat failed, re-entering: {:?}", e);
+                    retried = true;
+                    self.state = None;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl BiliClient {
+    /// Report watch time of the living room in background, needs a session.
+    pub fn spawn_web_heartbeat(
+        &self,
+        room_id: u64,
+        parent_area_id: u64,
+        area_id: u64,
+    ) -> JoinHandle<Result<()>> {
+        tokio::spawn(WebHeartbeat::new(self.clone(), room_id, parent_area_id, area_id).run())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(sign("data".to_string(), "key", &[]), "data");
+        // HMAC-SHA256("key", "data")
+        assert_eq!(
+            sign("data".to_string(), "key", &[2]),
+            "5031fe3d989c6d1537a013fa6e739da23463fdaec3b70137d828e36ace221bd0"
+        );
+        assert_eq!(sign("data".to_string(), "key", &[0, 2]).len(), 64);
+    }
+}
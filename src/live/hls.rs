@@ -0,0 +1,329 @@
+//! Download HLS live streams of fMP4 segments.
+use futures_util::stream::{self, StreamExt};
+use reqwest::Url;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::time::Duration;
+
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A media playlist (`.m3u8`), only the tags needed to follow a live stream.
+pub struct MediaPlaylist {
+    /// Seconds, the longest segment.
+    pub target_duration: f64,
+    pub segments: Vec<HlsSegment>,
+    /// Whether `#EXT-X-ENDLIST` is present, no segment will be added.
+    pub ended: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HlsSegment {
+    /// Media sequence number.
+    pub sequence: u64,
+    /// Absolute url.
+    pub url: String,
+    /// Seconds.
+    pub duration: f64,
+    /// Url of the init section (`#EXT-X-MAP`) the segment needs.
+    pub map: Option<String>,
+    /// Whether `#EXT-X-DISCONTINUITY` precedes the segment.
+    pub discontinuity: bool,
+}
+
+/// Parse a media playlist fetched from `base`, which relative urls are resolved against.
+pub fn parse_playlist(base: &Url, text: &str) -> MediaPlaylist {
+    let resolve = |uri: &str| {
+        base.join(uri)
+            .map(String::from)
+            .unwrap_or_else(|_| uri.to_string())
+    };
+    let mut playlist = MediaPlaylist::default();
+    let mut sequence = 0;
+    let mut map = None;
+    let mut duration = 0.0;
+    let mut discontinuity = false;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+            sequence = value.parse().unwrap_or_default();
+        } else if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            playlist.target_duration = value.parse().unwrap_or_default();
+        } else if let Some(value) = line.strip_prefix("#EXT-X-MAP:") {
+            map = value
+                .split(',')
+                .find_map(|attr| attr.strip_prefix("URI="))
+                .map(|uri| resolve(uri.trim_matches('"')));
+        } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+            duration = value
+                .split(',')
+                .next()
+                .and_then(|d| d.parse().ok())
+                .unwrap_or_default();
+        } else if line == "#EXT-X-DISCONTINUITY" {
+            discontinuity = true;
+        } else if line == "#EXT-X-ENDLIST" {
+            playlist.ended = true;
+        } else if !line.starts_with('#') {
+            playlist.segments.push(HlsSegment {
+                sequence,
+                url: resolve(line),
+                duration,
+                map: map.clone(),
+                discontinuity,
+            });
+            sequence += 1;
+            discontinuity = false;
+        }
+    }
+    playlist
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How [`download_hls`] fetches.
+pub struct HlsConfig {
+    /// Segments downloaded at once.
+    pub parallelism: usize,
+    /// Failures in a row of the playlist before giving up.
+    pub max_retries: usize,
+}
+
+impl Default for HlsConfig {
+    fn default() -> Self {
+        Self {
+            parallelism: 3,
+            max_retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// Tracks which segments of the refreshed playlists are downloaded.
+struct SegmentCursor {
+    /// Sequence of the segment after the last downloaded.
+    next: Option<u64>,
+    /// First sequence of the last playlist.
+    first: u64,
+    /// Sequences of the discontinuities in the last playlist.
+    discontinuities: Vec<u64>,
+}
+
+impl SegmentCursor {
+    /// Segments not downloaded yet.
+    ///
+    /// The media sequence restarts when the stream does, going backwards or reusing the
+    /// sequences already downloaded after a new discontinuity, which starts over from there.
+    fn pending(&mut self, segments: Vec<HlsSegment>) -> Vec<HlsSegment> {
+        let Some(first) = segments.first().map(|segment| segment.sequence) else {
+            return segments;
+        };
+        let restart = match self.next {
+            None => None,
+            Some(_) if first < self.first => Some(first),
+            Some(next) => segments
+                .iter()
+                .find(|segment| {
+                    segment.discontinuity
+                        && segment.sequence < next
+                        && !self.discontinuities.contains(&segment.sequence)
+                })
+                .map(|segment| segment.sequence),
+        };
+        if let Some(sequence) = restart {
+            warn!("hls media sequence restarted at {}", sequence);
+            self.next = Some(sequence);
+        }
+        self.first = first;
+        self.discontinuities = segments
+            .iter()
+            .filter(|segment| segment.discontinuity)
+            .map(|segment| segment.sequence)
+            .collect();
+        segments
+            .into_iter()
+            .filter(|segment| self.next.is_none_or(|next| segment.sequence >= next))
+            .collect()
+    }
+}
+
+/// Download the live stream of the playlist at `url` into `writer` until the playlist ends,
+/// returning the bytes written.
+///
+/// The playlist is refreshed as new segments are added. Segments are fetched in parallel
+/// but written in order, each preceded by its init section when it changes, e.g. after a
+/// discontinuity. A segment failing to download is skipped, and a restarted media sequence
+/// is followed from where it restarted.
+pub async fn download_hls<W>(
+    client: &BiliClient,
+    url: &str,
+    writer: &mut W,
+    config: HlsConfig,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+{
+    let base = Url::parse(url).map_err(|e| Error::UnexpectedResponse(e.to_string()))?;
+    let mut cursor = SegmentCursor::default();
+    let mut written_map = None;
+    let mut written = 0;
+    let mut failures = 0;
+    loop {
+        let playlist = match client.get_bytes(url, &()).await {
+            Ok(bytes) => {
+                failures = 0;
+                parse_playlist(&base, &String::from_utf8_lossy(&bytes))
+            }
+            Err(e) if failures < config.max_retries => {
+                failures += 1;
+                warn!("failed to fetch playlist, retry {}: {:?}", failures, e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let segments = cursor.pending(playlist.segments);
+        if let (Some(next), Some(first)) = (cursor.next, segments.first()) {
+            if first.sequence > next {
+                warn!(
+                    "{} hls segments expired before download",
+                    first.sequence - next
+                );
+            }
+        }
+        let has_new = !segments.is_empty();
+        let mut downloads = stream::iter(segments)
+            .map(|segment| async move {
+                let data = client.get_bytes(&segment.url, &()).await;
+                (segment, data)
+            })
+            .buffered(config.parallelism.max(1));
+        while let Some((segment, data)) = downloads.next().await {
+            cursor.next = Some(segment.sequence + 1);
+            let data = match data {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("skip hls segment {}: {:?}", segment.sequence, e);
+                    continue;
+                }
+            };
+            if segment.map.is_some() && segment.map != written_map {
+                let map = segment.map.as_deref().unwrap_or_default();
+                let init = client.get_bytes(map, &()).await?;
+                writer.write_all(&init).await?;
+                written += init.len() as u64;
+                written_map = segment.map.clone();
+            }
+            writer.write_all(&data).await?;
+            written += data.len() as u64;
+        }
+        if playlist.ended {
+            break;
+        }
+        // check again after a segment's time, sooner when none was added
+        let wait = if has_new {
+            playlist.target_duration
+        } else {
+            playlist.target_duration / 2.0
+        };
+        tokio::time::sleep(Duration::from_secs_f64(wait.max(0.5))).await;
+    }
+    writer.flush().await?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    const PLAYLIST: &str = "#EXTM3U
+#EXT-X-VERSION:7
+#EXT-X-MEDIA-SEQUENCE:100
+#EXT-X-TARGETDURATION:1
+#EXT-X-MAP:URI=\"h1.m4s\"
+#EXTINF:1.00,a3a1|b6dd4e3b
+100.m4s
+#EXTINF:1.00,
+101.m4s
+#EXT-X-DISCONTINUITY
+#EXT-X-MAP:URI=\"h2.m4s\"
+#EXTINF:0.50,
+102.m4s
+#EXT-X-ENDLIST
+";
+
+    #[test]
+    fn test_parse_playlist() {
+        let base = Url::parse("https://a.bilivideo.com/live-bvc/1/index.m3u8?expires=1").unwrap();
+        let playlist = parse_playlist(&base, PLAYLIST);
+        assert!(playlist.ended);
+        assert_eq!(playlist.target_duration, 1.0);
+        let segment = &playlist.segments[2];
+        assert_eq!(segment.sequence, 102);
+        assert_eq!(segment.url, "https://a.bilivideo.com/live-bvc/1/102.m4s");
+        assert_eq!(segment.duration, 0.5);
+        assert!(segment.discontinuity);
+        assert_eq!(
+            segment.map.as_deref(),
+            Some("https://a.bilivideo.com/live-bvc/1/h2.m4s")
+        );
+    }
+
+    fn segments(sequences: std::ops::Range<u64>, discontinuity: Option<u64>) -> Vec<HlsSegment> {
+        sequences
+            .map(|sequence| HlsSegment {
+                sequence,
+                discontinuity: discontinuity == Some(sequence),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn sequences(segments: Vec<HlsSegment>) -> Vec<u64> {
+        segments.iter().map(|segment| segment.sequence).collect()
+    }
+
+    #[test]
+    fn test_cursor_restart() {
+        let mut cursor = SegmentCursor::default();
+        assert_eq!(
+            sequences(cursor.pending(segments(100..103, Some(101)))),
+            [100, 101, 102]
+        );
+        cursor.next = Some(103);
+        // the known discontinuity is not a restart
+        assert_eq!(
+            sequences(cursor.pending(segments(101..104, Some(101)))),
+            [103]
+        );
+        cursor.next = Some(104);
+        // going backwards
+        assert_eq!(sequences(cursor.pending(segments(0..2, None))), [0, 1]);
+        cursor.next = Some(2);
+        assert_eq!(sequences(cursor.pending(segments(1..3, None))), [2]);
+        cursor.next = Some(3);
+        // a new discontinuity reusing sequences
+        assert_eq!(sequences(cursor.pending(segments(1..4, Some(2)))), [2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_download_hls() {
+        let base = "https://a.bilivideo.com/live-bvc/1/";
+        let mut transport = MockTransport::new().respond(
+            &format!("{}index.m3u8", base),
+            200,
+            PLAYLIST.as_bytes().to_vec(),
+        );
+        for name in ["h1", "h2", "100", "101", "102"] {
+            let url = format!("{}{}.m4s", base, name);
+            transport = transport.respond(&url, 200, name.as_bytes().to_vec());
+        }
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let mut output = Vec::new();
+        let url = format!("{}index.m3u8", base);
+        let written = download_hls(&client, &url, &mut output, HlsConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(output, b"h1100101h2102");
+        assert_eq!(written, output.len() as u64);
+    }
+}
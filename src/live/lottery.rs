@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A giveaway by the streamer (天选时刻).
+pub struct AnchorLot {
+    pub id: u64,
+    pub room_id: u64,
+    pub award_name: String,
+    pub award_num: u32,
+    pub award_image: String,
+    /// Danmaku to send to join.
+    pub danmu: String,
+    /// Gift to send to join, `0` if free.
+    pub gift_id: u64,
+    pub gift_name: String,
+    pub gift_num: u32,
+    /// `0` none, `1` follow, `2` fan medal, `3` guard.
+    pub require_type: u8,
+    /// e.g. the medal level needed.
+    pub require_value: u32,
+    /// e.g. `当前主播粉丝勋章至少1级`.
+    pub require_text: String,
+    /// Seconds left.
+    pub time: u64,
+    /// Seconds in total.
+    pub max_time: u64,
+    pub status: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Winners of an [`AnchorLot`].
+pub struct AnchorLotAward {
+    pub id: u64,
+    pub award_name: String,
+    pub award_num: u32,
+    pub award_image: String,
+    pub award_users: Vec<LotWinner>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LotWinner {
+    pub uid: u64,
+    pub uname: String,
+    pub face: String,
+    /// Count of awards won.
+    pub num: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A red envelope of gifts sent by a viewer (人气红包).
+pub struct RedPocket {
+    pub lot_id: u64,
+    pub sender_uid: u64,
+    pub sender_name: String,
+    pub sender_face: String,
+    /// `1` following the streamer needed.
+    pub join_requirement: u8,
+    /// Danmaku to send to join.
+    pub danmu: String,
+    /// Unix timestamps in seconds.
+    pub start_time: i64,
+    pub end_time: i64,
+    /// Seconds from the start to the draw.
+    pub last_time: u64,
+    pub lot_status: u8,
+    /// Price in 电池 * 100 of all awards.
+    pub total_price: u64,
+    pub awards: Vec<RedPocketAward>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedPocketAward {
+    pub gift_id: u64,
+    pub gift_name: String,
+    pub gift_pic: String,
+    pub num: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Giveaways running in a room.
+pub struct LotteryInfo {
+    /// `None` if the streamer runs none.
+    pub anchor: Option<AnchorLot>,
+    #[serde(deserialize_with = "crate::de::null_default")]
+    pub popularity_red_pocket: Vec<RedPocket>,
+}
+
+/// Get the giveaways and red envelopes running in a room.
+pub async fn get_lottery_info(client: &BiliClient, room_id: u64) -> Result<LotteryInfo> {
+    client
+        .get(consts::LOTTERY_INFO, &[("roomid", room_id)])
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_lottery_info() {
+        let transport = MockTransport::new().json(
+            consts::LOTTERY_INFO,
+            json!({"code": 0, "data": {
+                "anchor": {
+                    "id": 1, "room_id": 14507014, "award_name": "手办", "award_num": 1,
+                    "danmu": "冲", "gift_id": 0, "require_type": 1, "time": 60, "max_time": 600,
+                },
+                "popularity_red_pocket": null,
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let info = get_lottery_info(&client, 14507014).await.unwrap();
+        assert_eq!(info.anchor.unwrap().danmu, "冲");
+        assert!(info.popularity_red_pocket.is_empty());
+    }
+}
@@ -1,10 +1,78 @@
-use crate::{ApiResponse, Result};
+use std::collections::HashMap;
+
+use crate::{BiliClient, Result};
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
+mod admin;
+mod area;
+mod auto_record;
+mod card;
 pub mod consts;
+pub mod danmaku_export;
+mod emoticon;
+pub mod event;
+pub mod flv;
+mod follow;
+mod gift;
+mod guard;
+mod heartbeat;
+mod hls;
+mod lottery;
+pub mod model;
+#[cfg(feature = "native")]
+mod multi;
+mod play_info;
+mod probe;
+pub mod record;
+pub mod replay;
+mod send;
+mod sender;
+mod sign;
+pub mod sink;
+mod state;
+mod streamer;
+pub mod tasks;
+mod wallet;
 pub mod ws;
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub use admin::{
+    add_admin, ban_user, cancel_silent, get_banned_users, remove_admin, set_silent, unban_user,
+    BanDuration, BannedList, BannedUser, SilentMode,
+};
+pub use area::{
+    get_area_list, get_rooms_by_area, Area, AreaRoom, AreaRoomList, AreaSort, ParentArea,
+};
+pub use auto_record::{AutoRecordConfig, AutoRecorder};
+pub use card::{get_user_card_in_room, LiveUserCard};
+pub use emoticon::{get_emoticons, Emoticon, EmoticonPack};
+pub use follow::{get_followed_live, FollowedLiveList, FollowedRoom};
+pub use gift::{get_gift_config, send_gift, CoinType, Gift, GiftConfig, SentGift};
+pub use guard::{get_guard_list, paginate_guard_list, Guard, GuardList, GuardListInfo};
+pub use heartbeat::WebHeartbeat;
+pub use hls::{download_hls, parse_playlist, HlsConfig, HlsSegment, MediaPlaylist};
+pub use lottery::{
+    get_lottery_info, AnchorLot, AnchorLotAward, LotWinner, LotteryInfo, RedPocket, RedPocketAward,
+};
+pub use model::{FanMedal, GuardLevel};
+#[cfg(feature = "native")]
+pub use multi::MultiRoomStream;
+pub use play_info::{
+    get_room_play_info, LiveCodec, LiveFormat, LivePlayUrl, LivePlayUrlInfo, LiveProtocol,
+    LiveQuality, LiveStream, LiveStreamCodec, LiveStreamFormat, LiveUrlInfo, QualityPreference,
+    RoomPlayInfo, RoomPlayInfoOptions, SelectedStream,
+};
+pub use probe::{probe_fastest, probe_stream, StreamProbe};
+pub use send::{send_danmaku, LiveDanmakuDraft};
+pub use sender::{DanmakuSender, DanmakuSenderConfig};
+pub use sign::{do_sign, get_sign_info, SignInfo, SignResult};
+pub use state::{LiveStateTracker, RoomState, StateChange};
+pub use streamer::{start_live, stop_live, update_room, Rtmp};
+pub use wallet::{get_wallet, Wallet};
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// Living room Info.
 pub struct RoomInit {
     pub room_id: u64,
@@ -25,7 +93,33 @@ pub struct RoomInit {
     pub special_type: u64,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Living room details, with the title and area.
+pub struct RoomInfo {
+    pub room_id: u64,
+    pub short_id: u64,
+    pub uid: u64,
+    pub title: String,
+    pub description: String,
+    pub live_status: u64,
+    /// e.g. `2022-01-01 20:00:00`, `0000-00-00 00:00:00` when not living.
+    pub live_time: String,
+    pub area_id: u64,
+    pub area_name: String,
+    pub parent_area_id: u64,
+    pub parent_area_name: String,
+    pub user_cover: String,
+    pub keyframe: String,
+    pub online: u64,
+    pub attention: u64,
+    /// Fields not known to this crate, kept to survive upstream changes.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// DanmakuInfo
 pub struct DanmakuInfo {
     pub group: String,
@@ -35,9 +129,13 @@ pub struct DanmakuInfo {
     pub max_delay: u32,
     pub token: String,
     pub host_list: Vec<DanmakuHost>,
+    /// Fields not known to this crate, kept to survive upstream changes.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// DanmakuHost information
 pub struct DanmakuHost {
     pub host: String,
@@ -46,7 +144,8 @@ pub struct DanmakuHost {
     pub ws_port: u16,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// Playback Url Infos
 pub struct PlayUrlInfos {
     pub current_quality: u16,
@@ -56,14 +155,16 @@ pub struct PlayUrlInfos {
     pub durl: Vec<PlayUrl>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// Quality description
 pub struct QualityDescription {
     pub qn: u32,
     pub desc: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 /// Playback Url
 pub struct PlayUrl {
     pub url: String,
@@ -74,56 +175,246 @@ pub struct PlayUrl {
 }
 
 /// Get the living room info.
-pub async fn room_init(room_id: u64) -> Result<RoomInit> {
-    let url = format!("{}?id={}", consts::ROOM_INIT, room_id);
-    debug!("room_init request to: {}", url);
-    let response: ApiResponse<RoomInit> = reqwest::get(url).await?.json().await?;
-    debug!("response: {}", serde_json::to_string(&response).unwrap());
-    Ok(response.into_data())
+pub async fn room_init(client: &BiliClient, room_id: u64) -> Result<RoomInit> {
+    let room: RoomInit = client.get(consts::ROOM_INIT, &[("id", room_id)]).await?;
+    client.cache_room_id(room_id, room.room_id);
+    Ok(room)
+}
+
+/// Get the details of a living room, `room_id` being the real one.
+pub async fn get_room_info(client: &BiliClient, room_id: u64) -> Result<RoomInfo> {
+    client.get(consts::ROOM_INFO, &[("room_id", room_id)]).await
+}
+
+/// Get the info of many living rooms, with at most `max_concurrency` requests in flight,
+/// keyed by the given ids.
+///
+/// Requests go through the rate limiter of the client like any other.
+pub async fn room_init_many(
+    client: &BiliClient,
+    ids: impl IntoIterator<Item = u64>,
+    max_concurrency: usize,
+) -> HashMap<u64, Result<RoomInit>> {
+    stream::iter(ids)
+        .map(|id| async move { (id, room_init(client, id).await) })
+        .buffer_unordered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Map a short room id to the real one, real ids map to themselves.
+///
+/// Results are cached on the client for an hour, shared with [`room_init`].
+pub async fn resolve_room_id(client: &BiliClient, id: u64) -> Result<u64> {
+    if let Some(room_id) = client.cached_room_id(id) {
+        return Ok(room_id);
+    }
+    Ok(room_init(client, id).await?.room_id)
 }
 
 /// Get the danmaku server info.
-pub async fn get_danmaku_info(room_id: u64) -> Result<DanmakuInfo> {
-    let url = format!("{}?id={}&type=0", consts::DANMAKU_SERVER_CONF, room_id);
-    debug!("get_danmaku_info request to: {}", url);
-    let response: ApiResponse<DanmakuInfo> = reqwest::get(url).await?.json().await?;
-    debug!("response: {}", serde_json::to_string(&response).unwrap());
-    Ok(response.into_data())
+pub async fn get_danmaku_info(client: &BiliClient, room_id: u64) -> Result<DanmakuInfo> {
+    client
+        .get(consts::DANMAKU_SERVER_CONF, &[("id", room_id), ("type", 0)])
+        .await
 }
 
-pub async fn get_play_url_info(room_id: u64) -> Result<PlayUrlInfos> {
-    let url = format!("{}?cid={}&platform=web", consts::PLAY_URL, room_id);
-    debug!("get_play_url_info request to: {}", url);
-    let response: ApiResponse<PlayUrlInfos> = reqwest::get(url).await?.json().await?;
-    debug!("response: {}", serde_json::to_string(&response).unwrap());
-    Ok(response.into_data())
+/// Get the flv urls with the legacy api, see [`get_room_play_info`] for HLS and fMP4.
+pub async fn get_play_url_info(client: &BiliClient, room_id: u64) -> Result<PlayUrlInfos> {
+    client
+        .get(
+            consts::PLAY_URL,
+            &[
+                ("cid", room_id.to_string()),
+                ("platform", "web".to_string()),
+            ],
+        )
+        .await
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct HistoryDanmakus {
+    room: Vec<HistoryDanmaku>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct HistoryDanmaku {
+    text: String,
+    uid: u64,
+    nickname: String,
+    #[serde(default)]
+    isadmin: u8,
+    #[serde(default)]
+    medal: Value,
+    #[serde(default)]
+    user_level: Vec<Value>,
+    check_info: HistoryCheckInfo,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct HistoryCheckInfo {
+    ts: i64,
+}
+
+impl From<HistoryDanmaku> for event::Danmaku {
+    fn from(history: HistoryDanmaku) -> Self {
+        Self {
+            mode: 1,
+            font_size: 25,
+            color: 0xffffff,
+            timestamp: history.check_info.ts * 1000,
+            content: history.text,
+            uid: history.uid,
+            uname: history.nickname,
+            is_admin: history.isadmin == 1,
+            user_level: history
+                .user_level
+                .first()
+                .and_then(Value::as_u64)
+                .unwrap_or_default() as u32,
+            medal: FanMedal::from_info(&history.medal),
+        }
+    }
+}
+
+/// Get the latest danmaku sent in the living room, usually 10 of them.
+pub async fn get_history_danmaku(client: &BiliClient, room_id: u64) -> Result<Vec<event::Danmaku>> {
+    let history: HistoryDanmakus = client
+        .get(consts::HISTORY_DANMAKU, &[("roomid", room_id)])
+        .await?;
+    Ok(history.room.into_iter().map(Into::into).collect())
 }
 
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    fn client(url: &str, data: Value) -> BiliClient {
+        let transport = MockTransport::new().json(url, json!({"code": 0, "data": data}));
+        BiliClient::builder().transport(transport).build().unwrap()
+    }
 
     #[tokio::test]
     async fn test_room_init() {
-        pretty_env_logger::try_init().ok();
-        let resp = room_init(14507014).await.unwrap();
-        info!("{:?}", resp);
+        let client = client(
+            consts::ROOM_INIT,
+            json!({
+                "room_id": 14507014, "short_id": 0, "uid": 6067854, "need_p2p": 0,
+                "is_hidden": false, "is_locked": false, "is_portrait": false,
+                "live_status": 1, "hidden_till": 0, "lock_till": 0, "encrypted": false,
+                "pwd_verified": false, "live_time": 1639000000, "room_shield": 0,
+                "is_sp": 0, "special_type": 0,
+            }),
+        );
+        let resp = room_init(&client, 14507014).await.unwrap();
         assert_eq!(resp.room_id, 14507014);
     }
 
+    #[tokio::test]
+    async fn test_room_init_many() {
+        let client = client(
+            consts::ROOM_INIT,
+            json!({"room_id": 14507014, "uid": 6067854, "live_status": 1}),
+        );
+        let rooms = room_init_many(&client, vec![14507014, 1017], 2).await;
+        assert_eq!(rooms.len(), 2);
+        assert_eq!(rooms[&1017].as_ref().unwrap().room_id, 14507014);
+    }
+
+    #[tokio::test]
+    async fn test_room_init_schema_drift() {
+        // `special_type` dropped, `is_new_field` added upstream, which is ignored
+        let client = client(
+            consts::ROOM_INIT,
+            json!({"room_id": 14507014, "uid": 6067854, "live_status": 1, "is_new_field": 1}),
+        );
+        let resp = room_init(&client, 14507014).await.unwrap();
+        assert_eq!(resp.uid, 6067854);
+        assert_eq!(resp.special_type, 0);
+    }
+
     #[tokio::test]
     async fn test_get_danmaku_info() {
-        pretty_env_logger::try_init().ok();
-        let resp = get_danmaku_info(14507014).await.unwrap();
-        info!("{:?}", resp);
+        let client = client(
+            consts::DANMAKU_SERVER_CONF,
+            json!({
+                "group": "live", "business_id": 0, "refresh_row_factor": 0.125,
+                "refresh_rate": 100, "max_delay": 5000, "token": "token",
+                "host_list": [{
+                    "host": "broadcastlv.chat.bilibili.com",
+                    "port": 2243, "wss_port": 443, "ws_port": 2244,
+                }],
+            }),
+        );
+        let resp = get_danmaku_info(&client, 14507014).await.unwrap();
         assert!(resp.host_list.len() > 0);
     }
 
     #[tokio::test]
     async fn test_get_play_url_info() {
-        pretty_env_logger::try_init().ok();
-        let resp = get_play_url_info(14507014).await.unwrap();
-        info!("{:?}", resp);
+        let client = client(
+            consts::PLAY_URL,
+            json!({
+                "current_quality": 4, "accept_quality": ["4"], "current_qn": 10000,
+                "quality_description": [{"qn": 10000, "desc": "原画"}],
+                "durl": [{
+                    "url": "https://example.com/live.flv", "length": 0, "order": 1,
+                    "stream_type": 0, "p2p_type": 0,
+                }],
+            }),
+        );
+        let resp = get_play_url_info(&client, 14507014).await.unwrap();
         assert!(resp.durl.len() > 0);
     }
+
+    #[tokio::test]
+    async fn test_resolve_room_id() {
+        let transport = MockTransport::new().json(
+            consts::ROOM_INIT,
+            json!({"code": 0, "data": {"room_id": 14507014, "short_id": 1}}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        assert_eq!(resolve_room_id(&client, 1).await.unwrap(), 14507014);
+        assert_eq!(resolve_room_id(&client, 1).await.unwrap(), 14507014);
+        assert_eq!(resolve_room_id(&client, 14507014).await.unwrap(), 14507014);
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_decode_error() {
+        let client = client(consts::ROOM_INIT, json!({"room_id": "not a number"}));
+        match room_init(&client, 1).await {
+            Err(crate::Error::Decode { endpoint, body, .. }) => {
+                assert_eq!(endpoint, consts::ROOM_INIT);
+                assert!(body.contains("not a number"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_room_not_exist() {
+        let transport = MockTransport::new().json(
+            consts::ROOM_INIT,
+            json!({"code": 60004, "msg": "直播间不存在", "data": {}}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        match room_init(&client, 1).await {
+            Err(crate::Error::Api { code, raw, .. }) => {
+                assert_eq!(code, crate::ErrorCode::RoomNotExist);
+                assert_eq!(raw, 60004);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(transport.requests()[0].query(), Some("id=1"));
+    }
 }
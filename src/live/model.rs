@@ -0,0 +1,145 @@
+//! Types shared by live APIs and events.
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "u8", into = "u8")]
+/// Guard (大航海) level bought for a streamer, `0` to `3` on the wire.
+pub enum GuardLevel {
+    #[default]
+    None,
+    /// 总督, `1`.
+    Governor,
+    /// 提督, `2`.
+    Admiral,
+    /// 舰长, `3`.
+    Captain,
+}
+
+impl GuardLevel {
+    pub fn is_guard(&self) -> bool {
+        *self != GuardLevel::None
+    }
+
+    /// e.g. `舰长`, empty for none.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GuardLevel::None => "",
+            GuardLevel::Governor => "总督",
+            GuardLevel::Admiral => "提督",
+            GuardLevel::Captain => "舰长",
+        }
+    }
+}
+
+impl From<u8> for GuardLevel {
+    fn from(level: u8) -> Self {
+        match level {
+            1 => GuardLevel::Governor,
+            2 => GuardLevel::Admiral,
+            3 => GuardLevel::Captain,
+            _ => GuardLevel::None,
+        }
+    }
+}
+
+impl From<GuardLevel> for u8 {
+    fn from(level: GuardLevel) -> Self {
+        match level {
+            GuardLevel::None => 0,
+            GuardLevel::Governor => 1,
+            GuardLevel::Admiral => 2,
+            GuardLevel::Captain => 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A fan medal of a streamer, as worn by a user.
+///
+/// Each api names the fields differently, missing ones are left empty.
+pub struct FanMedal {
+    #[serde(alias = "medal_level")]
+    pub level: u32,
+    #[serde(alias = "medal_name")]
+    pub name: String,
+    /// Uid of the streamer.
+    #[serde(alias = "ruid")]
+    pub target_id: u64,
+    /// Name of the streamer.
+    #[serde(alias = "target_name")]
+    pub anchor_uname: String,
+    #[serde(alias = "anchor_roomid", alias = "roomid")]
+    pub room_id: u64,
+    /// Guard level of the wearer for the streamer.
+    pub guard_level: GuardLevel,
+    /// Whether the medal is lit, i.e. the wearer interacted recently.
+    #[serde(deserialize_with = "bool_or_number")]
+    pub is_lighted: bool,
+}
+
+impl FanMedal {
+    /// Parse the medal array of danmaku,
+    /// e.g. `[12, "name", "anchor", 14507014, color, "", 0, .., guard, lighted, ruid]`.
+    pub fn from_info(medal: &Value) -> Option<Self> {
+        let number = |index: usize| medal.get(index).and_then(Value::as_u64);
+        Some(Self {
+            level: number(0)? as u32,
+            name: medal.get(1)?.as_str()?.to_string(),
+            anchor_uname: medal.get(2)?.as_str()?.to_string(),
+            room_id: number(3)?,
+            guard_level: GuardLevel::from(number(10).unwrap_or_default() as u8),
+            is_lighted: number(11).unwrap_or(1) == 1,
+            target_id: number(12).unwrap_or_default(),
+        })
+    }
+}
+
+fn bool_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrNumber {
+        Bool(bool),
+        Number(u64),
+    }
+
+    Ok(match BoolOrNumber::deserialize(deserializer)? {
+        BoolOrNumber::Bool(b) => b,
+        BoolOrNumber::Number(n) => n != 0,
+    })
+}
+
+/// Deserialize a medal which is all zeros when not worn as `None`.
+pub(crate) fn worn_medal<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<FanMedal>, D::Error> {
+    let medal = Option::<FanMedal>::deserialize(deserializer)?;
+    Ok(medal.filter(|medal| medal.level > 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fan_medal() {
+        let medal: FanMedal = serde_json::from_value(json!({
+            "medal_level": 21, "medal_name": "medal", "target_id": 6067854,
+            "anchor_roomid": 14507014, "guard_level": 3, "is_lighted": 1,
+        }))
+        .unwrap();
+        assert_eq!(medal.room_id, 14507014);
+        assert_eq!(medal.guard_level, GuardLevel::Captain);
+        assert!(medal.is_lighted);
+        assert_eq!(serde_json::to_value(medal.guard_level).unwrap(), 3);
+
+        let medal = FanMedal::from_info(&json!([
+            12, "medal", "anchor", 14507014, 1725515, "", 0, 0, 0, 0, 2, 1, 6067854
+        ]))
+        .unwrap();
+        assert_eq!(medal.guard_level, GuardLevel::Admiral);
+        assert_eq!(medal.target_id, 6067854);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+use super::event::LiveEvent;
+use super::ws::{DanmakuStream, DanmakuStreamConfig};
+use crate::{BiliClient, Result};
+
+#[derive(Debug)]
+struct Backoff {
+    delay: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+#[derive(Clone, Debug)]
+/// Exponential backoff shared by all rooms, so a flaky network doesn't cause a reconnect storm.
+///
+/// Both connecting a room and reconnecting its [`DanmakuStream`] go through it.
+pub(crate) struct SharedBackoff(Arc<Mutex<Backoff>>);
+
+impl SharedBackoff {
+    pub(crate) fn new(min: Duration, max: Duration) -> Self {
+        Self(Arc::new(Mutex::new(Backoff {
+            delay: Duration::default(),
+            min,
+            max,
+        })))
+    }
+
+    /// Wait out the current delay before an attempt.
+    pub(crate) async fn wait(&self) {
+        let delay = self.0.lock().await.delay;
+        tokio::time::sleep(delay).await;
+    }
+
+    pub(crate) async fn failed(&self) {
+        let mut backoff = self.0.lock().await;
+        backoff.delay = (backoff.delay * 2).clamp(backoff.min, backoff.max);
+    }
+
+    pub(crate) async fn succeeded(&self) {
+        self.0.lock().await.delay = Duration::default();
+    }
+
+    #[cfg(test)]
+    pub(crate) async fn delay(&self) -> Duration {
+        self.0.lock().await.delay
+    }
+}
+
+/// Danmaku streams of many rooms merged into a single receiver.
+pub struct MultiRoomStream {
+    client: BiliClient,
+    config: DanmakuStreamConfig,
+    max_retries: usize,
+    backoff: SharedBackoff,
+    rooms: HashMap<u64, (DanmakuStream, JoinHandle<()>)>,
+    event_tx: mpsc::Sender<(u64, LiveEvent)>,
+}
+
+impl MultiRoomStream {
+    /// Create an empty multiplexer, events are tagged by the room id given to [`Self::add_room`].
+    ///
+    /// All rooms connect through `client`, sharing its connection pool and network settings.
+    pub fn new(
+        client: &BiliClient,
+        config: DanmakuStreamConfig,
+    ) -> (Self, mpsc::Receiver<(u64, LiveEvent)>) {
+        let (event_tx, event_rx) = mpsc::channel(config.buffer_capacity);
+        (
+            Self {
+                client: client.clone(),
+                config,
+                max_retries: 3,
+                backoff: SharedBackoff::new(Duration::from_secs(1), Duration::from_secs(60)),
+                rooms: HashMap::new(),
+                event_tx,
+            },
+            event_rx,
+        )
+    }
+
+    /// Set how many times connecting a room is retried before [`Self::add_room`] gives up.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Connect to a room, does nothing if the room is already added.
+    pub async fn add_room(&mut self, room_id: u64) -> Result<()> {
+        if self.rooms.contains_key(&room_id) {
+            return Ok(());
+        }
+        let mut retries = 0;
+        let (stream, mut pkt_rx) = loop {
+            self.backoff.wait().await;
+            let config = self.config.clone();
+            let backoff = Some(self.backoff.clone());
+            match DanmakuStream::new_with_backoff(&self.client, room_id, config, backoff).await {
+                Ok(connected) => {
+                    self.backoff.succeeded().await;
+                    break connected;
+                }
+                Err(e) if retries < self.max_retries => {
+                    warn!("failed to connect room {}, will retry: {:?}", room_id, e);
+                    self.backoff.failed().await;
+                    retries += 1;
+                }
+                Err(e) => {
+                    self.backoff.failed().await;
+                    return Err(e);
+                }
+            }
+        };
+
+        let event_tx = self.event_tx.clone();
+        let forwarder = tokio::spawn(async move {
+            loop {
+                let pkt = match pkt_rx.recv().await {
+                    Ok(pkt) => pkt,
+                    Err(RecvError::Lagged(n)) => {
+                        warn!("room {} lagged, {} packets skipped", room_id, n);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                match LiveEvent::from_packet(&pkt) {
+                    Ok(Some(event)) => {
+                        if event_tx.send((room_id, event)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("failed to decode packet from room {}: {:?}", room_id, e),
+                }
+            }
+        });
+        self.rooms.insert(room_id, (stream, forwarder));
+        debug!("room {} added to multi room stream", room_id);
+        Ok(())
+    }
+
+    /// Disconnect from a room, returns `false` if the room was not added.
+    pub async fn remove_room(&mut self, room_id: u64) -> bool {
+        if let Some((stream, forwarder)) = self.rooms.remove(&room_id) {
+            forwarder.abort();
+            stream.close().await;
+            debug!("room {} removed from multi room stream", room_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Ids of all added rooms.
+    pub fn rooms(&self) -> impl Iterator<Item = u64> + '_ {
+        self.rooms.keys().copied()
+    }
+
+    /// Disconnect from all rooms.
+    pub async fn close(&mut self) {
+        for (_, (stream, forwarder)) in self.rooms.drain() {
+            forwarder.abort();
+            stream.close().await;
+        }
+    }
+}
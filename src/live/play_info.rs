@@ -0,0 +1,424 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Transfer protocol of a live stream.
+pub enum LiveProtocol {
+    /// Plain HTTP, usually flv.
+    HttpStream,
+    /// HLS, ts or fMP4 segments.
+    HttpHls,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Container format of a live stream.
+pub enum LiveFormat {
+    Flv,
+    Ts,
+    Fmp4,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Video codec of a live stream.
+pub enum LiveCodec {
+    Avc,
+    Hevc,
+}
+
+impl LiveProtocol {
+    fn id(&self) -> u8 {
+        match self {
+            LiveProtocol::HttpStream => 0,
+            LiveProtocol::HttpHls => 1,
+        }
+    }
+
+    /// The `protocol_name` in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LiveProtocol::HttpStream => "http_stream",
+            LiveProtocol::HttpHls => "http_hls",
+        }
+    }
+}
+
+impl LiveFormat {
+    fn id(&self) -> u8 {
+        match self {
+            LiveFormat::Flv => 0,
+            LiveFormat::Ts => 1,
+            LiveFormat::Fmp4 => 2,
+        }
+    }
+
+    /// The `format_name` in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LiveFormat::Flv => "flv",
+            LiveFormat::Ts => "ts",
+            LiveFormat::Fmp4 => "fmp4",
+        }
+    }
+}
+
+impl LiveCodec {
+    fn id(&self) -> u8 {
+        match self {
+            LiveCodec::Avc => 0,
+            LiveCodec::Hevc => 1,
+        }
+    }
+
+    /// The `codec_name` in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LiveCodec::Avc => "avc",
+            LiveCodec::Hevc => "hevc",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// What [`get_room_play_info`] asks for.
+pub struct RoomPlayInfoOptions {
+    pub protocols: Vec<LiveProtocol>,
+    pub formats: Vec<LiveFormat>,
+    pub codecs: Vec<LiveCodec>,
+    /// Wanted quality, e.g. `10000` for the original, the server may give a lower one.
+    pub qn: u32,
+}
+
+impl Default for RoomPlayInfoOptions {
+    fn default() -> Self {
+        Self {
+            protocols: vec![LiveProtocol::HttpStream, LiveProtocol::HttpHls],
+            formats: vec![LiveFormat::Flv, LiveFormat::Ts, LiveFormat::Fmp4],
+            codecs: vec![LiveCodec::Avc, LiveCodec::Hevc],
+            qn: 10000,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// What [`RoomPlayInfo::select`] looks for, each list ordered by preference.
+pub struct QualityPreference {
+    /// Highest quality wanted.
+    pub qn: u32,
+    pub protocols: Vec<LiveProtocol>,
+    pub formats: Vec<LiveFormat>,
+    pub codecs: Vec<LiveCodec>,
+}
+
+impl Default for QualityPreference {
+    fn default() -> Self {
+        Self {
+            qn: 10000,
+            protocols: vec![LiveProtocol::HttpStream, LiveProtocol::HttpHls],
+            formats: vec![LiveFormat::Flv, LiveFormat::Fmp4, LiveFormat::Ts],
+            codecs: vec![LiveCodec::Avc, LiveCodec::Hevc],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+/// A stream picked by [`RoomPlayInfo::select`].
+pub struct SelectedStream<'a> {
+    pub protocol: LiveProtocol,
+    pub format: LiveFormat,
+    pub codec: LiveCodec,
+    pub stream: &'a LiveStreamCodec,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Room status and its playback streams.
+pub struct RoomPlayInfo {
+    pub room_id: u64,
+    pub short_id: u64,
+    pub uid: u64,
+    pub live_status: u64,
+    pub live_time: i64,
+    pub is_portrait: bool,
+    pub encrypted: bool,
+    pub pwd_verified: bool,
+    /// Absent when the room is not living.
+    pub playurl_info: Option<LivePlayUrlInfo>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LivePlayUrlInfo {
+    /// Player settings as stringified json.
+    pub conf_json: String,
+    pub playurl: LivePlayUrl,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LivePlayUrl {
+    pub cid: u64,
+    /// Descriptions of all qualities.
+    pub g_qn_desc: Vec<LiveQuality>,
+    pub stream: Vec<LiveStream>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LiveQuality {
+    pub qn: u32,
+    /// e.g. `原画`.
+    pub desc: String,
+    pub hdr_desc: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Streams of a protocol.
+pub struct LiveStream {
+    /// e.g. `http_stream`, see [`LiveProtocol::as_str`].
+    pub protocol_name: String,
+    pub format: Vec<LiveStreamFormat>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Streams of a container format.
+pub struct LiveStreamFormat {
+    /// e.g. `flv`, see [`LiveFormat::as_str`].
+    pub format_name: String,
+    pub codec: Vec<LiveStreamCodec>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// A playable stream, served by each of `url_info`.
+pub struct LiveStreamCodec {
+    /// e.g. `avc`, see [`LiveCodec::as_str`].
+    pub codec_name: String,
+    pub current_qn: u32,
+    pub accept_qn: Vec<u32>,
+    /// Path of the stream, between the host and the extra query.
+    pub base_url: String,
+    pub url_info: Vec<LiveUrlInfo>,
+    pub hdr_qn: Option<u32>,
+    pub dolby_type: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LiveUrlInfo {
+    /// e.g. `https://cn-gddg-ct-01-01.bilivideo.com`.
+    pub host: String,
+    /// Query carrying the signature.
+    pub extra: String,
+    /// Seconds before the url expires.
+    pub stream_ttl: u64,
+}
+
+impl RoomPlayInfo {
+    /// Find the stream of the protocol, format and codec, if the server gave one.
+    pub fn find(
+        &self,
+        protocol: LiveProtocol,
+        format: LiveFormat,
+        codec: LiveCodec,
+    ) -> Option<&LiveStreamCodec> {
+        self.playurl_info
+            .as_ref()?
+            .playurl
+            .stream
+            .iter()
+            .filter(|stream| stream.protocol_name == protocol.as_str())
+            .flat_map(|stream| &stream.format)
+            .filter(|f| f.format_name == format.as_str())
+            .flat_map(|f| &f.codec)
+            .find(|c| c.codec_name == codec.as_str())
+    }
+
+    /// Pick the best stream allowed by `preference`.
+    ///
+    /// Streams are ranked by quality first: the highest one not above `preference.qn`,
+    /// or the lowest one if all are above. Ties are broken by the order of
+    /// `protocols`, then `formats`, then `codecs`. Streams of a protocol, format or
+    /// codec not listed are never picked.
+    ///
+    /// The server only gives one quality per request, if `preference.qn` is in
+    /// `accept_qn` but not picked, request again with it in [`RoomPlayInfoOptions::qn`].
+    pub fn select(&self, preference: &QualityPreference) -> Option<SelectedStream<'_>> {
+        let mut candidates = Vec::new();
+        for (p, &protocol) in preference.protocols.iter().enumerate() {
+            for (f, &format) in preference.formats.iter().enumerate() {
+                for (c, &codec) in preference.codecs.iter().enumerate() {
+                    if let Some(stream) = self.find(protocol, format, codec) {
+                        let selected = SelectedStream {
+                            protocol,
+                            format,
+                            codec,
+                            stream,
+                        };
+                        candidates.push(((p, f, c), selected));
+                    }
+                }
+            }
+        }
+        candidates
+            .into_iter()
+            .min_by_key(|(order, selected)| {
+                let qn = selected.stream.current_qn;
+                // within the limit, higher is better; beyond it, lower is better
+                let rank = if qn <= preference.qn {
+                    (0, u32::MAX - qn)
+                } else {
+                    (1, qn)
+                };
+                (rank, *order)
+            })
+            .map(|(_, selected)| selected)
+    }
+}
+
+impl LiveStreamCodec {
+    /// Full urls of the stream, one per host.
+    pub fn urls(&self) -> Vec<String> {
+        self.url_info
+            .iter()
+            .map(|info| format!("{}{}{}", info.host, self.base_url, info.extra))
+            .collect()
+    }
+}
+
+fn join_ids(ids: impl Iterator<Item = u8>) -> String {
+    ids.map(|id| id.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// Get the playback streams of a living room with the v2 api, covering HLS and fMP4.
+pub async fn get_room_play_info(
+    client: &BiliClient,
+    room_id: u64,
+    options: &RoomPlayInfoOptions,
+) -> Result<RoomPlayInfo> {
+    client
+        .get(
+            consts::ROOM_PLAY_INFO,
+            &[
+                ("room_id", room_id.to_string()),
+                (
+                    "protocol",
+                    join_ids(options.protocols.iter().map(LiveProtocol::id)),
+                ),
+                (
+                    "format",
+                    join_ids(options.formats.iter().map(LiveFormat::id)),
+                ),
+                ("codec", join_ids(options.codecs.iter().map(LiveCodec::id))),
+                ("qn", options.qn.to_string()),
+                ("platform", "web".to_string()),
+                ("ptype", "8".to_string()),
+            ],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_room_play_info() {
+        let codec = |name: &str| {
+            json!({
+                "codec_name": name, "current_qn": 10000, "accept_qn": [10000, 400],
+                "base_url": format!("/live-bvc/123/live_{}.m3u8?", name),
+                "url_info": [{"host": "https://cn.bilivideo.com", "extra": "expires=1", "stream_ttl": 3600}],
+                "hdr_qn": null, "dolby_type": 0,
+            })
+        };
+        let transport = MockTransport::new().json(
+            consts::ROOM_PLAY_INFO,
+            json!({"code": 0, "data": {
+                "room_id": 14507014, "short_id": 0, "uid": 6067854, "live_status": 1,
+                "playurl_info": {"conf_json": "{}", "playurl": {
+                    "cid": 14507014,
+                    "g_qn_desc": [{"qn": 10000, "desc": "原画", "hdr_desc": ""}],
+                    "stream": [{"protocol_name": "http_hls", "format": [
+                        {"format_name": "ts", "codec": [codec("avc")]},
+                        {"format_name": "fmp4", "codec": [codec("avc"), codec("hevc")]},
+                    ]}],
+                }},
+            }}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let info = get_room_play_info(&client, 14507014, &RoomPlayInfoOptions::default())
+            .await
+            .unwrap();
+        let hevc = info
+            .find(LiveProtocol::HttpHls, LiveFormat::Fmp4, LiveCodec::Hevc)
+            .unwrap();
+        assert_eq!(
+            hevc.urls(),
+            vec!["https://cn.bilivideo.com/live-bvc/123/live_hevc.m3u8?expires=1"]
+        );
+        assert!(info
+            .find(LiveProtocol::HttpStream, LiveFormat::Flv, LiveCodec::Avc)
+            .is_none());
+        let query = transport.requests()[0].query().unwrap().to_string();
+        assert!(query.contains("protocol=0%2C1&format=0%2C1%2C2&codec=0%2C1"));
+    }
+
+    fn stream(protocol: &str, format: &str, codec: &str, qn: u32) -> LiveStream {
+        LiveStream {
+            protocol_name: protocol.to_string(),
+            format: vec![LiveStreamFormat {
+                format_name: format.to_string(),
+                codec: vec![LiveStreamCodec {
+                    codec_name: codec.to_string(),
+                    current_qn: qn,
+                    ..LiveStreamCodec::default()
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let info = RoomPlayInfo {
+            playurl_info: Some(LivePlayUrlInfo {
+                conf_json: String::new(),
+                playurl: LivePlayUrl {
+                    stream: vec![
+                        stream("http_stream", "flv", "avc", 400),
+                        stream("http_hls", "ts", "avc", 10000),
+                        stream("http_hls", "fmp4", "hevc", 10000),
+                        stream("http_hls", "fmp4", "avc", 20000),
+                    ],
+                    ..LivePlayUrl::default()
+                },
+            }),
+            ..RoomPlayInfo::default()
+        };
+        let selected = info.select(&QualityPreference::default()).unwrap();
+        assert_eq!(selected.format, LiveFormat::Fmp4);
+        assert_eq!(selected.codec, LiveCodec::Hevc);
+
+        let preference = QualityPreference {
+            codecs: vec![LiveCodec::Avc],
+            ..QualityPreference::default()
+        };
+        let selected = info.select(&preference).unwrap();
+        assert_eq!(selected.format, LiveFormat::Ts);
+
+        let preference = QualityPreference {
+            qn: 250,
+            protocols: vec![LiveProtocol::HttpHls],
+            ..QualityPreference::default()
+        };
+        assert_eq!(info.select(&preference).unwrap().stream.current_qn, 10000);
+    }
+}
@@ -0,0 +1,131 @@
+use futures_util::future::join_all;
+use reqwest::header::RANGE;
+use reqwest::Method;
+use tokio::time::{timeout, Duration, Instant};
+
+use crate::error::Error;
+use crate::retry::check_status;
+use crate::{BiliClient, Result};
+
+/// Longest wait for the first bytes of a stream.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A stream url found serving data.
+pub struct StreamProbe {
+    pub url: String,
+    /// Time until the first bytes, or the whole playlist for HLS.
+    pub latency: Duration,
+}
+
+/// Check that a play url serves data, the server often gives stale ones.
+///
+/// An HLS playlist (`.m3u8`) must list at least one segment, other streams must send
+/// their first bytes within a few seconds.
+pub async fn probe_stream(client: &BiliClient, url: &str) -> Result<StreamProbe> {
+    let started = Instant::now();
+    let probe = async {
+        if is_playlist(url) {
+            let response = client.send(client.request(Method::GET, url)).await?;
+            let playlist = check_status(response)?.text().await?;
+            if !has_segment(&playlist) {
+                return Err(Error::UnexpectedResponse(
+                    "playlist lists no segment".to_string(),
+                ));
+            }
+        } else {
+            // live flv ignores the range, still only the first chunk is read
+            let request = client
+                .request(Method::GET, url)
+                .header(RANGE, "bytes=0-1023");
+            let mut response = check_status(client.send(request).await?)?;
+            match response.chunk().await? {
+                Some(chunk) if !chunk.is_empty() => {}
+                _ => {
+                    return Err(Error::UnexpectedResponse(
+                        "stream sends no data".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    };
+    match timeout(PROBE_TIMEOUT, probe).await {
+        Ok(result) => result?,
+        Err(_) => {
+            return Err(Error::UnexpectedResponse(
+                "stream probe timed out".to_string(),
+            ))
+        }
+    }
+    Ok(StreamProbe {
+        url: url.to_string(),
+        latency: started.elapsed(),
+    })
+}
+
+/// Probe all `urls` at once, e.g. the `durl` entries or
+/// [`LiveStreamCodec::urls`](super::LiveStreamCodec::urls), returning the fastest serving one.
+pub async fn probe_fastest(client: &BiliClient, urls: &[String]) -> Option<StreamProbe> {
+    join_all(urls.iter().map(|url| probe_stream(client, url)))
+        .await
+        .into_iter()
+        .zip(urls)
+        .filter_map(|(result, url)| match result {
+            Ok(probe) => Some(probe),
+            Err(e) => {
+                debug!("stream {} failed the probe: {:?}", url, e);
+                None
+            }
+        })
+        .min_by_key(|probe| probe.latency)
+}
+
+fn is_playlist(url: &str) -> bool {
+    url.split('?').next().unwrap_or_default().ends_with(".m3u8")
+}
+
+fn has_segment(playlist: &str) -> bool {
+    playlist.starts_with("#EXTM3U")
+        && playlist
+            .lines()
+            .any(|line| !line.trim().is_empty() && !line.starts_with('#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    #[tokio::test]
+    async fn test_probe() {
+        let transport = MockTransport::new()
+            .respond("https://a.bilivideo.com/live.flv", 200, b"FLV\x01".to_vec())
+            .respond("https://b.bilivideo.com/live.flv", 404, Vec::new())
+            .respond(
+                "https://c.bilivideo.com/index.m3u8",
+                200,
+                b"#EXTM3U\n#EXT-X-MAP:URI=\"h1.m4s\"\n#EXTINF:1.0,\n2.m4s\n".to_vec(),
+            )
+            .respond(
+                "https://d.bilivideo.com/index.m3u8",
+                200,
+                b"#EXTM3U\n".to_vec(),
+            );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        assert!(
+            probe_stream(&client, "https://c.bilivideo.com/index.m3u8?expires=1")
+                .await
+                .is_ok()
+        );
+        assert!(probe_stream(&client, "https://d.bilivideo.com/index.m3u8")
+            .await
+            .is_err());
+        let urls = [
+            "https://b.bilivideo.com/live.flv".to_string(),
+            "https://a.bilivideo.com/live.flv".to_string(),
+        ];
+        let probe = probe_fastest(&client, &urls).await.unwrap();
+        assert_eq!(probe.url, urls[1]);
+    }
+}
@@ -0,0 +1,454 @@
+//! Record live streams into FLV files, split by a [`Rotation`] and named by a
+//! [`FilenameTemplate`].
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::Method;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::flv::{FlvReader, FlvTag, TimestampFixer, HEADER, TAG_AUDIO, TAG_SCRIPT, TAG_VIDEO};
+pub use super::sink::Rotation;
+use super::RoomInfo;
+use crate::error::Error;
+use crate::retry::check_status;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Name of recorded files, placeholders replaced by:
+///
+/// - `{room_id}`, `{uid}`, `{title}` and `{area}` from [`RoomInfo`]
+/// - `{start_time}`, when the file is started, as `20220101-200000` in Beijing time
+/// - `{part}`, the index of the file in the recording, from `1`
+///
+/// Unknown placeholders are kept as is.
+pub struct FilenameTemplate(String);
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self::new("{room_id}_{title}_{start_time}.flv")
+    }
+}
+
+impl FilenameTemplate {
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    /// The file name of the `part` started at `start`, characters not allowed in file names
+    /// of the values replaced by `_`.
+    pub fn render(&self, room: &RoomInfo, start: SystemTime, part: u32) -> String {
+        let mut name = String::new();
+        let mut rest = self.0.as_str();
+        while let Some(open) = rest.find('{') {
+            name += &rest[..open];
+            let Some(len) = rest[open..].find('}') else {
+                break;
+            };
+            let key = &rest[open + 1..open + len];
+            let value = match key {
+                "room_id" => room.room_id.to_string(),
+                "uid" => room.uid.to_string(),
+                "title" => room.title.clone(),
+                "area" => room.area_name.clone(),
+                "start_time" => format_time(start),
+                "part" => part.to_string(),
+                _ => {
+                    name += &rest[open..=open + len];
+                    rest = &rest[open + len + 1..];
+                    continue;
+                }
+            };
+            name.extend(value.chars().map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            }));
+            rest = &rest[open + len + 1..];
+        }
+        name + rest
+    }
+}
+
+/// Format as `%Y%m%d-%H%M%S` in UTC+8.
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+        + 8 * 3600;
+    let (days, secs) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // days to the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[derive(Debug)]
+struct Output {
+    file: BufWriter<File>,
+    path: PathBuf,
+    written: u64,
+    opened: Instant,
+    /// Timestamp of the first tag, subtracted so each file starts at `0`.
+    base: u32,
+}
+
+/// Writes an FLV stream into files under a directory, starting a new one on a key frame
+/// once the [`Rotation`] is due.
+///
+/// Every file starts with the FLV header, the latest metadata and decoder configurations,
+/// so each plays on its own. Timestamps are kept continuous across
+/// [reconnections](Self::new_stream).
+///
+/// Writing blocks on the files, in async code [spawn](RecorderHandle::spawn) it.
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+    template: FilenameTemplate,
+    rotation: Rotation,
+    room: RoomInfo,
+    reader: FlvReader,
+    fixer: TimestampFixer,
+    /// Latest script, video and audio sequence header tags.
+    headers: Vec<FlvTag>,
+    output: Option<Output>,
+    files: Vec<PathBuf>,
+}
+
+impl Recorder {
+    /// Nothing is touched until the first key frame arrives, which creates `dir` if needed
+    /// and opens the first file.
+    pub fn new(
+        dir: impl AsRef<Path>,
+        template: FilenameTemplate,
+        rotation: Rotation,
+        room: RoomInfo,
+    ) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            template,
+            rotation,
+            room,
+            reader: FlvReader::new(),
+            fixer: TimestampFixer::new(),
+            headers: Vec::new(),
+            output: None,
+            files: Vec::new(),
+        }
+    }
+
+    /// Files written so far, the last one being written.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Append bytes of the stream, however they are chunked.
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.reader.push(bytes);
+        while let Some(tag) = self.reader.next_tag()? {
+            self.write_tag(tag)?;
+        }
+        Ok(())
+    }
+
+    /// Prepare for the stream of a new connection, which starts with its own FLV header.
+    ///
+    /// The stream continues in the current file, its timestamps following the last tag.
+    pub fn new_stream(&mut self) {
+        self.reader = FlvReader::new();
+        self.fixer.new_stream();
+    }
+
+    fn write_tag(&mut self, mut tag: FlvTag) -> std::io::Result<()> {
+        if !matches!(tag.tag_type, TAG_AUDIO | TAG_VIDEO | TAG_SCRIPT) {
+            return Ok(());
+        }
+        tag.timestamp = self.fixer.fix(tag.timestamp);
+        if tag.tag_type == TAG_SCRIPT || tag.is_sequence_header() {
+            let repeated = self
+                .headers
+                .iter()
+                .any(|header| header.tag_type == tag.tag_type && header.data == tag.data);
+            self.headers
+                .retain(|header| header.tag_type != tag.tag_type);
+            self.headers.push(tag.clone());
+            // metadata belongs at the start of a file, a repeated decoder configuration,
+            // e.g. after a reconnection, is useless
+            if repeated || tag.tag_type == TAG_SCRIPT {
+                return Ok(());
+            }
+        } else if tag.is_keyframe() && self.should_split() {
+            self.split(tag.timestamp)?;
+        }
+        // nothing plays before the first key frame
+        if let Some(output) = &mut self.output {
+            let tag = FlvTag {
+                timestamp: tag.timestamp.saturating_sub(output.base),
+                ..tag
+            };
+            let bytes = tag.encode();
+            output.file.write_all(&bytes)?;
+            output.written += bytes.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn should_split(&self) -> bool {
+        let Some(output) = &self.output else {
+            return true;
+        };
+        let Rotation { max_bytes, max_age } = self.rotation;
+        max_bytes.is_some_and(|max| output.written >= max)
+            || max_age.is_some_and(|max| output.opened.elapsed() >= max)
+    }
+
+    /// Close the current file and open the next one, starting at `base`.
+    fn split(&mut self, base: u32) -> std::io::Result<()> {
+        match self.output.take() {
+            Some(mut output) => output.file.flush()?,
+            None => fs::create_dir_all(&self.dir)?,
+        }
+        let name = self
+            .template
+            .render(&self.room, SystemTime::now(), self.files.len() as u32 + 1);
+        let mut path = self.dir.join(&name);
+        // the template may not tell parts apart
+        let mut n = 1;
+        while path.exists() {
+            let stem = Path::new(&name).file_stem().unwrap_or_default();
+            let mut file_name = format!("{}-{}", stem.to_string_lossy(), n);
+            if let Some(ext) = Path::new(&name).extension() {
+                file_name = format!("{}.{}", file_name, ext.to_string_lossy());
+            }
+            path = self.dir.join(file_name);
+            n += 1;
+        }
+        debug!("recording into {}", path.display());
+        let mut file = BufWriter::new(File::create(&path)?);
+        file.write_all(&HEADER)?;
+        let mut written = HEADER.len() as u64;
+        for header in &self.headers {
+            let bytes = FlvTag {
+                timestamp: 0,
+                ..header.clone()
+            }
+            .encode();
+            file.write_all(&bytes)?;
+            written += bytes.len() as u64;
+        }
+        self.files.push(path.clone());
+        self.output = Some(Output {
+            file,
+            path,
+            written,
+            opened: Instant::now(),
+            base,
+        });
+        Ok(())
+    }
+
+    /// Flush the last file, returning all files written.
+    pub fn finish(mut self) -> std::io::Result<Vec<PathBuf>> {
+        if let Some(output) = &mut self.output {
+            output.file.flush()?;
+        }
+        Ok(std::mem::take(&mut self.files))
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Some(output) = &mut self.output {
+            if let Err(e) = output.file.flush() {
+                warn!("failed to flush {}: {:?}", output.path.display(), e);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Command {
+    Write(Vec<u8>),
+    NewStream,
+}
+
+/// A [`Recorder`] writing on a blocking thread, fed over a channel so that the runtime never
+/// waits on the files.
+#[derive(Debug)]
+pub struct RecorderHandle {
+    tx: mpsc::Sender<Command>,
+    task: JoinHandle<std::io::Result<Vec<PathBuf>>>,
+}
+
+impl RecorderHandle {
+    /// Chunks queued before [`RecorderHandle::write`] waits for the files.
+    const CAPACITY: usize = 64;
+
+    /// Move `recorder` onto a blocking thread.
+    pub fn spawn(mut recorder: Recorder) -> Self {
+        let (tx, mut rx) = mpsc::channel(Self::CAPACITY);
+        let task = tokio::task::spawn_blocking(move || {
+            while let Some(command) = rx.blocking_recv() {
+                match command {
+                    Command::Write(bytes) => recorder.write(&bytes)?,
+                    Command::NewStream => recorder.new_stream(),
+                }
+            }
+            recorder.finish()
+        });
+        Self { tx, task }
+    }
+
+    /// Queue bytes of the stream, failing if the recorder stopped on an error, which
+    /// [`RecorderHandle::finish`] returns.
+    pub async fn write(&self, bytes: Vec<u8>) -> Result<()> {
+        self.send(Command::Write(bytes)).await
+    }
+
+    /// See [`Recorder::new_stream`].
+    pub async fn new_stream(&self) -> Result<()> {
+        self.send(Command::NewStream).await
+    }
+
+    async fn send(&self, command: Command) -> Result<()> {
+        self.tx.send(command).await.map_err(|_| {
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "recorder stopped",
+            ))
+        })
+    }
+
+    /// Write what is queued and flush the last file, returning all files written.
+    pub async fn finish(self) -> Result<Vec<PathBuf>> {
+        drop(self.tx);
+        let files = self
+            .task
+            .await
+            .map_err(|e| Error::UnexpectedResponse(format!("recorder panicked: {}", e)))??;
+        Ok(files)
+    }
+}
+
+/// Download the FLV stream at `url` into `recorder` until the server ends it,
+/// returning the bytes received.
+pub async fn record(client: &BiliClient, url: &str, recorder: &RecorderHandle) -> Result<u64> {
+    let response = client.send(client.request(Method::GET, url)).await?;
+    let mut response = check_status(response)?;
+    let mut received = 0;
+    while let Some(chunk) = response.chunk().await? {
+        received += chunk.len() as u64;
+        recorder.write(chunk.to_vec()).await?;
+    }
+    Ok(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room() -> RoomInfo {
+        RoomInfo {
+            room_id: 14507014,
+            title: "a/b: c".to_string(),
+            ..RoomInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_template() {
+        let start = UNIX_EPOCH + std::time::Duration::from_secs(1_640_995_200);
+        let template = FilenameTemplate::new("{room_id}_{title}_{start_time}_{part}{x}.flv");
+        assert_eq!(
+            template.render(&room(), start, 2),
+            "14507014_a_b_ c_20220101-080000_2{x}.flv"
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let dir = std::env::temp_dir().join(format!("bili-record-{}", std::process::id()));
+        let rotation = Rotation {
+            max_bytes: Some(1),
+            max_age: None,
+        };
+        let template = FilenameTemplate::new("{room_id}.flv");
+        let mut recorder = Recorder::new(&dir, template, rotation, room());
+        let tag = |timestamp, data: &[u8]| FlvTag {
+            tag_type: TAG_VIDEO,
+            timestamp,
+            data: data.to_vec(),
+        };
+        let mut stream = HEADER.to_vec();
+        stream.extend(tag(0, &[0x17, 0, 0, 0, 0]).encode());
+        stream.extend(tag(100, &[0x27, 1, 0, 0, 0]).encode());
+        stream.extend(tag(1000, &[0x17, 1, 0, 0, 0]).encode());
+        stream.extend(tag(1100, &[0x27, 1, 0, 0, 0]).encode());
+        stream.extend(tag(2000, &[0x17, 1, 0, 0, 0]).encode());
+        recorder.write(&stream).unwrap();
+        let files = recorder.finish().unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files[1].ends_with("14507014-1.flv"));
+
+        let mut reader = FlvReader::new();
+        reader.push(&fs::read(&files[0]).unwrap());
+        let sequence_header = reader.next_tag().unwrap().unwrap();
+        assert!(sequence_header.is_sequence_header());
+        let first = reader.next_tag().unwrap().unwrap();
+        assert_eq!(first.timestamp, 0);
+        assert_eq!(reader.next_tag().unwrap().unwrap().timestamp, 100);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let dir = std::env::temp_dir().join(format!("bili-reconnect-{}", std::process::id()));
+        let template = FilenameTemplate::new("{room_id}.flv");
+        let mut recorder = Recorder::new(&dir, template, Rotation::default(), room());
+        let mut stream = HEADER.to_vec();
+        for (timestamp, data) in [(0, [0x17, 0, 1]), (0, [0x17, 1, 0]), (40, [0x27, 1, 0])] {
+            let tag = FlvTag {
+                tag_type: TAG_VIDEO,
+                timestamp,
+                data: data.to_vec(),
+            };
+            stream.extend(tag.encode());
+        }
+        recorder.write(&stream).unwrap();
+        recorder.new_stream();
+        recorder.write(&stream).unwrap();
+        let files = recorder.finish().unwrap();
+        assert_eq!(files.len(), 1);
+
+        let mut reader = FlvReader::new();
+        reader.push(&fs::read(&files[0]).unwrap());
+        let mut tags = Vec::new();
+        while let Some(tag) = reader.next_tag().unwrap() {
+            tags.push(tag);
+        }
+        let sequence_headers = tags.iter().filter(|tag| tag.is_sequence_header()).count();
+        assert_eq!(sequence_headers, 1);
+        let timestamps: Vec<_> = tags.iter().map(|tag| tag.timestamp).collect();
+        assert_eq!(timestamps, [0, 0, 40, 73, 113]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
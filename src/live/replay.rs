@@ -0,0 +1,187 @@
+//! Replay recorded events, e.g. to develop a bot without a living room.
+//!
+//! Events recorded by [`JsonlSink`](super::sink::JsonlSink) are read with [`from_jsonl`],
+//! messages of [`DanmakuStream::subscribe_raw`](super::ws::DanmakuStream::subscribe_raw)
+//! appended with [`write_raw`] are read with [`from_raw`].
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::time::{Duration, Instant};
+
+use super::event::LiveEvent;
+use super::sink::EventRecord;
+use super::ws::codec::Decoder;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// How fast to replay.
+pub enum Pace {
+    /// Keep the time between events as recorded.
+    Original,
+    /// Yield events as soon as they are read.
+    Fastest,
+}
+
+/// Sleeps so records are yielded at their recorded offsets.
+struct Clock {
+    pace: Pace,
+    /// Start of the replay and time of the first record.
+    start: Option<(Instant, u64)>,
+}
+
+impl Clock {
+    async fn wait(&mut self, time: u64) {
+        if self.pace == Pace::Fastest {
+            return;
+        }
+        let (start, first) = *self.start.get_or_insert((Instant::now(), time));
+        let offset = Duration::from_millis(time.saturating_sub(first));
+        tokio::time::sleep_until(start + offset).await;
+    }
+}
+
+/// Replay the events of a file written by [`JsonlSink`](super::sink::JsonlSink).
+///
+/// Malformed lines are skipped, the stream ends at the end of the file or on a read error.
+pub fn from_jsonl(
+    path: impl AsRef<Path>,
+    pace: Pace,
+) -> std::io::Result<impl Stream<Item = LiveEvent>> {
+    let lines = BufReader::new(File::open(path)?).lines();
+    let clock = Clock { pace, start: None };
+    Ok(stream::unfold(
+        (lines, clock),
+        |(mut lines, mut clock)| async move {
+            loop {
+                let line = match lines.next()? {
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!("failed to read recorded events: {:?}", e);
+                        return None;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<EventRecord>(&line) {
+                    Ok(record) => {
+                        clock.wait(record.time).await;
+                        return Some((record.event, (lines, clock)));
+                    }
+                    Err(e) => warn!("skip malformed recorded event: {:?}", e),
+                }
+            }
+        },
+    ))
+}
+
+/// Append a message received at `time` to a raw log read by [`from_raw`].
+///
+/// Each message is stored as its unix timestamp in milliseconds (`u64`),
+/// its length (`u32`) and the bytes, integers in big endian.
+pub fn write_raw<W: Write>(writer: &mut W, time: SystemTime, msg: &[u8]) -> std::io::Result<()> {
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    writer.write_all(&millis.to_be_bytes())?;
+    writer.write_all(&(msg.len() as u32).to_be_bytes())?;
+    writer.write_all(msg)
+}
+
+fn read_raw<R: Read>(reader: &mut R) -> std::io::Result<Option<(u64, Vec<u8>)>> {
+    let mut header = [0; 12];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut time = [0; 8];
+    time.copy_from_slice(&header[..8]);
+    let len = u32::from_be_bytes([header[8], header[9], header[10], header[11]]);
+    let mut msg = vec![0; len as usize];
+    reader.read_exact(&mut msg)?;
+    Ok(Some((u64::from_be_bytes(time), msg)))
+}
+
+/// Replay a raw log written by [`write_raw`] through the packet decoder.
+///
+/// Packets failing to decode are skipped, the stream ends at the end of the file
+/// or on a read error.
+pub fn from_raw(
+    path: impl AsRef<Path>,
+    pace: Pace,
+) -> std::io::Result<impl Stream<Item = LiveEvent>> {
+    let reader = BufReader::new(File::open(path)?);
+    let clock = Clock { pace, start: None };
+    let messages = stream::unfold(
+        (reader, clock, Decoder::new()),
+        |(mut reader, mut clock, mut decoder)| async move {
+            let (time, msg) = match read_raw(&mut reader) {
+                Ok(record) => record?,
+                Err(e) => {
+                    warn!("failed to read raw log: {:?}", e);
+                    return None;
+                }
+            };
+            clock.wait(time).await;
+            let events: Vec<_> = decoder
+                .packets(&msg)
+                .filter_map(
+                    |pkt| match pkt.and_then(|pkt| LiveEvent::from_packet(&pkt)) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            warn!("skip undecodable recorded packet: {:?}", e);
+                            None
+                        }
+                    },
+                )
+                .collect();
+            Some((stream::iter(events), (reader, clock, decoder)))
+        },
+    );
+    Ok(messages.flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::live::sink::{EventSink, JsonlSink, Rotation};
+    use crate::live::ws::{codec, Operation, ProtoVer, WsPacket};
+
+    #[tokio::test]
+    async fn test_replay() {
+        let dir = std::env::temp_dir().join(format!("bili-replay-{}", std::process::id()));
+        let mut sink = JsonlSink::new(&dir, "room", Rotation::default()).unwrap();
+        let start = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        sink.write_event(&LiveEvent::Popularity(1), start).unwrap();
+        sink.write_event(&LiveEvent::Popularity(2), start + Duration::from_millis(50))
+            .unwrap();
+        sink.flush().unwrap();
+        let replayed = Instant::now();
+        let events: Vec<_> = from_jsonl(sink.path(), Pace::Original)
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(events, [LiveEvent::Popularity(1), LiveEvent::Popularity(2)]);
+        assert!(replayed.elapsed() >= Duration::from_millis(50));
+
+        let path = dir.join("raw.bin");
+        let mut raw = Vec::new();
+        let mut msg = codec::encode(&WsPacket::new(
+            ProtoVer::Json,
+            Operation::HeartBeatReply,
+            7i32.to_be_bytes().to_vec(),
+        ))
+        .unwrap();
+        msg.extend(include_bytes!("ws/fixtures/zlib_notifications.bin"));
+        write_raw(&mut raw, start, &msg).unwrap();
+        std::fs::write(&path, raw).unwrap();
+        let events: Vec<_> = from_raw(&path, Pace::Fastest).unwrap().collect().await;
+        assert_eq!(events[0], LiveEvent::Popularity(7));
+        assert!(matches!(events[1], LiveEvent::Danmaku(_)));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, PartialEq)]
+/// A danmaku to send to a living room.
+pub struct LiveDanmakuDraft {
+    /// The text, or the `emoticon_unique` of an emoticon.
+    pub msg: String,
+    /// `0` text, `1` emoticon.
+    pub dm_type: u8,
+    /// `1` scroll, `4` bottom, `5` top.
+    pub mode: u8,
+    /// RGB color, e.g. `0xffffff`.
+    pub color: u32,
+    pub fontsize: u32,
+}
+
+impl LiveDanmakuDraft {
+    /// A white scrolling text danmaku.
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: msg.to_string(),
+            dm_type: 0,
+            mode: 1,
+            color: 0xffffff,
+            fontsize: 25,
+        }
+    }
+
+    /// An emoticon danmaku, see [`get_emoticons`](super::get_emoticons).
+    pub fn emoticon(emoticon_unique: &str) -> Self {
+        Self {
+            dm_type: 1,
+            ..Self::new(emoticon_unique)
+        }
+    }
+}
+
+/// Send a danmaku to the living room.
+pub async fn send_danmaku(
+    client: &BiliClient,
+    room_id: u64,
+    draft: LiveDanmakuDraft,
+) -> Result<()> {
+    let csrf = client.csrf()?;
+    let rnd = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut form = vec![
+        ("bubble", "0".to_string()),
+        ("msg", draft.msg),
+        ("color", draft.color.to_string()),
+        ("mode", draft.mode.to_string()),
+        ("fontsize", draft.fontsize.to_string()),
+        ("rnd", rnd.to_string()),
+        ("roomid", room_id.to_string()),
+        ("csrf_token", csrf.clone()),
+        ("csrf", csrf),
+    ];
+    if draft.dm_type == 1 {
+        form.push(("dm_type", "1".to_string()));
+        form.push(("emoticonOptions", "[object Object]".to_string()));
+    }
+    client.post_action(consts::SEND_DANMAKU, &form).await
+}
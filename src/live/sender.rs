@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Duration, Instant};
+
+use super::send::{send_danmaku, LiveDanmakuDraft};
+use crate::error::{Error, ErrorCode};
+use crate::{BiliClient, Result};
+
+/// `10030` sending too fast, `10031` repeating too fast.
+const TOO_FAST_CODES: [i64; 2] = [10030, 10031];
+
+#[derive(Clone, Debug)]
+/// Limits a [`DanmakuSender`] keeps to.
+pub struct DanmakuSenderConfig {
+    /// Least time between two danmaku in a room.
+    pub interval: Duration,
+    /// Characters of a danmaku, `20` by default, `30` or `40` for users of higher levels.
+    pub max_len: usize,
+    /// Retries of a danmaku rejected for being sent too fast.
+    pub max_retries: usize,
+    /// Wait before the first retry, doubled after each.
+    pub backoff: Duration,
+    /// Messages queued per room before [`DanmakuSender::send`] waits.
+    pub capacity: usize,
+}
+
+impl Default for DanmakuSenderConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_len: 20,
+            max_retries: 3,
+            backoff: Duration::from_secs(2),
+            capacity: 64,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Queued {
+    parts: Vec<LiveDanmakuDraft>,
+    reply: Option<oneshot::Sender<Result<()>>>,
+}
+
+/// Queues danmaku per room, sending them no faster than allowed.
+///
+/// Long texts are split into several danmaku, rejections for sending too fast are retried
+/// with backoff. Each room has its own queue, so a slow room doesn't hold others back.
+#[derive(Debug)]
+pub struct DanmakuSender {
+    client: BiliClient,
+    config: DanmakuSenderConfig,
+    rooms: Mutex<HashMap<u64, mpsc::Sender<Queued>>>,
+}
+
+impl DanmakuSender {
+    pub fn new(client: &BiliClient, config: DanmakuSenderConfig) -> Self {
+        Self {
+            client: client.clone(),
+            config,
+            rooms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Send a danmaku, waiting until all of its parts are sent or one failed.
+    pub async fn send(&self, room_id: u64, draft: LiveDanmakuDraft) -> Result<()> {
+        let (reply, replied) = oneshot::channel();
+        self.enqueue(room_id, draft, Some(reply)).await?;
+        replied.await.unwrap_or_else(|_| Err(closed()))
+    }
+
+    /// Queue a danmaku without waiting for it to be sent, failures are only logged.
+    pub async fn queue(&self, room_id: u64, draft: LiveDanmakuDraft) -> Result<()> {
+        self.enqueue(room_id, draft, None).await
+    }
+
+    async fn enqueue(
+        &self,
+        room_id: u64,
+        draft: LiveDanmakuDraft,
+        reply: Option<oneshot::Sender<Result<()>>>,
+    ) -> Result<()> {
+        if draft.msg.trim().is_empty() {
+            return Err(Error::UnexpectedResponse("danmaku is empty".to_string()));
+        }
+        let parts = if draft.dm_type == 0 {
+            split_message(&draft.msg, self.config.max_len)
+                .into_iter()
+                .map(|msg| LiveDanmakuDraft {
+                    msg,
+                    ..draft.clone()
+                })
+                .collect()
+        } else {
+            vec![draft]
+        };
+        let tx = self
+            .rooms
+            .lock()
+            .unwrap()
+            .entry(room_id)
+            .or_insert_with(|| {
+                let (tx, rx) = mpsc::channel(self.config.capacity.max(1));
+                tokio::spawn(run(self.client.clone(), room_id, self.config.clone(), rx));
+                tx
+            })
+            .clone();
+        tx.send(Queued { parts, reply }).await.map_err(|_| closed())
+    }
+}
+
+fn closed() -> Error {
+    Error::UnexpectedResponse("danmaku sender is closed".to_string())
+}
+
+/// Split a text into pieces of at most `max_len` characters.
+fn split_message(msg: &str, max_len: usize) -> Vec<String> {
+    let chars: Vec<char> = msg.chars().collect();
+    chars
+        .chunks(max_len.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn is_too_fast(e: &Error) -> bool {
+    match e {
+        Error::Api { code, raw, .. } => {
+            *code == ErrorCode::TooFrequent || TOO_FAST_CODES.contains(raw)
+        }
+        _ => false,
+    }
+}
+
+/// Send the danmaku queued for a room until the sender is dropped.
+async fn run(
+    client: BiliClient,
+    room_id: u64,
+    config: DanmakuSenderConfig,
+    mut rx: mpsc::Receiver<Queued>,
+) {
+    let mut last_sent: Option<Instant> = None;
+    while let Some(queued) = rx.recv().await {
+        let mut result = Ok(());
+        for part in queued.parts {
+            let mut retries = 0;
+            result = loop {
+                if let Some(last_sent) = last_sent {
+                    tokio::time::sleep_until(last_sent + config.interval).await;
+                }
+                let sent = send_danmaku(&client, room_id, part.clone()).await;
+                last_sent = Some(Instant::now());
+                match sent {
+                    Err(e) if is_too_fast(&e) && retries < config.max_retries => {
+                        let backoff = config.backoff * 2u32.pow(retries as u32);
+                        retries += 1;
+                        debug!(
+                            "danmaku to room {} sent too fast, retry {} in {:?}",
+                            room_id, retries, backoff
+                        );
+                        tokio::time::sleep(backoff).await;
+                    }
+                    sent => break sent,
+                }
+            };
+            if result.is_err() {
+                break;
+            }
+        }
+        match queued.reply {
+            Some(reply) => {
+                let _ = reply.send(result);
+            }
+            None => {
+                if let Err(e) = result {
+                    warn!("failed to send danmaku to room {}: {:?}", room_id, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Session;
+    use crate::live::consts;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_message() {
+        assert_eq!(split_message("一二三四五", 2), ["一二", "三四", "五"]);
+        assert_eq!(split_message("hi", 20), ["hi"]);
+    }
+
+    fn config() -> DanmakuSenderConfig {
+        DanmakuSenderConfig {
+            interval: Duration::from_millis(10),
+            max_len: 2,
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+            capacity: 8,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let transport = MockTransport::new().json(
+            consts::SEND_DANMAKU,
+            json!({"code": 0, "message": "", "data": {}}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .session(Session::from_cookie_str("DedeUserID=10086; bili_jct=csrf"))
+            .build()
+            .unwrap();
+        let sender = DanmakuSender::new(&client, config());
+        let started = Instant::now();
+        sender
+            .send(1, LiveDanmakuDraft::new("hello"))
+            .await
+            .unwrap();
+        assert_eq!(transport.requests().len(), 3);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_send_empty() {
+        let transport = MockTransport::new();
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let sender = DanmakuSender::new(&client, config());
+        assert!(sender.send(1, LiveDanmakuDraft::new(" ")).await.is_err());
+        assert!(sender.queue(1, LiveDanmakuDraft::new("")).await.is_err());
+        assert!(transport.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_retry_too_fast() {
+        let transport = MockTransport::new().json(
+            consts::SEND_DANMAKU,
+            json!({"code": 10030, "message": "您发送弹幕的频率过快", "data": {}}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .session(Session::from_cookie_str("DedeUserID=10086; bili_jct=csrf"))
+            .build()
+            .unwrap();
+        let sender = DanmakuSender::new(&client, config());
+        let result = sender.send(1, LiveDanmakuDraft::new("hi")).await;
+        assert!(is_too_fast(&result.unwrap_err()));
+        assert_eq!(transport.requests().len(), 3);
+    }
+}
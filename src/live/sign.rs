@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+/// Reward of signing in.
+pub struct SignResult {
+    /// e.g. `3000点用户经验,2根辣条`.
+    pub text: String,
+    pub special_text: String,
+    /// Days in this month.
+    pub all_days: u32,
+    /// Days signed in this month.
+    pub had_sign_days: u32,
+    pub is_bonus_day: u8,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+/// Sign-in status of this month.
+pub struct SignInfo {
+    pub text: String,
+    pub special_text: String,
+    /// `1` signed in today.
+    pub status: u8,
+    pub all_days: u32,
+    pub had_sign_days: u32,
+    pub cur_year: u32,
+    pub cur_month: u32,
+    pub cur_day: u32,
+}
+
+impl SignInfo {
+    pub fn signed_today(&self) -> bool {
+        self.status == 1
+    }
+}
+
+/// Do the live daily sign-in, which fails if already signed in today.
+pub async fn do_sign(client: &BiliClient) -> Result<SignResult> {
+    client.get(consts::DO_SIGN, &()).await
+}
+
+/// Get whether the account signed in today and how many days this month.
+pub async fn get_sign_info(client: &BiliClient) -> Result<SignInfo> {
+    client.get(consts::SIGN_INFO, &()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_sign_info() {
+        let transport = MockTransport::new().json(
+            consts::SIGN_INFO,
+            json!({"code": 0, "data": {
+                "text": "", "specialText": "", "status": 1, "allDays": 31, "curYear": 2024,
+                "curMonth": 1, "curDay": 2, "curDate": "2024-1-2", "hadSignDays": 2,
+                "signDaysList": [1, 2],
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let info = get_sign_info(&client).await.unwrap();
+        assert!(info.signed_today());
+        assert_eq!(info.had_sign_days, 2);
+    }
+}
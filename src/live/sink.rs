@@ -0,0 +1,222 @@
+//! Sinks persisting the events of a room, e.g. for an archiver.
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::{Duration, Instant};
+
+use super::event::{EventReceiver, LiveEvent};
+use crate::error::Error;
+use crate::Result;
+
+/// Somewhere events are appended to.
+pub trait EventSink {
+    /// Append an event received at `time`.
+    fn write_event(&mut self, event: &LiveEvent, time: SystemTime) -> std::io::Result<()>;
+
+    /// Flush buffered events.
+    fn flush(&mut self) -> std::io::Result<()>;
+}
+
+/// Consume events until the stream is closed, appending every one to `sink`, then give the
+/// sink back.
+///
+/// The sink writes on a blocking thread, events are queued for it so the stream is never
+/// held up. Stops early if the sink fails.
+pub async fn persist<S>(mut events: EventReceiver, mut sink: S) -> Result<S>
+where
+    S: EventSink + Send + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel::<(LiveEvent, SystemTime)>();
+    let writer = tokio::task::spawn_blocking(move || {
+        for (event, time) in rx {
+            sink.write_event(&event, time)?;
+        }
+        sink.flush()?;
+        Ok::<_, std::io::Error>(sink)
+    });
+    while let Some(event) = events.recv().await {
+        if tx.send((event, SystemTime::now())).is_err() {
+            break;
+        }
+    }
+    drop(tx);
+    let sink = writer
+        .await
+        .map_err(|e| Error::UnexpectedResponse(format!("event sink panicked: {}", e)))??;
+    Ok(sink)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A line of a [`JsonlSink`] file.
+pub struct EventRecord {
+    /// Unix timestamp in milliseconds when the event was received.
+    pub time: u64,
+    pub event: LiveEvent,
+}
+
+impl EventRecord {
+    pub fn new(event: LiveEvent, time: SystemTime) -> Self {
+        let time = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self { time, event }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// When to start a new file, whichever comes first, never if both are `None`.
+pub struct Rotation {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+}
+
+/// Appends events as newline-delimited json into `<dir>/<prefix>-<unix ms>.jsonl`,
+/// starting a new file following a [`Rotation`].
+#[derive(Debug)]
+pub struct JsonlSink {
+    dir: PathBuf,
+    prefix: String,
+    rotation: Rotation,
+    file: BufWriter<File>,
+    path: PathBuf,
+    written: u64,
+    opened: Instant,
+}
+
+impl JsonlSink {
+    /// Create `dir` if needed and open the first file.
+    pub fn new(dir: impl AsRef<Path>, prefix: &str, rotation: Rotation) -> std::io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let (file, path) = Self::open(&dir, prefix)?;
+        Ok(Self {
+            dir,
+            prefix: prefix.to_string(),
+            rotation,
+            file,
+            path,
+            written: 0,
+            opened: Instant::now(),
+        })
+    }
+
+    /// The file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn open(dir: &Path, prefix: &str) -> std::io::Result<(BufWriter<File>, PathBuf)> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut path = dir.join(format!("{}-{}.jsonl", prefix, millis));
+        // rotating twice in a millisecond must not append to the previous file
+        let mut n = 1;
+        while path.exists() {
+            path = dir.join(format!("{}-{}-{}.jsonl", prefix, millis, n));
+            n += 1;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        debug!("writing events into {}", path.display());
+        Ok((BufWriter::new(file), path))
+    }
+
+    fn should_rotate(&self) -> bool {
+        let Rotation { max_bytes, max_age } = self.rotation;
+        max_bytes.is_some_and(|max| self.written >= max)
+            || max_age.is_some_and(|max| self.opened.elapsed() >= max)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        let (file, path) = Self::open(&self.dir, &self.prefix)?;
+        self.file = file;
+        self.path = path;
+        self.written = 0;
+        self.opened = Instant::now();
+        Ok(())
+    }
+}
+
+impl EventSink for JsonlSink {
+    fn write_event(&mut self, event: &LiveEvent, time: SystemTime) -> std::io::Result<()> {
+        if self.written > 0 && self.should_rotate() {
+            self.rotate()?;
+        }
+        let mut line = serde_json::to_vec(&EventRecord::new(event.clone(), time))?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Drop for JsonlSink {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.flush() {
+            warn!("failed to flush {}: {:?}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jsonl_rotation() {
+        let dir = std::env::temp_dir().join(format!("bili-sink-{}", std::process::id()));
+        let rotation = Rotation {
+            max_bytes: Some(1),
+            max_age: None,
+        };
+        let mut sink = JsonlSink::new(&dir, "room", rotation).unwrap();
+        let first = sink.path().to_path_buf();
+        sink.write_event(&LiveEvent::Popularity(1), UNIX_EPOCH)
+            .unwrap();
+        sink.write_event(&LiveEvent::Popularity(2), UNIX_EPOCH)
+            .unwrap();
+        sink.flush().unwrap();
+        assert_ne!(sink.path(), first);
+
+        let line = fs::read_to_string(&first).unwrap();
+        let record: EventRecord = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(record.time, 0);
+        assert_eq!(record.event, LiveEvent::Popularity(1));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_persist() {
+        use crate::live::ws::{Operation, ProtoVer, WsPacket};
+
+        let dir = std::env::temp_dir().join(format!("bili-persist-{}", std::process::id()));
+        let sink = JsonlSink::new(&dir, "room", Rotation::default()).unwrap();
+        let (tx, rx) = tokio::sync::broadcast::channel(4);
+        for popularity in 1..=2i32 {
+            let reply = WsPacket::new(
+                ProtoVer::Json,
+                Operation::HeartBeatReply,
+                popularity.to_be_bytes().to_vec(),
+            );
+            tx.send(reply).unwrap();
+        }
+        drop(tx);
+        let sink = persist(EventReceiver::from(rx), sink).await.unwrap();
+        let text = fs::read_to_string(sink.path()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use super::event::LiveEvent;
+use super::RoomInit;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Whether a room is living.
+pub enum RoomState {
+    Offline,
+    /// Living since the unix timestamp in seconds, `0` if unknown.
+    Live {
+        live_time: i64,
+    },
+    /// Replaying recordings (轮播).
+    Round,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// A change of [`RoomState`] seen by a [`LiveStateTracker`].
+pub struct StateChange {
+    /// `None` if the state was not known yet.
+    pub from: Option<RoomState>,
+    pub to: RoomState,
+}
+
+#[derive(Clone, Debug, Default)]
+/// Folds live status events into the current [`RoomState`], reporting only real changes.
+///
+/// `LIVE` is usually sent more than once per live, so are repeated polls of [`RoomInit`],
+/// the tracker drops those duplicates. Start it from [`room_init`](super::room_init) to know
+/// the state before the first event.
+pub struct LiveStateTracker {
+    state: Option<RoomState>,
+}
+
+impl RoomState {
+    /// Map the `live_status` of [`RoomInit`], `1` living and `2` replaying.
+    pub fn from_room_init(room: &RoomInit) -> Self {
+        match room.live_status {
+            1 => RoomState::Live {
+                live_time: room.live_time.max(0),
+            },
+            2 => RoomState::Round,
+            _ => RoomState::Offline,
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        matches!(self, RoomState::Live { .. })
+    }
+}
+
+impl LiveStateTracker {
+    /// A tracker which knows nothing about the room yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tracker starting from the state in `room`.
+    pub fn from_room_init(room: &RoomInit) -> Self {
+        Self {
+            state: Some(RoomState::from_room_init(room)),
+        }
+    }
+
+    /// The current state, `None` until an event or [`RoomInit`] tells.
+    pub fn state(&self) -> Option<RoomState> {
+        self.state
+    }
+
+    /// Apply an event, returning the change if it is a live status event which changed the state.
+    pub fn update(&mut self, event: &LiveEvent) -> Option<StateChange> {
+        let to = match event {
+            LiveEvent::LiveStart(start) => {
+                // keep the known start time when `LIVE` is repeated without one
+                if let (Some(RoomState::Live { .. }), 0) = (self.state, start.live_time) {
+                    return None;
+                }
+                RoomState::Live {
+                    live_time: start.live_time,
+                }
+            }
+            LiveEvent::Preparing(preparing) if preparing.round == 1 => RoomState::Round,
+            LiveEvent::Preparing(_) | LiveEvent::CutOff(_) => RoomState::Offline,
+            _ => return None,
+        };
+        self.transit(to)
+    }
+
+    /// Apply a polled [`RoomInit`], e.g. to catch up after a reconnection.
+    pub fn update_room_init(&mut self, room: &RoomInit) -> Option<StateChange> {
+        self.transit(RoomState::from_room_init(room))
+    }
+
+    fn transit(&mut self, to: RoomState) -> Option<StateChange> {
+        let from = self.state;
+        let changed = match (from, to) {
+            // a live with a refined start time is still the same live
+            (Some(RoomState::Live { .. }), RoomState::Live { .. }) => false,
+            (from, to) => from != Some(to),
+        };
+        self.state = Some(to);
+        if changed {
+            Some(StateChange { from, to })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tracker() {
+        let live = LiveEvent::from_body(json!({"cmd": "LIVE", "roomid": 1, "live_time": 100}));
+        let preparing = LiveEvent::from_body(json!({"cmd": "PREPARING", "roomid": "1"}));
+        let round = LiveEvent::from_body(json!({"cmd": "PREPARING", "roomid": "1", "round": 1}));
+
+        let mut tracker = LiveStateTracker::from_room_init(&RoomInit::default());
+        assert_eq!(tracker.state(), Some(RoomState::Offline));
+        assert_eq!(
+            tracker.update(&live),
+            Some(StateChange {
+                from: Some(RoomState::Offline),
+                to: RoomState::Live { live_time: 100 },
+            })
+        );
+        assert_eq!(tracker.update(&live), None);
+        assert_eq!(
+            tracker.update(&round).map(|change| change.to),
+            Some(RoomState::Round)
+        );
+        assert_eq!(
+            tracker.update(&preparing).map(|change| change.to),
+            Some(RoomState::Offline)
+        );
+        assert_eq!(tracker.update(&preparing), None);
+        tracker.update(&live);
+        let cut_off = LiveEvent::from_body(json!({"cmd": "CUT_OFF", "msg": "", "roomid": 1}));
+        assert_eq!(
+            tracker.update(&cut_off).map(|change| change.to),
+            Some(RoomState::Offline)
+        );
+
+        let room = RoomInit {
+            live_status: 1,
+            live_time: 200,
+            ..RoomInit::default()
+        };
+        assert!(tracker.update_room_init(&room).unwrap().to.is_live());
+    }
+}
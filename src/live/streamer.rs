@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Address to push the stream to, given when a live starts.
+pub struct Rtmp {
+    /// e.g. `rtmp://live-push.bilivideo.com/live-bvc/`.
+    pub addr: String,
+    /// The stream key.
+    pub code: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StartLive {
+    rtmp: Rtmp,
+}
+
+/// Start a live of the own room in the sub area, returning where to push the stream.
+pub async fn start_live(client: &BiliClient, room_id: u64, area_id: u64) -> Result<Rtmp> {
+    let csrf = client.csrf()?;
+    let started: StartLive = client
+        .post_form(
+            consts::START_LIVE,
+            &[
+                ("room_id", room_id.to_string()),
+                ("area_v2", area_id.to_string()),
+                ("platform", "pc".to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await?;
+    Ok(started.rtmp)
+}
+
+/// Stop the live of the own room.
+pub async fn stop_live(client: &BiliClient, room_id: u64) -> Result<()> {
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::STOP_LIVE,
+            &[
+                ("room_id", room_id.to_string()),
+                ("platform", "pc".to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await
+}
+
+/// Update the title and/or the sub area of the own room, `None` keeps it unchanged.
+pub async fn update_room(
+    client: &BiliClient,
+    room_id: u64,
+    title: Option<&str>,
+    area_id: Option<u64>,
+) -> Result<()> {
+    let csrf = client.csrf()?;
+    let mut form = vec![("room_id", room_id.to_string())];
+    if let Some(title) = title {
+        form.push(("title", title.to_string()));
+    }
+    if let Some(area_id) = area_id {
+        form.push(("area_id", area_id.to_string()));
+    }
+    form.push(("csrf_token", csrf.clone()));
+    form.push(("csrf", csrf));
+    client.post_action(consts::UPDATE_ROOM, &form).await
+}
@@ -0,0 +1,222 @@
+//! Daily fan medal tasks: liking, sharing and watching rooms to gain intimacy.
+use futures_util::future::join_all;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+use super::{consts, WebHeartbeat};
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// A room to do the tasks in, usually one per owned medal.
+pub struct MedalRoom {
+    pub room_id: u64,
+    /// Uid of the streamer.
+    pub anchor_id: u64,
+    /// Area ids, needed by watch-time reporting.
+    pub parent_area_id: u64,
+    pub area_id: u64,
+}
+
+#[derive(Clone, Debug)]
+/// Which tasks to do and how.
+pub struct MedalTaskConfig {
+    /// Likes sent per room, `0` to skip.
+    pub like_clicks: u32,
+    /// Share each room.
+    pub share: bool,
+    /// Watch time reported per room, all rooms are watched at once, zero to skip.
+    pub watch: Duration,
+    /// Pause between rooms for likes and shares.
+    pub room_delay: Duration,
+    /// How often [`BiliClient::spawn_medal_tasks`] runs the tasks.
+    pub every: Duration,
+}
+
+impl Default for MedalTaskConfig {
+    fn default() -> Self {
+        Self {
+            like_clicks: 30,
+            share: true,
+            // intimacy from watching is capped after 25 minutes a day
+            watch: Duration::from_secs(25 * 60),
+            room_delay: Duration::from_secs(3),
+            every: Duration::from_secs(24 * 3600),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// One of the daily tasks.
+pub enum MedalTask {
+    Like,
+    Share,
+    Watch,
+}
+
+#[derive(Debug)]
+/// A task which failed in a room, the others go on.
+pub struct TaskFailure {
+    pub room_id: u64,
+    pub task: MedalTask,
+    pub error: Error,
+}
+
+/// Like the room `clicks` times at once.
+pub async fn like_room(client: &BiliClient, room: &MedalRoom, clicks: u32) -> Result<()> {
+    let uid = client
+        .session()
+        .and_then(|session| session.uid())
+        .ok_or(Error::MissingCredential("DedeUserID"))?;
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::LIKE_REPORT,
+            &[
+                ("click_time", clicks.to_string()),
+                ("room_id", room.room_id.to_string()),
+                ("uid", uid.to_string()),
+                ("anchor_id", room.anchor_id.to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await
+}
+
+/// Share the room.
+pub async fn share_room(client: &BiliClient, room_id: u64) -> Result<()> {
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::SHARE_ROOM,
+            &[
+                ("roomid", room_id.to_string()),
+                ("interact_type", "3".to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await
+}
+
+/// Report watch time of the room with a [`WebHeartbeat`] for `duration`.
+pub async fn watch_room(client: &BiliClient, room: &MedalRoom, duration: Duration) -> Result<()> {
+    let deadline = Instant::now() + duration;
+    let mut heartbeat = WebHeartbeat::new(
+        client.clone(),
+        room.room_id,
+        room.parent_area_id,
+        room.area_id,
+    );
+    while Instant::now() < deadline {
+        let interval = heartbeat.beat().await?;
+        tokio::time::sleep_until(deadline.min(Instant::now() + interval)).await;
+    }
+    Ok(())
+}
+
+/// Do the tasks in all rooms once, returning the failed ones.
+pub async fn run_medal_tasks(
+    client: &BiliClient,
+    rooms: &[MedalRoom],
+    config: &MedalTaskConfig,
+) -> Vec<TaskFailure> {
+    let mut failures = Vec::new();
+    let mut fail = |room: &MedalRoom, task, error| {
+        warn!(
+            "medal task {:?} failed in room {}: {:?}",
+            task, room.room_id, error
+        );
+        failures.push(TaskFailure {
+            room_id: room.room_id,
+            task,
+            error,
+        });
+    };
+    for room in rooms {
+        if config.like_clicks > 0 {
+            if let Err(e) = like_room(client, room, config.like_clicks).await {
+                fail(room, MedalTask::Like, e);
+            }
+        }
+        if config.share {
+            if let Err(e) = share_room(client, room.room_id).await {
+                fail(room, MedalTask::Share, e);
+            }
+        }
+        tokio::time::sleep(config.room_delay).await;
+    }
+    if !config.watch.is_zero() {
+        let watched = join_all(
+            rooms
+                .iter()
+                .map(|room| watch_room(client, room, config.watch)),
+        );
+        for (room, result) in rooms.iter().zip(watched.await) {
+            if let Err(e) = result {
+                fail(room, MedalTask::Watch, e);
+            }
+        }
+    }
+    debug!("medal tasks done, {} failed", failures.len());
+    failures
+}
+
+impl BiliClient {
+    /// Do the medal tasks in background every [`MedalTaskConfig::every`], needs a session.
+    pub fn spawn_medal_tasks(
+        &self,
+        rooms: Vec<MedalRoom>,
+        config: MedalTaskConfig,
+    ) -> JoinHandle<()> {
+        let client = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let started = Instant::now();
+                run_medal_tasks(&client, &rooms, &config).await;
+                tokio::time::sleep_until(started + config.every).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Session;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_run_medal_tasks() {
+        let transport = MockTransport::new()
+            .json(consts::LIKE_REPORT, json!({"code": 0, "data": {}}))
+            .json(
+                consts::SHARE_ROOM,
+                json!({"code": 1, "message": "分享失败"}),
+            );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .session(Session::from_cookie_str(
+                "DedeUserID=10086; bili_jct=csrf; SESSDATA=s",
+            ))
+            .build()
+            .unwrap();
+        let room = MedalRoom {
+            room_id: 14507014,
+            anchor_id: 6067854,
+            parent_area_id: 6,
+            area_id: 86,
+        };
+        let config = MedalTaskConfig {
+            watch: Duration::ZERO,
+            room_delay: Duration::ZERO,
+            ..MedalTaskConfig::default()
+        };
+        let failures = run_medal_tasks(&client, &[room], &config).await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].task, MedalTask::Share);
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
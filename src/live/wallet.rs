@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use super::{consts, Gift};
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Balances of the account.
+pub struct Wallet {
+    /// 金瓜子, 1000 is 1 CNY.
+    pub gold: u64,
+    /// 银瓜子.
+    pub silver: u64,
+    /// B币.
+    #[serde(deserialize_with = "crate::de::string_or_f64")]
+    pub bp: f64,
+    /// 硬币.
+    #[serde(deserialize_with = "crate::de::string_or_f64")]
+    pub metal: f64,
+}
+
+impl Wallet {
+    /// Whether `num` of the gift can be paid with gold or silver.
+    pub fn can_afford(&self, gift: &Gift, num: u64) -> bool {
+        let balance = if gift.is_paid() {
+            self.gold
+        } else {
+            self.silver
+        };
+        gift.price.saturating_mul(num) <= balance
+    }
+}
+
+/// Get the gold, silver, B币 and coin balances of the account.
+pub async fn get_wallet(client: &BiliClient) -> Result<Wallet> {
+    client
+        .get(
+            consts::MY_WALLET,
+            &[("need_bp", "1"), ("need_metal", "1"), ("platform", "pc")],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_wallet() {
+        let transport = MockTransport::new().json(
+            consts::MY_WALLET,
+            json!({"code": 0, "data": {
+                "gold": 1500, "silver": 0, "bp": "1.5", "metal": 12,
+                "need_use_new_bp": true, "ios_bp": 0, "common_bp": 0, "new_bp": "0",
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let wallet = get_wallet(&client).await.unwrap();
+        assert_eq!(wallet.bp, 1.5);
+        assert_eq!(wallet.metal, 12.0);
+        let gift: Gift = serde_json::from_value(
+            json!({"id": 1, "name": "gift", "price": 1000, "coin_type": "gold"}),
+        )
+        .unwrap();
+        assert!(wallet.can_afford(&gift, 1));
+        assert!(!wallet.can_afford(&gift, 2));
+    }
+}
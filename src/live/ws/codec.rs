@@ -0,0 +1,337 @@
+//! Framing and compression of danmaku packets, independent of the connection.
+use std::convert::TryFrom;
+
+use deku::prelude::*;
+use flate2::{Decompress, FlushDecompress, Status};
+
+use super::{ProtoVer, WsPacket};
+use crate::error::Error;
+use crate::Result;
+
+/// Encode a packet into its wire format, with lengths computed from its body.
+pub fn encode(pkt: &WsPacket) -> Result<Vec<u8>> {
+    let pkt_len = u32::try_from(HEADER_LEN + pkt.data.len())
+        .ok()
+        .filter(|len| *len <= WsPacket::MAX_LEN)
+        .ok_or_else(|| {
+            DekuError::InvalidParam(format!("packet body too large: {}", pkt.data.len()))
+        })?;
+    let header = WsPacket {
+        pkt_len,
+        hdr_len: WsPacket::HEADER_LEN,
+        data: Vec::new(),
+        ..*pkt
+    };
+    let mut wire = header.to_bytes()?;
+    wire.extend_from_slice(&pkt.data);
+    Ok(wire)
+}
+
+/// Decode every packet in a message, e.g. a ws message or a TCP frame,
+/// unpacking zlib compressed ones into the packets they carry.
+///
+/// Use a [`Decoder`] to decode many messages.
+pub fn decode_all(data: &[u8]) -> Result<Vec<WsPacket>> {
+    Decoder::new().decode(data)
+}
+
+const HEADER_LEN: usize = WsPacket::HEADER_LEN as usize;
+/// Wire value of [`super::ProtoVer::ZlibBuf`].
+const ZLIB_PROTO_VER: u16 = 2;
+/// Bytes inflated at a time, so memory is bounded by the largest inner packet, not the body.
+const INFLATE_CHUNK: usize = 16 * 1024;
+
+/// Decoder of packet messages, reusing its zlib state and buffer between messages.
+pub struct Decoder {
+    zlib: Decompress,
+    inflated: Vec<u8>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Decoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Decoder")
+            .field("capacity", &self.inflated.capacity())
+            .finish()
+    }
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self {
+            zlib: Decompress::new(true),
+            inflated: Vec::new(),
+        }
+    }
+
+    /// Decode every packet in a message, see [`decode_all`].
+    pub fn decode(&mut self, data: &[u8]) -> Result<Vec<WsPacket>> {
+        self.packets(data).collect()
+    }
+
+    /// Iterate over the packets in a message, inflating compressed ones chunk by chunk
+    /// so inner packets are yielded as soon as they are complete.
+    pub fn packets<'a>(&'a mut self, data: &'a [u8]) -> Packets<'a> {
+        Packets {
+            decoder: self,
+            data,
+            compressed: None,
+            start: 0,
+            ended: false,
+            nested: Vec::new().into_iter(),
+        }
+    }
+}
+
+/// Iterator over the packets in a message, see [`Decoder::packets`], ends after an error.
+#[derive(Debug)]
+pub struct Packets<'a> {
+    decoder: &'a mut Decoder,
+    /// Rest of the message.
+    data: &'a [u8],
+    /// Rest of the body of the zlib packet being inflated.
+    compressed: Option<&'a [u8]>,
+    /// Start of the bytes not parsed yet in the inflated buffer.
+    start: usize,
+    /// Whether the zlib stream being inflated has ended.
+    ended: bool,
+    /// Packets of a zlib packet nested in another.
+    nested: std::vec::IntoIter<WsPacket>,
+}
+
+impl Iterator for Packets<'_> {
+    type Item = Result<WsPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.try_next().transpose();
+        if let Some(Err(_)) = next {
+            self.data = &[];
+            self.compressed = None;
+        }
+        next
+    }
+}
+
+impl<'a> Packets<'a> {
+    fn try_next(&mut self) -> Result<Option<WsPacket>> {
+        loop {
+            if let Some(pkt) = self.nested.next() {
+                return Ok(Some(pkt));
+            }
+            if let Some(compressed) = self.compressed {
+                let inflated = &self.decoder.inflated[self.start..];
+                if let Some(len) = packet_len(inflated)? {
+                    trace!("zlib inner({} bytes): {}", len, Hex(&inflated[..len]));
+                    let (_, pkt) = WsPacket::from_bytes((&inflated[..len], 0))?;
+                    self.start += len;
+                    if pkt.proto_ver == ProtoVer::ZlibBuf {
+                        self.nested = decode_all(&pkt.data)?.into_iter();
+                        continue;
+                    }
+                    return Ok(Some(pkt));
+                }
+                if !self.ended {
+                    self.compressed = Some(self.inflate_chunk(compressed)?);
+                    continue;
+                }
+                if !inflated.is_empty() {
+                    return Err(Error::Zlib(std::io::ErrorKind::UnexpectedEof.into()));
+                }
+                self.compressed = None;
+            }
+            if self.data.is_empty() {
+                return Ok(None);
+            }
+            // peek the header so compressed bodies are inflated from the message in place
+            if self.data.len() >= HEADER_LEN
+                && u16::from_be_bytes([self.data[6], self.data[7]]) == ZLIB_PROTO_VER
+            {
+                if let Some(len) = packet_len(self.data)? {
+                    let hdr_len = u16::from_be_bytes([self.data[4], self.data[5]]) as usize;
+                    self.decoder.zlib.reset(true);
+                    self.decoder.inflated.clear();
+                    self.start = 0;
+                    self.ended = false;
+                    self.compressed = Some(&self.data[hdr_len..len]);
+                    self.data = &self.data[len..];
+                    continue;
+                }
+            }
+            let ((rest, _), pkt) = WsPacket::from_bytes((self.data, 0))?;
+            self.data = rest;
+            return Ok(Some(pkt));
+        }
+    }
+
+    /// Inflate the next chunk of `input` after the unparsed bytes, returning the rest of it.
+    fn inflate_chunk(&mut self, input: &'a [u8]) -> Result<&'a [u8]> {
+        let Decoder { zlib, inflated } = &mut *self.decoder;
+        inflated.drain(..self.start);
+        self.start = 0;
+        let len = inflated.len();
+        inflated.resize(len + INFLATE_CHUNK, 0);
+        let (total_in, total_out) = (zlib.total_in(), zlib.total_out());
+        let status = zlib
+            .decompress(input, &mut inflated[len..], FlushDecompress::None)
+            .map_err(|e| Error::Zlib(e.into()));
+        let consumed = (zlib.total_in() - total_in) as usize;
+        let produced = (zlib.total_out() - total_out) as usize;
+        inflated.truncate(len + produced);
+        self.ended = status? == Status::StreamEnd;
+        if !self.ended && consumed == 0 && produced == 0 {
+            return Err(Error::Zlib(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(&input[consumed..])
+    }
+}
+
+/// Length of the packet at the start of `data`, `None` if it is not complete yet.
+fn packet_len(data: &[u8]) -> Result<Option<usize>> {
+    if data.len() < HEADER_LEN {
+        return Ok(None);
+    }
+    let pkt_len = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    let hdr_len = u16::from_be_bytes([data[4], data[5]]) as usize;
+    if hdr_len != HEADER_LEN || pkt_len < hdr_len || pkt_len > WsPacket::MAX_LEN as usize {
+        return Err(DekuError::Parse(format!(
+            "bad packet length {}, header length {}",
+            pkt_len, hdr_len
+        ))
+        .into());
+    }
+    Ok(Some(pkt_len).filter(|len| *len <= data.len()))
+}
+
+/// Hex dump of bytes, encoded only when formatted, i.e. when the log level is enabled.
+pub(crate) struct Hex<'a>(pub &'a [u8]);
+
+impl std::fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Operation;
+    use super::*;
+    use crate::live::event::LiveEvent;
+
+    #[test]
+    fn test_round_trip() {
+        let pkt = WsPacket::new_heartbeat_with(b"[object Object]".to_vec());
+        let wire = encode(&pkt).unwrap();
+        assert_eq!(&wire[..4], &31u32.to_be_bytes());
+        assert_eq!(decode_all(&wire).unwrap(), [pkt]);
+    }
+
+    #[test]
+    fn test_bad_lengths() {
+        let mut wire = encode(&WsPacket::new_heartbeat_with(b"abc".to_vec())).unwrap();
+        // stale lengths are recomputed
+        let stale = WsPacket {
+            pkt_len: 0,
+            ..WsPacket::new_heartbeat_with(b"abc".to_vec())
+        };
+        assert_eq!(encode(&stale).unwrap(), wire);
+        wire[..4].copy_from_slice(&8u32.to_be_bytes());
+        assert!(decode_all(&wire).is_err());
+        wire[..4].copy_from_slice(&(WsPacket::MAX_LEN + 1).to_be_bytes());
+        assert!(decode_all(&wire).is_err());
+        assert!(WsPacket::from_bytes((&wire, 0)).is_err());
+    }
+
+    #[test]
+    fn test_unknown_ids() {
+        let mut wire = encode(&WsPacket::new(
+            ProtoVer::Other(9),
+            Operation::Other(42),
+            b"abc".to_vec(),
+        ))
+        .unwrap();
+        wire.extend(encode(&WsPacket::new_heartbeat()).unwrap());
+        let packets = decode_all(&wire).unwrap();
+        assert_eq!(packets[0].proto_ver, ProtoVer::Other(9));
+        assert_eq!(packets[0].operation, Operation::Other(42));
+        assert_eq!(packets[1], WsPacket::new_heartbeat());
+        assert_eq!(LiveEvent::from_packet(&packets[0]).unwrap(), None);
+        assert!(matches!(
+            packets[0].decode_json_value(),
+            Err(Error::UnsupportedBody {
+                proto_ver: ProtoVer::Other(9)
+            })
+        ));
+    }
+
+    #[test]
+    fn test_zlib_fixture() {
+        let packets = decode_all(include_bytes!("fixtures/zlib_notifications.bin")).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert!(packets
+            .iter()
+            .all(|pkt| pkt.operation == Operation::Notification));
+        match LiveEvent::from_packet(&packets[0]).unwrap() {
+            Some(LiveEvent::Danmaku(danmaku)) => assert_eq!(danmaku.content, "hello"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_concatenated_fixtures() {
+        let mut wire = include_bytes!("fixtures/entering_reply.bin").to_vec();
+        wire.extend_from_slice(include_bytes!("fixtures/heartbeat_reply.bin"));
+        let packets = decode_all(&wire).unwrap();
+        assert_eq!(packets[0].operation, Operation::EnteringReply);
+        assert_eq!(packets[1].popularity(), Some(1234));
+    }
+
+    #[test]
+    fn test_decoder_reuse() {
+        let zlib = include_bytes!("fixtures/zlib_notifications.bin");
+        let mut decoder = Decoder::new();
+        let first = decoder.decode(zlib).unwrap();
+        assert_eq!(decoder.decode(zlib).unwrap(), first);
+        assert!(decoder.decode(&zlib[..zlib.len() - 4]).is_err());
+        assert_eq!(Hex(&[0x00, 0xab]).to_string(), "00ab");
+    }
+
+    #[test]
+    fn test_streamed_inflate() {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let inner = encode(&WsPacket::new_heartbeat_with(vec![b'x'; 1000])).unwrap();
+        let mut z = ZlibEncoder::new(Vec::new(), Compression::default());
+        for _ in 0..500 {
+            z.write_all(&inner).unwrap();
+        }
+        let body = z.finish().unwrap();
+        let mut wire = encode(&WsPacket::new(
+            ProtoVer::ZlibBuf,
+            Operation::Notification,
+            body,
+        ))
+        .unwrap();
+        wire.extend_from_slice(&inner);
+
+        let mut decoder = Decoder::new();
+        assert_eq!(decoder.packets(&wire).filter(Result::is_ok).count(), 501);
+        // inflated chunk by chunk instead of the whole half megabyte body
+        assert!(decoder.inflated.capacity() < 4 * INFLATE_CHUNK);
+
+        let mut truncated = wire[..wire.len() - inner.len() - 8].to_vec();
+        let len = truncated.len() as u32;
+        truncated[..4].copy_from_slice(&len.to_be_bytes());
+        let packets: Vec<_> = decoder.packets(&truncated).collect();
+        assert!(packets.last().unwrap().is_err());
+    }
+}
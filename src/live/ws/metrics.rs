@@ -0,0 +1,130 @@
+//! Runtime statistics of a [`DanmakuStream`](super::DanmakuStream).
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{Duration, Instant};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Statistics of a stream, see [`DanmakuStream::metrics`](super::DanmakuStream::metrics).
+pub struct StreamMetrics {
+    pub room_id: u64,
+    /// Packets decoded, including the ones carried by compressed packets.
+    pub packets: u64,
+    /// Bytes of the messages received, as on the wire.
+    pub bytes: u64,
+    /// Messages which failed to decode or decompress, each one makes the stream reconnect.
+    pub decode_failures: u64,
+    /// Fail-overs to the next server.
+    pub reconnects: u64,
+    /// Packets discarded or evicted because a subscriber was too slow.
+    pub dropped: u64,
+    /// Time between the last answered heartbeat and its reply.
+    pub last_heartbeat_rtt: Option<Duration>,
+}
+
+/// Callback receiving the metrics after each heartbeat reply.
+pub(super) type MetricsHook = Arc<dyn Fn(&StreamMetrics) + Send + Sync>;
+
+/// Counters updated by the tasks of a stream.
+pub(super) struct Metrics {
+    room_id: u64,
+    started: Instant,
+    pub(super) packets: AtomicU64,
+    pub(super) bytes: AtomicU64,
+    pub(super) decode_failures: AtomicU64,
+    pub(super) reconnects: AtomicU64,
+    dropped: Arc<AtomicU64>,
+    /// Nanoseconds from `started` to the last heartbeat, `u64::MAX` if none.
+    heartbeat_sent: AtomicU64,
+    /// Nanoseconds, `u64::MAX` if none.
+    rtt: AtomicU64,
+    hook: Mutex<Option<MetricsHook>>,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.snapshot().fmt(f)
+    }
+}
+
+impl Metrics {
+    pub(super) fn new(room_id: u64, dropped: Arc<AtomicU64>) -> Self {
+        Self {
+            room_id,
+            started: Instant::now(),
+            packets: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            decode_failures: AtomicU64::new(0),
+            reconnects: AtomicU64::new(0),
+            dropped,
+            heartbeat_sent: AtomicU64::new(u64::MAX),
+            rtt: AtomicU64::new(u64::MAX),
+            hook: Mutex::new(None),
+        }
+    }
+
+    pub(super) fn count(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub(super) fn heartbeat_sent(&self) {
+        let sent = self.started.elapsed().as_nanos() as u64;
+        self.heartbeat_sent.store(sent, Ordering::Relaxed);
+    }
+
+    /// Record the round trip of the last heartbeat and call the hook.
+    pub(super) fn heartbeat_replied(&self) {
+        let sent = self.heartbeat_sent.load(Ordering::Relaxed);
+        if sent != u64::MAX {
+            let rtt = (self.started.elapsed().as_nanos() as u64).saturating_sub(sent);
+            self.rtt.store(rtt, Ordering::Relaxed);
+        }
+        let hook = self.hook.lock().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(&self.snapshot());
+        }
+    }
+
+    pub(super) fn set_hook(&self, hook: Option<MetricsHook>) {
+        *self.hook.lock().unwrap() = hook;
+    }
+
+    pub(super) fn snapshot(&self) -> StreamMetrics {
+        let rtt = self.rtt.load(Ordering::Relaxed);
+        StreamMetrics {
+            room_id: self.room_id,
+            packets: self.packets.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            decode_failures: self.decode_failures.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            last_heartbeat_rtt: Some(rtt)
+                .filter(|rtt| *rtt != u64::MAX)
+                .map(Duration::from_nanos),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_heartbeat_rtt() {
+        let metrics = Metrics::new(14507014, Arc::new(AtomicU64::new(0)));
+        let (tx, rx) = std::sync::mpsc::channel();
+        metrics.set_hook(Some(Arc::new(move |m: &StreamMetrics| {
+            tx.send(*m).unwrap();
+        })));
+        Metrics::count(&metrics.packets, 2);
+        metrics.heartbeat_replied();
+        assert_eq!(rx.recv().unwrap().last_heartbeat_rtt, None);
+
+        metrics.heartbeat_sent();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        metrics.heartbeat_replied();
+        let snapshot = rx.recv().unwrap();
+        assert_eq!(snapshot.packets, 2);
+        assert!(snapshot.last_heartbeat_rtt.unwrap() >= Duration::from_millis(40));
+    }
+}
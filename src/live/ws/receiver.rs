@@ -0,0 +1,55 @@
+//! Subscribers of the packets of a [`DanmakuStream`](super::DanmakuStream).
+use std::sync::Arc;
+
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+use tokio::sync::Notify;
+
+use super::WsPacket;
+
+/// Receives the packets of a danmaku stream, like a [`broadcast::Receiver`], telling the
+/// stream when a packet is taken so that [`OverflowPolicy::Block`] resumes reading.
+///
+/// [`OverflowPolicy::Block`]: super::OverflowPolicy::Block
+#[derive(Debug)]
+pub struct PacketReceiver {
+    rx: broadcast::Receiver<WsPacket>,
+    consumed: Arc<Notify>,
+}
+
+impl PacketReceiver {
+    pub(crate) fn new(rx: broadcast::Receiver<WsPacket>, consumed: Arc<Notify>) -> Self {
+        Self { rx, consumed }
+    }
+
+    /// See [`broadcast::Receiver::recv`].
+    pub async fn recv(&mut self) -> Result<WsPacket, RecvError> {
+        let received = self.rx.recv().await;
+        self.consumed.notify_waiters();
+        received
+    }
+
+    /// See [`broadcast::Receiver::try_recv`].
+    pub fn try_recv(&mut self) -> Result<WsPacket, TryRecvError> {
+        let received = self.rx.try_recv();
+        self.consumed.notify_waiters();
+        received
+    }
+
+    /// Create another receiver starting from the next packet.
+    pub fn resubscribe(&self) -> Self {
+        Self::new(self.rx.resubscribe(), self.consumed.clone())
+    }
+}
+
+impl From<broadcast::Receiver<WsPacket>> for PacketReceiver {
+    fn from(rx: broadcast::Receiver<WsPacket>) -> Self {
+        Self::new(rx, Arc::default())
+    }
+}
+
+impl Drop for PacketReceiver {
+    fn drop(&mut self) {
+        // a blocked stream waits for the last subscriber too
+        self.consumed.notify_waiters();
+    }
+}
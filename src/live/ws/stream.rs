@@ -0,0 +1,720 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures_util::stream::{self, BoxStream};
+use futures_util::{sink, Sink, SinkExt, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{broadcast, watch, Mutex, Notify};
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+use tokio::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::metrics::{Metrics, MetricsHook, StreamMetrics};
+use super::{
+    codec, DanmakuStreamConfig, EnteringBody, Operation, OverflowPolicy, PacketReceiver, Transport,
+    WsPacket,
+};
+use crate::error::Error;
+use crate::live::event::EventReceiver;
+use crate::live::multi::SharedBackoff;
+use crate::live::{get_danmaku_info, room_init, DanmakuInfo, RoomInit};
+use crate::net::NetConfig;
+use crate::{BiliClient, Result};
+
+/// Messages received from the server, each holding one or more packets.
+type MessageReader = BoxStream<'static, Result<Vec<u8>>>;
+/// Encoded packets to send to the server.
+type MessageWriter = Pin<Box<dyn Sink<Vec<u8>, Error = Error> + Send>>;
+/// Reader and heartbeat tasks of a connection, both end when the connection fails.
+type ConnectionTasks = JoinSet<Result<()>>;
+
+/// Least time between two fail-overs, so a dead network is not hammered.
+const FAIL_OVER_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Connection state of a [`DanmakuStream`], see [`DanmakuStream::state`].
+pub enum StreamState {
+    /// Connected to a server, receiving packets.
+    Connected,
+    /// A task of the connection failed, connecting to the next server.
+    Reconnecting {
+        cause: String,
+        /// Whether the task panicked rather than failed, which is a bug worth reporting.
+        panicked: bool,
+    },
+    /// Closed by [`DanmakuStream::close`] or because no subscriber is left.
+    Closed,
+}
+
+#[derive(Debug, Clone)]
+pub struct DanmakuStream {
+    inner: Arc<Mutex<DanmakuStreamInner>>,
+    supervisor: Arc<Mutex<JoinHandle<()>>>,
+    state_rx: watch::Receiver<StreamState>,
+    pkt_tx: broadcast::Sender<WsPacket>,
+    consumed: Arc<Notify>,
+    raw_tx: broadcast::Sender<Vec<u8>>,
+    dropped: Arc<AtomicU64>,
+    metrics: Arc<Metrics>,
+}
+
+/// Sends decoded packets to subscribers following an [`OverflowPolicy`].
+#[derive(Clone, Debug)]
+struct PacketSink {
+    tx: broadcast::Sender<WsPacket>,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+    /// Notified by [`PacketReceiver`]s as they take packets.
+    consumed: Arc<Notify>,
+}
+
+impl PacketSink {
+    /// `broadcast` rounds its capacity up to a power of two, so the `full`
+    /// check has to use the same rounded value.
+    fn new(
+        capacity: usize,
+        overflow: OverflowPolicy,
+        dropped: Arc<AtomicU64>,
+        consumed: Arc<Notify>,
+    ) -> (Self, broadcast::Receiver<WsPacket>) {
+        let capacity = capacity.max(1).next_power_of_two();
+        let (tx, rx) = broadcast::channel(capacity);
+        let sink = PacketSink {
+            tx,
+            capacity,
+            overflow,
+            dropped,
+            consumed,
+        };
+        (sink, rx)
+    }
+
+    async fn send(&self, pkt: WsPacket) -> Result<()> {
+        let full = |tx: &broadcast::Sender<WsPacket>| tx.len() >= self.capacity;
+        match self.overflow {
+            OverflowPolicy::Block => loop {
+                // listen before checking, a packet taken in between must not be missed
+                let mut consumed = std::pin::pin!(self.consumed.notified());
+                consumed.as_mut().enable();
+                if !full(&self.tx) || self.tx.receiver_count() == 0 {
+                    break;
+                }
+                consumed.await;
+            },
+            OverflowPolicy::DropOldest if full(&self.tx) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            OverflowPolicy::DropNewest if full(&self.tx) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+            _ => {}
+        }
+        self.tx.send(pkt)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct DanmakuStreamInner {
+    config: DanmakuStreamConfig,
+    net: NetConfig,
+    room_info: RoomInit,
+    danmaku_info: DanmakuInfo,
+    srv_index: usize,
+    sink: PacketSink,
+    raw_tx: broadcast::Sender<Vec<u8>>,
+    state_tx: watch::Sender<StreamState>,
+    metrics: Arc<Metrics>,
+    /// Backoff shared with other rooms of a [`MultiRoomStream`](crate::live::MultiRoomStream).
+    backoff: Option<SharedBackoff>,
+}
+
+impl DanmakuStream {
+    pub async fn new(room_id: u64) -> Result<(Self, PacketReceiver)> {
+        Self::new_with_config(room_id, DanmakuStreamConfig::default()).await
+    }
+
+    pub async fn new_with_config(
+        room_id: u64,
+        config: DanmakuStreamConfig,
+    ) -> Result<(Self, PacketReceiver)> {
+        Self::new_with_client(&BiliClient::new(), room_id, config).await
+    }
+
+    /// Connect with the network settings, e.g. the proxy, of `client`.
+    pub async fn new_with_client(
+        client: &BiliClient,
+        room_id: u64,
+        config: DanmakuStreamConfig,
+    ) -> Result<(Self, PacketReceiver)> {
+        Self::new_with_backoff(client, room_id, config, None).await
+    }
+
+    /// Like [`DanmakuStream::new_with_client`], reconnecting through `backoff` if any.
+    pub(crate) async fn new_with_backoff(
+        client: &BiliClient,
+        room_id: u64,
+        config: DanmakuStreamConfig,
+        backoff: Option<SharedBackoff>,
+    ) -> Result<(Self, PacketReceiver)> {
+        let room_info = room_init(client, room_id).await?;
+        let danmaku_info = get_danmaku_info(client, room_info.room_id).await?;
+        let (state_tx, state_rx) = watch::channel(StreamState::Connected);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let consumed = Arc::new(Notify::new());
+        let (sink, pkt_rx) = PacketSink::new(
+            config.buffer_capacity,
+            config.overflow,
+            dropped.clone(),
+            consumed.clone(),
+        );
+        let pkt_tx = sink.tx.clone();
+        let (raw_tx, _) = broadcast::channel(sink.capacity);
+        let metrics = Arc::new(Metrics::new(room_info.room_id, dropped.clone()));
+
+        let mut inner = DanmakuStreamInner {
+            config,
+            net: client.net().clone(),
+            room_info,
+            danmaku_info,
+            srv_index: 0,
+            sink,
+            raw_tx: raw_tx.clone(),
+            state_tx,
+            metrics: metrics.clone(),
+            backoff,
+        };
+
+        debug!("init {:?}", inner);
+
+        let tasks = inner.connect().await?;
+        let inner = Arc::new(Mutex::new(inner));
+        let supervisor = tokio::spawn(Self::supervise(inner.clone(), tasks));
+
+        Ok((
+            Self {
+                inner,
+                supervisor: Arc::new(Mutex::new(supervisor)),
+                state_rx,
+                pkt_tx,
+                consumed: consumed.clone(),
+                raw_tx,
+                dropped,
+                metrics,
+            },
+            PacketReceiver::new(pkt_rx, consumed),
+        ))
+    }
+
+    /// Wait for the tasks of the connection, failing over to the next server when one fails.
+    async fn supervise(inner: Arc<Mutex<DanmakuStreamInner>>, mut tasks: ConnectionTasks) {
+        let backoff = inner.lock().await.backoff.clone();
+        let mut last_failed: Option<Instant> = None;
+        loop {
+            let (mut cause, panicked) = match tasks.join_next().await {
+                Some(Ok(Ok(()))) | None => {
+                    debug!("danmaku stream has no subscriber left, closing");
+                    inner
+                        .lock()
+                        .await
+                        .state_tx
+                        .send_replace(StreamState::Closed);
+                    return;
+                }
+                Some(Ok(Err(e))) => {
+                    error!("error occurred in ws task: {:?}", e);
+                    (e.to_string(), false)
+                }
+                Some(Err(e)) => {
+                    let message = panic_message(e);
+                    error!("ws task panicked: {}", message);
+                    (message, true)
+                }
+            };
+            tasks.abort_all();
+            loop {
+                if let Some(last_failed) = last_failed {
+                    tokio::time::sleep_until(last_failed + FAIL_OVER_DELAY).await;
+                }
+                if let Some(backoff) = &backoff {
+                    backoff.wait().await;
+                }
+                last_failed = Some(Instant::now());
+                let mut inner = inner.lock().await;
+                inner.state_tx.send_replace(StreamState::Reconnecting {
+                    cause: cause.clone(),
+                    panicked,
+                });
+                match inner.fail_over().await {
+                    Ok(new_tasks) => {
+                        if let Some(backoff) = &backoff {
+                            backoff.succeeded().await;
+                        }
+                        info!("danmaku stream has been reset");
+                        Metrics::count(&inner.metrics.reconnects, 1);
+                        tasks = new_tasks;
+                        break;
+                    }
+                    Err(e) => {
+                        error!(
+                            "while reset danmaku stream, another error occurred: {:?}",
+                            e
+                        );
+                        if let Some(backoff) = &backoff {
+                            backoff.failed().await;
+                        }
+                        cause = e.to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn subscribe(&self) -> PacketReceiver {
+        PacketReceiver::new(self.pkt_tx.subscribe(), self.consumed.clone())
+    }
+
+    /// Subscribe to decoded events, e.g. one receiver each for an overlay, a logger and a bot.
+    pub fn subscribe_events(&self) -> EventReceiver {
+        self.subscribe().into()
+    }
+
+    /// Packets discarded or evicted so far because a subscriber was too slow.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Statistics of the stream so far.
+    pub fn metrics(&self) -> StreamMetrics {
+        self.metrics.snapshot()
+    }
+
+    /// Call `hook` with the metrics after each heartbeat reply, about every heartbeat interval,
+    /// e.g. to export them to a Prometheus collector. Replaces the previous hook.
+    pub fn set_metrics_hook<F>(&self, hook: F)
+    where
+        F: Fn(&StreamMetrics) + Send + Sync + 'static,
+    {
+        self.metrics.set_hook(Some(Arc::new(hook) as MetricsHook));
+    }
+
+    /// Remove the hook set by [`DanmakuStream::set_metrics_hook`].
+    pub fn clear_metrics_hook(&self) {
+        self.metrics.set_hook(None);
+    }
+
+    /// Subscribe to the ws messages exactly as received, before decompressing and splitting,
+    /// e.g. to archive the wire data while consuming decoded packets.
+    ///
+    /// Messages are only copied while there is a raw subscriber.
+    pub fn subscribe_raw(&self) -> broadcast::Receiver<Vec<u8>> {
+        self.raw_tx.subscribe()
+    }
+
+    /// Watch the connection state, e.g. to show reconnections or report panics.
+    pub fn state(&self) -> watch::Receiver<StreamState> {
+        self.state_rx.clone()
+    }
+
+    /// Stop the supervisor and ws tasks, receivers get closed once all clones are dropped.
+    pub async fn close(&self) {
+        // the connection tasks are owned by the supervisor and aborted along with it
+        self.supervisor.lock().await.abort();
+        self.inner
+            .lock()
+            .await
+            .state_tx
+            .send_replace(StreamState::Closed);
+    }
+}
+
+impl DanmakuStreamInner {
+    /// Where the configured transport connects, `host:port` for plain TCP.
+    fn get_url(&self) -> String {
+        let srv = &self.danmaku_info.host_list[self.srv_index];
+        match self.config.transport {
+            Transport::Ws => format!("ws://{}:{}/sub", srv.host, srv.ws_port),
+            Transport::Wss => format!("wss://{}:{}/sub", srv.host, srv.wss_port),
+            Transport::Tcp => format!("{}:{}", srv.host, srv.port),
+        }
+    }
+
+    /// Open a connection with the configured transport.
+    async fn open(&self) -> Result<(MessageWriter, MessageReader)> {
+        if self.config.transport == Transport::Tcp {
+            let srv = &self.danmaku_info.host_list[self.srv_index];
+            let stream = self.net.connect_tcp(&srv.host, srv.port).await?;
+            debug!("tcp stream connected to {}", self.get_url());
+            let (read_half, write_half) = stream.into_split();
+            let reader = stream::unfold(read_half, |mut read_half| async move {
+                let frame = read_frame(&mut read_half).await;
+                Some((frame, read_half))
+            });
+            let writer = sink::unfold(write_half, |mut write_half, bytes: Vec<u8>| async move {
+                write_half.write_all(&bytes).await?;
+                Ok::<_, Error>(write_half)
+            });
+            return Ok((Box::pin(writer), reader.boxed()));
+        }
+        let url = self.get_url();
+        let stream = self.net.connect_websocket(&url).await?;
+        debug!("ws stream connected to {}", url);
+        let (writer, reader) = stream.split();
+        let writer = writer
+            .sink_map_err(Error::from)
+            .with(|bytes| async { Ok::<_, Error>(Message::Binary(bytes)) });
+        let reader = reader.map(|msg| Ok(msg?.into_data()));
+        Ok((Box::pin(writer), reader.boxed()))
+    }
+
+    async fn fail_over(&mut self) -> Result<ConnectionTasks> {
+        self.srv_index = (self.srv_index + 1) % self.danmaku_info.host_list.len();
+        self.connect().await
+    }
+
+    async fn connect(&mut self) -> Result<ConnectionTasks> {
+        let (mut ws_writer, ws_reader) = self.open().await?;
+
+        let entering_body = EnteringBody {
+            uid: self.config.uid,
+            platform: self.config.platform.clone(),
+            proto_ver: self.config.proto_ver,
+            ..EnteringBody::new(self.room_info.room_id, self.danmaku_info.token.clone())
+        };
+        let pkt = WsPacket::new_json(&entering_body, Operation::Entering)?;
+        let payload = codec::encode(&pkt)?;
+        ws_writer.send(payload).await?;
+        ws_writer.flush().await?;
+        debug!("entering_body sent for {}", self.room_info.room_id);
+
+        let mut tasks = JoinSet::new();
+        let heartbeat = WsPacket::new_heartbeat_with(self.config.heartbeat_payload.clone());
+        tasks.spawn(Self::send_heartbeat(
+            ws_writer,
+            heartbeat,
+            self.config.heartbeat_interval,
+            self.metrics.clone(),
+        ));
+        debug!(
+            "ws writer task (heartbeat) set for {}",
+            self.room_info.room_id
+        );
+
+        let parse = Self::parse_pkt(
+            ws_reader,
+            self.sink.clone(),
+            self.raw_tx.clone(),
+            self.metrics.clone(),
+        );
+        #[cfg(feature = "tracing")]
+        let parse = tracing::Instrument::instrument(
+            parse,
+            info_span!("danmaku_stream", room_id = self.room_info.room_id),
+        );
+        tasks.spawn(parse);
+        debug!("ws reader task set for {}", self.room_info.room_id);
+
+        self.state_tx.send_replace(StreamState::Connected);
+        Ok(tasks)
+    }
+
+    /// Decode messages into the sink until the connection fails,
+    /// returning `Ok` once no subscriber is left.
+    async fn parse_pkt(
+        mut ws_reader: MessageReader,
+        sink: PacketSink,
+        raw_tx: broadcast::Sender<Vec<u8>>,
+        metrics: Arc<Metrics>,
+    ) -> Result<()> {
+        async fn parse_pkt_inner(
+            ws_reader: &mut MessageReader,
+            decoder: &mut codec::Decoder,
+            sink: &PacketSink,
+            raw_tx: &broadcast::Sender<Vec<u8>>,
+            metrics: &Metrics,
+        ) -> Result<()> {
+            let msg = match ws_reader.next().await {
+                Some(msg) => msg?,
+                None => {
+                    return Err(Error::UnexpectedResponse(
+                        "connection closed by server".to_string(),
+                    ))
+                }
+            };
+            trace!("got ws message ({} bytes): {}", msg.len(), codec::Hex(&msg));
+            Metrics::count(&metrics.bytes, msg.len() as u64);
+            let mut result = Ok(());
+            for pkt in decoder.packets(&msg) {
+                let pkt = match pkt {
+                    Ok(pkt) => pkt,
+                    Err(e) => {
+                        Metrics::count(&metrics.decode_failures, 1);
+                        result = Err(e);
+                        break;
+                    }
+                };
+                Metrics::count(&metrics.packets, 1);
+                if pkt.operation == Operation::HeartBeatReply {
+                    metrics.heartbeat_replied();
+                }
+                #[cfg(feature = "tracing")]
+                trace!(operation = ?pkt.operation, size = pkt.pkt_len, "ws packet");
+                #[cfg(not(feature = "tracing"))]
+                trace!("ws packet {:?} ({} bytes)", pkt.operation, pkt.pkt_len);
+                if let Err(e) = sink.send(pkt).await {
+                    result = Err(e);
+                    break;
+                }
+            }
+            if raw_tx.receiver_count() > 0 {
+                // a raw subscriber leaving must not break the stream
+                let _ = raw_tx.send(msg);
+            }
+            result
+        }
+
+        let mut decoder = codec::Decoder::new();
+        loop {
+            match parse_pkt_inner(&mut ws_reader, &mut decoder, &sink, &raw_tx, &metrics).await {
+                Ok(()) => {}
+                Err(Error::Consumer(_)) => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn send_heartbeat(
+        mut ws_writer: MessageWriter,
+        heartbeat: WsPacket,
+        interval: Duration,
+        metrics: Arc<Metrics>,
+    ) -> Result<()> {
+        async fn send_heartbeat_inner(
+            ws_writer: &mut MessageWriter,
+            heartbeat: &WsPacket,
+            metrics: &Metrics,
+        ) -> Result<()> {
+            ws_writer.send(codec::encode(heartbeat)?).await?;
+            ws_writer.flush().await?;
+            metrics.heartbeat_sent();
+            Ok(())
+        }
+
+        loop {
+            let checkpoint = Instant::now();
+            send_heartbeat_inner(&mut ws_writer, &heartbeat, &metrics).await?;
+            tokio::time::sleep_until(checkpoint + interval).await;
+        }
+    }
+}
+
+fn panic_message(e: JoinError) -> String {
+    match e.try_into_panic() {
+        Ok(panic) => panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Read a packet framed by its leading length from a raw TCP connection.
+async fn read_frame<R: AsyncReadExt + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut frame = vec![0; 4];
+    reader.read_exact(&mut frame).await?;
+    let len = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]) as usize;
+    if !(WsPacket::HEADER_LEN as usize..=WsPacket::MAX_LEN as usize).contains(&len) {
+        return Err(Error::UnexpectedResponse(format!(
+            "invalid packet length {}",
+            len
+        )));
+    }
+    frame.resize(len, 0);
+    reader.read_exact(&mut frame[4..]).await?;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink_with_capacity(
+        capacity: usize,
+        overflow: OverflowPolicy,
+    ) -> (PacketSink, PacketReceiver) {
+        let consumed = Arc::new(Notify::new());
+        let (sink, rx) = PacketSink::new(
+            capacity,
+            overflow,
+            Arc::new(AtomicU64::new(0)),
+            consumed.clone(),
+        );
+        (sink, PacketReceiver::new(rx, consumed))
+    }
+
+    fn sink(overflow: OverflowPolicy) -> (PacketSink, PacketReceiver) {
+        sink_with_capacity(2, overflow)
+    }
+
+    fn unreachable_inner() -> (DanmakuStreamInner, watch::Receiver<StreamState>) {
+        let (state_tx, state_rx) = watch::channel(StreamState::Connected);
+        let (sink, _) = sink(OverflowPolicy::DropOldest);
+        let mut danmaku_info = DanmakuInfo::default();
+        // nothing listens there, so reconnecting fails
+        danmaku_info.host_list.push(crate::live::DanmakuHost {
+            host: "127.0.0.1".to_string(),
+            port: 1,
+            ..Default::default()
+        });
+        let inner = DanmakuStreamInner {
+            config: DanmakuStreamConfig {
+                transport: Transport::Tcp,
+                ..Default::default()
+            },
+            net: NetConfig::default(),
+            room_info: RoomInit::default(),
+            danmaku_info,
+            srv_index: 0,
+            sink,
+            raw_tx: broadcast::channel(1).0,
+            state_tx,
+            metrics: Arc::new(Metrics::new(0, Arc::new(AtomicU64::new(0)))),
+            backoff: None,
+        };
+        (inner, state_rx)
+    }
+
+    #[test]
+    fn test_get_url() {
+        let (mut inner, _) = unreachable_inner();
+        inner.danmaku_info.host_list[0].ws_port = 2244;
+        inner.danmaku_info.host_list[0].wss_port = 443;
+        assert_eq!(inner.get_url(), "127.0.0.1:1");
+        inner.config.transport = Transport::Ws;
+        assert_eq!(inner.get_url(), "ws://127.0.0.1:2244/sub");
+        inner.config.transport = Transport::Wss;
+        assert_eq!(inner.get_url(), "wss://127.0.0.1:443/sub");
+    }
+
+    #[tokio::test]
+    async fn test_supervise() {
+        let (inner, mut state_rx) = unreachable_inner();
+        let mut tasks = JoinSet::new();
+        tasks.spawn(async { panic!("boom") });
+        let supervisor = tokio::spawn(DanmakuStream::supervise(Arc::new(Mutex::new(inner)), tasks));
+        state_rx.changed().await.unwrap();
+        assert_eq!(
+            *state_rx.borrow(),
+            StreamState::Reconnecting {
+                cause: "boom".to_string(),
+                panicked: true,
+            }
+        );
+        supervisor.abort();
+
+        let (inner, state_rx) = unreachable_inner();
+        let mut tasks = JoinSet::new();
+        // the reader ends once no subscriber is left
+        tasks.spawn(async { Ok(()) });
+        tasks.spawn(std::future::pending());
+        DanmakuStream::supervise(Arc::new(Mutex::new(inner)), tasks).await;
+        assert_eq!(*state_rx.borrow(), StreamState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_supervise_backoff() {
+        let (mut inner, _) = unreachable_inner();
+        let backoff = SharedBackoff::new(Duration::from_millis(1), Duration::from_millis(10));
+        inner.backoff = Some(backoff.clone());
+        let mut tasks = JoinSet::new();
+        tasks.spawn(async { Err(Error::UnexpectedResponse("gone".to_string())) });
+        let supervisor = tokio::spawn(DanmakuStream::supervise(Arc::new(Mutex::new(inner)), tasks));
+        // the failed reconnect is counted by the backoff other rooms share
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while backoff.delay().await == Duration::ZERO {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        supervisor.abort();
+    }
+
+    fn heartbeat(seq_id: u32) -> WsPacket {
+        WsPacket {
+            seq_id,
+            ..WsPacket::new_heartbeat()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_frame() {
+        let mut wire = codec::encode(&WsPacket::new_heartbeat_with(b"abc".to_vec())).unwrap();
+        wire.extend(codec::encode(&WsPacket::new_heartbeat()).unwrap());
+        let mut reader = wire.as_slice();
+        let first = read_frame(&mut reader).await.unwrap();
+        assert_eq!(first.len(), 19);
+        assert_eq!(&first[16..], b"abc");
+        assert_eq!(read_frame(&mut reader).await.unwrap().len(), 16);
+        assert!(read_frame(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policy() {
+        let (newest, mut rx) = sink(OverflowPolicy::DropNewest);
+        for seq_id in 0..3 {
+            newest.send(heartbeat(seq_id)).await.unwrap();
+        }
+        assert_eq!(newest.dropped.load(Ordering::Relaxed), 1);
+        assert_eq!(rx.recv().await.unwrap().seq_id, 0);
+
+        let (oldest, mut rx) = sink(OverflowPolicy::DropOldest);
+        for seq_id in 0..3 {
+            oldest.send(heartbeat(seq_id)).await.unwrap();
+        }
+        assert_eq!(oldest.dropped.load(Ordering::Relaxed), 1);
+        assert!(rx.recv().await.is_err());
+        assert_eq!(rx.recv().await.unwrap().seq_id, 1);
+
+        let (block, mut rx) = sink(OverflowPolicy::Block);
+        let sending = tokio::spawn(async move {
+            for seq_id in 0..3 {
+                block.send(heartbeat(seq_id)).await.unwrap();
+            }
+            block
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!sending.is_finished());
+        assert_eq!(rx.recv().await.unwrap().seq_id, 0);
+        let block = sending.await.unwrap();
+        assert_eq!(block.dropped.load(Ordering::Relaxed), 0);
+        assert_eq!(rx.recv().await.unwrap().seq_id, 1);
+        assert_eq!(rx.recv().await.unwrap().seq_id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_rounded_capacity() {
+        // the channel holds 4 packets, dropping at 3 would lose one it has room for
+        let (newest, mut rx) = sink_with_capacity(3, OverflowPolicy::DropNewest);
+        assert_eq!(newest.capacity, 4);
+        for seq_id in 0..5 {
+            newest.send(heartbeat(seq_id)).await.unwrap();
+        }
+        assert_eq!(newest.dropped.load(Ordering::Relaxed), 1);
+        for seq_id in 0..4 {
+            assert_eq!(rx.recv().await.unwrap().seq_id, seq_id);
+        }
+
+        let (oldest, mut rx) = sink_with_capacity(3, OverflowPolicy::DropOldest);
+        for seq_id in 0..5 {
+            oldest.send(heartbeat(seq_id)).await.unwrap();
+        }
+        assert_eq!(oldest.dropped.load(Ordering::Relaxed), 1);
+        assert!(rx.recv().await.is_err());
+        assert_eq!(rx.recv().await.unwrap().seq_id, 1);
+    }
+}
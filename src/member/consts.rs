@@ -0,0 +1,4 @@
+pub const ARCHIVES: &str = "https://member.bilibili.com/x/web/archives";
+pub const INDEX_STAT: &str = "https://member.bilibili.com/x/web/index/stat";
+pub const EDIT: &str = "https://member.bilibili.com/x/vu/web/edit";
+pub const DELETE: &str = "https://member.bilibili.com/x/web/archive/delete";
@@ -0,0 +1,230 @@
+//! Creator center APIs, for the videos of the logged in user.
+use serde::{Deserialize, Serialize};
+
+use crate::upload::{SubmitRequest, SubmitResult};
+use crate::{BiliClient, Result};
+
+pub mod consts;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Which of the own videos to list.
+pub enum ArchiveStatus {
+    All,
+    /// Under review.
+    Pending,
+    Published,
+    /// Rejected or locked.
+    NotPublished,
+}
+
+impl ArchiveStatus {
+    /// Value of the `status` parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArchiveStatus::All => "is_pubing,pubed,not_pubed",
+            ArchiveStatus::Pending => "is_pubing",
+            ArchiveStatus::Published => "pubed",
+            ArchiveStatus::NotPublished => "not_pubed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of own videos.
+pub struct MyArchives {
+    #[serde(default)]
+    pub arc_audits: Vec<MyArchive>,
+    pub page: MyArchivesPage,
+    /// Counts of all own videos by status.
+    #[serde(rename = "class", default)]
+    pub counts: ArchiveCounts,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct MyArchivesPage {
+    pub pn: u64,
+    pub ps: u64,
+    pub count: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveCounts {
+    pub is_pubing: u64,
+    pub pubed: u64,
+    pub not_pubed: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An own video with its review state and counters.
+pub struct MyArchive {
+    #[serde(rename = "Archive")]
+    pub archive: ArchiveInfo,
+    pub stat: ArchiveCounters,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveInfo {
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub cover: String,
+    pub tid: u64,
+    /// `0` published, negative while reviewing or rejected, see `state_desc`.
+    pub state: i64,
+    pub state_desc: String,
+    /// Why the video was rejected, empty otherwise.
+    pub reject_reason: String,
+    /// Seconds.
+    pub duration: u64,
+    /// Unix timestamp in seconds of the submission.
+    pub ctime: i64,
+    /// Unix timestamp in seconds of the publication.
+    pub ptime: i64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveCounters {
+    pub view: u64,
+    pub danmaku: u64,
+    pub reply: u64,
+    pub favorite: u64,
+    pub coin: u64,
+    pub share: u64,
+    pub like: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Totals of all own videos, and their increase since yesterday.
+pub struct CreatorStat {
+    pub total_click: u64,
+    pub total_dm: u64,
+    pub total_reply: u64,
+    pub total_fav: u64,
+    pub total_coin: u64,
+    pub total_like: u64,
+    pub total_share: u64,
+    pub total_fans: u64,
+    pub incr_click: i64,
+    pub incr_dm: i64,
+    pub incr_reply: i64,
+    pub incr_fav: i64,
+    pub incr_coin: i64,
+    pub incr_like: i64,
+    pub incr_share: i64,
+    pub incr_fans: i64,
+}
+
+#[derive(Clone, Debug)]
+/// New content of a submitted video, which goes under review again.
+pub struct EditRequest {
+    pub aid: u64,
+    /// Replaces everything, so keep the parts which stay in `videos` too.
+    pub content: SubmitRequest,
+}
+
+impl EditRequest {
+    pub fn new(aid: u64, content: SubmitRequest) -> Self {
+        Self { aid, content }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut json = self.content.to_json();
+        json["aid"] = self.aid.into();
+        json
+    }
+}
+
+/// Get a page of own videos with `status`, 10 per page, starting from 1.
+pub async fn get_my_archives(
+    client: &BiliClient,
+    page: u64,
+    status: ArchiveStatus,
+) -> Result<MyArchives> {
+    client
+        .get(
+            consts::ARCHIVES,
+            &[
+                ("status", status.as_str().to_string()),
+                ("pn", page.to_string()),
+                ("ps", "10".to_string()),
+                ("coop", "1".to_string()),
+            ],
+        )
+        .await
+}
+
+/// Get the totals shown on the creator center home.
+pub async fn get_archive_stat(client: &BiliClient) -> Result<CreatorStat> {
+    client.get(consts::INDEX_STAT, &()).await
+}
+
+/// Replace the title, tags, parts etc. of an own video.
+pub async fn edit_archive(client: &BiliClient, request: &EditRequest) -> Result<SubmitResult> {
+    let csrf = client.csrf()?;
+    client
+        .post_json(consts::EDIT, &[("csrf", csrf)], &request.to_json())
+        .await
+}
+
+/// Delete an own video.
+pub async fn delete_archive(client: &BiliClient, aid: u64) -> Result<()> {
+    client
+        .post_action(
+            consts::DELETE,
+            &[("aid", aid.to_string()), ("csrf", client.csrf()?)],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_my_archives() {
+        let transport = MockTransport::new().json(
+            consts::ARCHIVES,
+            json!({"code": 0, "data": {
+                "arc_audits": [{
+                    "Archive": {
+                        "aid": 2, "bvid": "BV1xx411c7mD", "title": "title", "state": -2,
+                        "state_desc": "已退回", "reject_reason": "封面不符合要求",
+                    },
+                    "stat": {"view": 10, "like": 1},
+                }],
+                "page": {"pn": 1, "ps": 10, "count": 1},
+                "class": {"is_pubing": 0, "pubed": 3, "not_pubed": 1},
+            }}),
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let archives = get_my_archives(&client, 1, ArchiveStatus::NotPublished)
+            .await
+            .unwrap();
+        assert_eq!(
+            archives.arc_audits[0].archive.reject_reason,
+            "封面不符合要求"
+        );
+        assert_eq!(archives.counts.pubed, 3);
+        assert!(transport.requests()[0]
+            .query()
+            .unwrap()
+            .starts_with("status=not_pubed"));
+    }
+
+    #[test]
+    fn test_edit_json() {
+        let request = EditRequest::new(2, SubmitRequest::new("new title", 171));
+        let json = request.to_json();
+        assert_eq!(json["aid"], 2);
+        assert_eq!(json["title"], "new title");
+    }
+}
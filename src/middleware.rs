@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use reqwest::{Request, Response};
+use tokio::time::Duration;
+
+use crate::{Error, Result};
+
+/// Hooks run around every HTTP request sent by a [`BiliClient`](crate::BiliClient),
+/// e.g. to log, add tracing spans, mutate headers or record metrics.
+///
+/// All hooks do nothing by default.
+pub trait Middleware: Send + Sync {
+    /// Called before the request is sent, an error aborts it.
+    fn on_request<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<()>> {
+        let _ = request;
+        Box::pin(async { Ok(()) })
+    }
+
+    /// Called once the response head arrived, `elapsed` since the request was sent.
+    fn on_response<'a>(&'a self, response: &'a Response, elapsed: Duration) -> BoxFuture<'a, ()> {
+        let _ = (response, elapsed);
+        Box::pin(async {})
+    }
+
+    /// Called when the request failed without a response.
+    fn on_error<'a>(&'a self, error: &'a Error, elapsed: Duration) -> BoxFuture<'a, ()> {
+        let _ = (error, elapsed);
+        Box::pin(async {})
+    }
+}
+
+/// Share a middleware, e.g. to read the metrics it records.
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn on_request<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<()>> {
+        (**self).on_request(request)
+    }
+
+    fn on_response<'a>(&'a self, response: &'a Response, elapsed: Duration) -> BoxFuture<'a, ()> {
+        (**self).on_response(response, elapsed)
+    }
+
+    fn on_error<'a>(&'a self, error: &'a Error, elapsed: Duration) -> BoxFuture<'a, ()> {
+        (**self).on_error(error, elapsed)
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares(pub(crate) Vec<Arc<dyn Middleware>>);
+
+impl std::fmt::Debug for Middlewares {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Middlewares({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BiliClient;
+    use reqwest::Method;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[derive(Default)]
+    struct Tagger {
+        responses: AtomicUsize,
+    }
+
+    impl Middleware for Tagger {
+        fn on_request<'a>(&'a self, request: &'a mut Request) -> BoxFuture<'a, Result<()>> {
+            request
+                .headers_mut()
+                .insert("x-tag", "tagged".parse().unwrap());
+            Box::pin(async { Ok(()) })
+        }
+
+        fn on_response<'a>(&'a self, _: &'a Response, _: Duration) -> BoxFuture<'a, ()> {
+            self.responses.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async {})
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+        let client = BiliClient::new();
+        let tagger = Arc::new(Tagger::default());
+        client.add_middleware(tagger.clone());
+        let url = format!("http://{}/", addr);
+        client
+            .send(client.request(Method::GET, &url))
+            .await
+            .unwrap();
+        assert!(server.await.unwrap().contains("x-tag: tagged"));
+        assert_eq!(tagger.responses.load(Ordering::SeqCst), 1);
+    }
+}
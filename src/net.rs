@@ -0,0 +1,196 @@
+//! Connection settings shared by the HTTP client and the danmaku websocket.
+//!
+//! Only the settings are built without the `native` feature, connecting needs it.
+use std::net::IpAddr;
+#[cfg(feature = "native")]
+use std::net::SocketAddr;
+
+#[cfg(feature = "native")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "native")]
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+#[cfg(feature = "native")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(feature = "native")]
+use tokio::net::{lookup_host, TcpSocket, TcpStream};
+use tokio::time::Duration;
+#[cfg(feature = "native")]
+use tokio_tungstenite::{client_async_tls_with_config, Connector, MaybeTlsStream, WebSocketStream};
+
+#[cfg(feature = "native")]
+use crate::error::Error;
+#[cfg(feature = "native")]
+use crate::Result;
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub(crate) struct NetConfig {
+    /// HTTP proxy.
+    pub(crate) proxy: Option<Url>,
+    /// Extra trusted roots in PEM.
+    pub(crate) root_certificates: Vec<Vec<u8>>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) local_address: Option<IpAddr>,
+}
+
+#[cfg(feature = "native")]
+impl NetConfig {
+    /// Open a TCP connection to `host:port`, through the proxy if any.
+    pub(crate) async fn connect_tcp(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let connect = async {
+            match &self.proxy {
+                Some(proxy) => {
+                    let proxy_host = proxy.host_str().unwrap_or_default();
+                    let proxy_port = proxy.port_or_known_default().unwrap_or(8080);
+                    let mut stream = self.connect_direct(proxy_host, proxy_port).await?;
+                    http_connect(&mut stream, proxy, host, port).await?;
+                    Ok(stream)
+                }
+                None => self.connect_direct(host, port).await,
+            }
+        };
+        match self.connect_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, connect)
+                .await
+                .map_err(|_| Error::Io(std::io::ErrorKind::TimedOut.into()))?,
+            None => connect.await,
+        }
+    }
+
+    async fn connect_direct(&self, host: &str, port: u16) -> Result<TcpStream> {
+        let mut last_error = None;
+        for addr in lookup_host((host, port)).await? {
+            let socket = match addr {
+                SocketAddr::V4(_) => TcpSocket::new_v4()?,
+                SocketAddr::V6(_) => TcpSocket::new_v6()?,
+            };
+            if let Some(local) = self.local_address {
+                if local.is_ipv4() != addr.is_ipv4() {
+                    continue;
+                }
+                socket.bind(SocketAddr::new(local, 0))?;
+            }
+            match socket.connect(addr).await {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(Error::Io(last_error.unwrap_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("no usable address for {}", host),
+            )
+        })))
+    }
+
+    fn tls_connector(&self) -> Result<native_tls::TlsConnector> {
+        let mut builder = native_tls::TlsConnector::builder();
+        for pem in &self.root_certificates {
+            let certificate = native_tls::Certificate::from_pem(pem)
+                .map_err(|e| Error::UnexpectedResponse(format!("bad certificate: {}", e)))?;
+            builder.add_root_certificate(certificate);
+        }
+        builder
+            .build()
+            .map_err(|e| Error::UnexpectedResponse(format!("failed to set up tls: {}", e)))
+    }
+
+    /// Open a websocket connection to `url` with these settings.
+    pub(crate) async fn connect_websocket(
+        &self,
+        url: &str,
+    ) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>> {
+        let parsed = Url::parse(url).map_err(|e| Error::UnexpectedResponse(e.to_string()))?;
+        let host = parsed.host_str().unwrap_or_default();
+        let port = parsed.port_or_known_default().unwrap_or(443);
+        let stream = self.connect_tcp(host, port).await?;
+        let connector = Connector::NativeTls(self.tls_connector()?);
+        let (stream, _) = client_async_tls_with_config(url, stream, None, Some(connector)).await?;
+        Ok(stream)
+    }
+}
+
+#[cfg(feature = "native")]
+/// Ask an HTTP proxy to tunnel to `host:port`.
+async fn http_connect(stream: &mut TcpStream, proxy: &Url, host: &str, port: u16) -> Result<()> {
+    let mut request = format!(
+        "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+        host = host,
+        port = port
+    );
+    if !proxy.username().is_empty() {
+        // userinfo stays percent-encoded in the url
+        let decode = |s| percent_decode_str(s).collect::<Vec<u8>>();
+        let mut credentials = decode(proxy.username());
+        credentials.push(b':');
+        credentials.extend(decode(proxy.password().unwrap_or_default()));
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // read the response head byte by byte to leave the tunneled stream untouched
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8192 {
+            return Err(Error::UnexpectedResponse(
+                "proxy response too long".to_string(),
+            ));
+        }
+        head.push(stream.read_u8().await?);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status = head.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(Error::UnexpectedResponse(format!(
+            "proxy refused to connect: {}",
+            head.lines().next().unwrap_or_default()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_http_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = listener.local_addr().unwrap();
+        let proxy = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\ntunneled")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+        let config = NetConfig {
+            proxy: Some(Url::parse(&format!("http://us%3Aer:p%40ss@{}", proxy_addr)).unwrap()),
+            ..NetConfig::default()
+        };
+        let mut stream = config.connect_tcp("example.com", 443).await.unwrap();
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "tunneled");
+        let request = proxy.await.unwrap();
+        assert!(request.starts_with("CONNECT example.com:443 HTTP/1.1\r\n"));
+        assert!(request.contains("Proxy-Authorization: Basic dXM6ZXI6cEBzcw==\r\n"));
+    }
+
+    #[test]
+    fn test_proxy_scheme() {
+        let build = |proxy| crate::BiliClient::builder().proxy(proxy).build();
+        assert!(build("http://127.0.0.1:8080").is_ok());
+        assert!(build("https://127.0.0.1:8080").is_err());
+        assert!(build("socks5://127.0.0.1:1080").is_err());
+    }
+}
@@ -0,0 +1,4 @@
+pub const START: &str = "https://live-open.biliapi.com/v2/app/start";
+pub const END: &str = "https://live-open.biliapi.com/v2/app/end";
+pub const HEARTBEAT: &str = "https://live-open.biliapi.com/v2/app/heartbeat";
+pub const BATCH_HEARTBEAT: &str = "https://live-open.biliapi.com/v2/app/batchHeartbeat";
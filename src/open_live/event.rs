@@ -0,0 +1,180 @@
+//! Events pushed by the open platform danmaku server.
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::live::GuardLevel;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Decoded notification of an open platform stream.
+pub enum OpenLiveEvent {
+    /// A danmaku (`LIVE_OPEN_PLATFORM_DM`).
+    Danmaku(OpenDanmaku),
+    /// A gift (`LIVE_OPEN_PLATFORM_SEND_GIFT`).
+    Gift(OpenGift),
+    /// A super chat (`LIVE_OPEN_PLATFORM_SUPER_CHAT`).
+    SuperChat(OpenSuperChat),
+    /// A guard was bought (`LIVE_OPEN_PLATFORM_GUARD`).
+    Guard(OpenGuard),
+    /// Likes (`LIVE_OPEN_PLATFORM_LIKE`).
+    Like(OpenLike),
+    /// The project was ended (`LIVE_OPEN_PLATFORM_INTERACTION_END`).
+    InteractionEnd { game_id: String },
+    /// Notification which has no typed model yet.
+    Other { cmd: String, body: Value },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Fields shared by all events sent by a viewer.
+pub struct OpenUser {
+    pub uid: u64,
+    /// Id of the viewer within the app.
+    pub open_id: String,
+    pub uname: String,
+    pub uface: String,
+    pub fans_medal_level: u32,
+    pub fans_medal_name: String,
+    /// Whether the medal is of this room and worn.
+    pub fans_medal_wearing_status: bool,
+    pub guard_level: GuardLevel,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenDanmaku {
+    #[serde(flatten)]
+    pub user: OpenUser,
+    pub room_id: u64,
+    pub msg: String,
+    pub msg_id: String,
+    /// `0` text, `1` emoticon, whose image is `emoji_img_url`.
+    pub dm_type: u8,
+    pub emoji_img_url: String,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenGift {
+    #[serde(flatten)]
+    pub user: OpenUser,
+    pub room_id: u64,
+    pub gift_id: u64,
+    pub gift_name: String,
+    pub gift_num: u32,
+    /// Price of one gift, in 1/1000 yuan if `paid`, or silver.
+    pub price: u64,
+    pub paid: bool,
+    pub gift_icon: String,
+    pub msg_id: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenSuperChat {
+    #[serde(flatten)]
+    pub user: OpenUser,
+    pub room_id: u64,
+    pub message_id: u64,
+    pub message: String,
+    /// Price in yuan.
+    pub rmb: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub msg_id: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenGuard {
+    /// The buyer, only the uid, open id, name and face are set.
+    pub user_info: OpenUser,
+    pub room_id: u64,
+    pub guard_level: GuardLevel,
+    pub guard_num: u32,
+    /// e.g. `月`.
+    pub guard_unit: String,
+    pub fans_medal_level: u32,
+    pub fans_medal_name: String,
+    pub msg_id: String,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenLike {
+    #[serde(flatten)]
+    pub user: OpenUser,
+    pub room_id: u64,
+    /// e.g. `为主播点赞了`.
+    pub like_text: String,
+    pub like_count: u32,
+    pub msg_id: String,
+    pub timestamp: i64,
+}
+
+impl OpenLiveEvent {
+    /// Decode a notification body, a json object with `cmd` and `data`.
+    pub fn from_body(body: Value) -> Self {
+        fn data<T: DeserializeOwned>(body: &Value) -> Option<T> {
+            serde_json::from_value(body["data"].clone()).ok()
+        }
+
+        let cmd = body["cmd"].as_str().unwrap_or_default().to_string();
+        let event = match cmd.as_str() {
+            "LIVE_OPEN_PLATFORM_DM" => data(&body).map(OpenLiveEvent::Danmaku),
+            "LIVE_OPEN_PLATFORM_SEND_GIFT" => data(&body).map(OpenLiveEvent::Gift),
+            "LIVE_OPEN_PLATFORM_SUPER_CHAT" => data(&body).map(OpenLiveEvent::SuperChat),
+            "LIVE_OPEN_PLATFORM_GUARD" => data(&body).map(OpenLiveEvent::Guard),
+            "LIVE_OPEN_PLATFORM_LIKE" => data(&body).map(OpenLiveEvent::Like),
+            "LIVE_OPEN_PLATFORM_INTERACTION_END" => Some(OpenLiveEvent::InteractionEnd {
+                game_id: body["data"]["game_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            }),
+            _ => None,
+        };
+        event.unwrap_or(OpenLiveEvent::Other { cmd, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode() {
+        let body = json!({
+            "cmd": "LIVE_OPEN_PLATFORM_DM",
+            "data": {
+                "room_id": 14507014, "uid": 10086, "open_id": "o", "uname": "someone",
+                "msg": "hello", "msg_id": "m", "fans_medal_level": 21, "fans_medal_name": "medal",
+                "fans_medal_wearing_status": true, "guard_level": 3, "timestamp": 1650000000,
+                "uface": "", "emoji_img_url": "", "dm_type": 0,
+            },
+        });
+        match OpenLiveEvent::from_body(body) {
+            OpenLiveEvent::Danmaku(danmaku) => {
+                assert_eq!(danmaku.msg, "hello");
+                assert_eq!(danmaku.user.uname, "someone");
+                assert_eq!(danmaku.user.guard_level, GuardLevel::Captain);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        let body = json!({"cmd": "LIVE_OPEN_PLATFORM_GUARD", "data": {
+            "user_info": {"uid": 10086, "open_id": "o", "uname": "someone", "uface": ""},
+            "guard_level": 3, "guard_num": 1, "guard_unit": "月", "room_id": 14507014,
+        }});
+        match OpenLiveEvent::from_body(body) {
+            OpenLiveEvent::Guard(guard) => assert_eq!(guard.user_info.uid, 10086),
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! The official open platform (开放平台) for interactive apps.
+//!
+//! With the credentials of a registered app and the 身份码 of a streamer, an app
+//! starts a project, keeps it alive with heartbeats and receives the events of the room
+//! from a dedicated danmaku server, see [`OpenLiveStream`].
+use hmac::{Hmac, Mac};
+use md5::{Digest, Md5};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Sha256;
+
+use crate::client::api_response;
+use crate::{BiliClient, Result};
+
+pub mod consts;
+pub mod event;
+#[cfg(feature = "native")]
+mod stream;
+
+pub use event::{
+    OpenDanmaku, OpenGift, OpenGuard, OpenLike, OpenLiveEvent, OpenSuperChat, OpenUser,
+};
+#[cfg(feature = "native")]
+pub use stream::OpenLiveStream;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Credentials of an app registered on the open platform.
+pub struct OpenLiveCredential {
+    pub access_key_id: String,
+    pub access_key_secret: String,
+    pub app_id: i64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// A started project.
+pub struct AppStart {
+    pub game_info: GameInfo,
+    pub websocket_info: WebsocketInfo,
+    pub anchor_info: AnchorInfo,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameInfo {
+    /// Id of the project, empty for apps which are not games.
+    pub game_id: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// Where to receive the events.
+pub struct WebsocketInfo {
+    /// Body of the entering packet.
+    pub auth_body: String,
+    pub wss_link: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// The streamer who gave the 身份码.
+pub struct AnchorInfo {
+    pub room_id: u64,
+    pub uname: String,
+    pub uface: String,
+    pub uid: u64,
+    /// Id of the streamer within the app.
+    pub open_id: String,
+}
+
+/// Headers signing a request, `Authorization` being the HMAC-SHA256 of the `x-bili-*` ones.
+fn signed_headers(
+    credential: &OpenLiveCredential,
+    body: &str,
+    timestamp: i64,
+    nonce: &str,
+) -> Vec<(&'static str, String)> {
+    // sorted by name, as required by the signature
+    let mut headers = vec![
+        ("x-bili-accesskeyid", credential.access_key_id.clone()),
+        ("x-bili-content-md5", hex::encode(Md5::digest(body))),
+        ("x-bili-signature-method", "HMAC-SHA256".to_string()),
+        ("x-bili-signature-nonce", nonce.to_string()),
+        ("x-bili-signature-version", "1.0".to_string()),
+        ("x-bili-timestamp", timestamp.to_string()),
+    ];
+    let canonical = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(credential.access_key_secret.as_bytes())
+        .expect("hmac accepts any key");
+    mac.update(canonical.as_bytes());
+    headers.push(("Authorization", hex::encode(mac.finalize().into_bytes())));
+    headers
+}
+
+/// POST a signed json body and unwrap its [`ApiResponse`](crate::ApiResponse).
+async fn post_signed<T: DeserializeOwned>(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    url: &str,
+    body: &serde_json::Value,
+) -> Result<T> {
+    let body = body.to_string();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let nonce = format!("{}{}", timestamp, rand::random::<u32>());
+    // no cookies, the app authenticates by the signature
+    let mut request = client
+        .http()
+        .request(Method::POST, url)
+        .header("Accept", "application/json")
+        .header("Content-Type", "application/json");
+    for (name, value) in signed_headers(credential, &body, timestamp, &nonce) {
+        request = request.header(name, value);
+    }
+    debug!("POST signed {}", url);
+    let response = client.send(request.body(body)).await?;
+    api_response(response).await?.into_result()
+}
+
+/// Start a project in the room of the streamer who gave the 身份码 `code`.
+pub async fn start(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    code: &str,
+) -> Result<AppStart> {
+    post_signed(
+        client,
+        credential,
+        consts::START,
+        &json!({"code": code, "app_id": credential.app_id}),
+    )
+    .await
+}
+
+/// End a project, which should be done when the app stops.
+pub async fn end(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    game_id: &str,
+) -> Result<()> {
+    post_signed::<serde::de::IgnoredAny>(
+        client,
+        credential,
+        consts::END,
+        &json!({"app_id": credential.app_id, "game_id": game_id}),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Keep a project alive, it ends after about a minute without heartbeat.
+pub async fn heartbeat(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    game_id: &str,
+) -> Result<()> {
+    post_signed::<serde::de::IgnoredAny>(
+        client,
+        credential,
+        consts::HEARTBEAT,
+        &json!({"game_id": game_id}),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Keep many projects alive at once, returning the ids which failed.
+pub async fn batch_heartbeat(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    game_ids: &[String],
+) -> Result<Vec<String>> {
+    #[derive(Deserialize)]
+    struct BatchHeartbeat {
+        #[serde(default)]
+        failed_game_ids: Vec<String>,
+    }
+
+    let result: BatchHeartbeat = post_signed(
+        client,
+        credential,
+        consts::BATCH_HEARTBEAT,
+        &json!({"game_ids": game_ids}),
+    )
+    .await?;
+    Ok(result.failed_game_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    fn credential() -> OpenLiveCredential {
+        OpenLiveCredential {
+            access_key_id: "key".to_string(),
+            access_key_secret: "secret".to_string(),
+            app_id: 1650000000000,
+        }
+    }
+
+    #[test]
+    fn test_signed_headers() {
+        let headers = signed_headers(&credential(), "{}", 1650000000, "nonce");
+        assert_eq!(headers[1].1, "99914b932bd37a50b983c5e7c90ae93b");
+        let (name, signature) = headers.last().unwrap();
+        assert_eq!(*name, "Authorization");
+        assert_eq!(signature.len(), 64);
+        let other = signed_headers(&credential(), "{}", 1650000001, "nonce");
+        assert_ne!(other.last().unwrap().1, *signature);
+    }
+
+    #[tokio::test]
+    async fn test_start() {
+        let transport = MockTransport::new().json(
+            consts::START,
+            json!({"code": 0, "message": "0", "request_id": "1", "data": {
+                "game_info": {"game_id": "game"},
+                "websocket_info": {"auth_body": "{}", "wss_link": ["wss://example.com/sub"]},
+                "anchor_info": {"room_id": 14507014, "uname": "anchor", "uid": 6067854, "open_id": "o"},
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let app = start(&client, &credential(), "code").await.unwrap();
+        assert_eq!(app.game_info.game_id, "game");
+        assert_eq!(app.anchor_info.room_id, 14507014);
+        assert_eq!(app.websocket_info.wss_link.len(), 1);
+    }
+}
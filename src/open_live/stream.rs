@@ -0,0 +1,145 @@
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{end, heartbeat, start, AppStart, OpenLiveCredential, OpenLiveEvent};
+use crate::error::Error;
+use crate::live::ws::{codec, Operation, ProtoVer, WsPacket};
+use crate::{BiliClient, Result};
+
+/// Interval of both the danmaku and the project heartbeats.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+/// Wait before connecting to the next server after a failure.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Events of a room through the open platform, keeping the project alive while open.
+#[derive(Debug)]
+pub struct OpenLiveStream {
+    client: BiliClient,
+    credential: OpenLiveCredential,
+    app: AppStart,
+    tx: broadcast::Sender<OpenLiveEvent>,
+    task: JoinHandle<()>,
+}
+
+impl OpenLiveStream {
+    /// Start a project with the 身份码 `code` and connect to its danmaku server.
+    pub async fn start(
+        client: &BiliClient,
+        credential: OpenLiveCredential,
+        code: &str,
+        capacity: usize,
+    ) -> Result<(Self, broadcast::Receiver<OpenLiveEvent>)> {
+        let app = start(client, &credential, code).await?;
+        if app.websocket_info.wss_link.is_empty() {
+            return Err(Error::UnexpectedResponse(
+                "no danmaku server for the project".to_string(),
+            ));
+        }
+        let (tx, rx) = broadcast::channel(capacity);
+        let task = tokio::spawn(run(
+            client.clone(),
+            credential.clone(),
+            app.clone(),
+            tx.clone(),
+        ));
+        Ok((
+            Self {
+                client: client.clone(),
+                credential,
+                app,
+                tx,
+                task,
+            },
+            rx,
+        ))
+    }
+
+    /// The started project, with the streamer.
+    pub fn app(&self) -> &AppStart {
+        &self.app
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<OpenLiveEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Disconnect and end the project.
+    pub async fn close(self) -> Result<()> {
+        self.task.abort();
+        end(&self.client, &self.credential, &self.app.game_info.game_id).await
+    }
+}
+
+/// Keep connected, trying the servers in turn, until no subscriber is left.
+async fn run(
+    client: BiliClient,
+    credential: OpenLiveCredential,
+    app: AppStart,
+    tx: broadcast::Sender<OpenLiveEvent>,
+) {
+    let links = &app.websocket_info.wss_link;
+    for link in links.iter().cycle() {
+        match connect(&client, &credential, &app, link, &tx).await {
+            Ok(()) => {
+                debug!("open live stream has no subscriber left, closing");
+                return;
+            }
+            Err(e) => error!("error occurred in open live stream {}: {:?}", link, e),
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn connect(
+    client: &BiliClient,
+    credential: &OpenLiveCredential,
+    app: &AppStart,
+    link: &str,
+    tx: &broadcast::Sender<OpenLiveEvent>,
+) -> Result<()> {
+    let mut ws = client.net().connect_websocket(link).await?;
+    let auth = WsPacket::new(
+        ProtoVer::Json,
+        Operation::Entering,
+        app.websocket_info.auth_body.as_bytes().to_vec(),
+    );
+    ws.send(Message::Binary(codec::encode(&auth)?)).await?;
+    debug!("open live stream connected to {}", link);
+
+    let mut decoder = codec::Decoder::new();
+    let mut ticker = interval(HEARTBEAT_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            msg = ws.next() => {
+                let msg = match msg {
+                    Some(msg) => msg?.into_data(),
+                    None => {
+                        return Err(Error::UnexpectedResponse(
+                            "connection closed by server".to_string(),
+                        ))
+                    }
+                };
+                for pkt in decoder.packets(&msg) {
+                    let pkt = pkt?;
+                    if pkt.operation != Operation::Notification {
+                        continue;
+                    }
+                    let event = OpenLiveEvent::from_body(pkt.decode_json_value()?);
+                    if tx.send(event).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                ws.send(Message::Binary(codec::encode(&WsPacket::new_heartbeat())?)).await?;
+                if let Err(e) = heartbeat(client, credential, &app.game_info.game_id).await {
+                    warn!("open live project heartbeat failed: {:?}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use futures_util::FutureExt;
+
+use crate::Result;
+
+/// Items of a page and the cursor of the next page, `None` on the last one.
+type Fetch<T> = Box<dyn FnMut(u64) -> BoxFuture<'static, Result<(Vec<T>, Option<u64>)>> + Send>;
+
+/// A list endpoint walked page by page, either by page number or by an offset the server
+/// returns with each page.
+pub struct Paginated<T> {
+    fetch: Fetch<T>,
+    next: Option<u64>,
+}
+
+impl<T> std::fmt::Debug for Paginated<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginated")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Send + 'static> Paginated<T> {
+    /// Pages numbered from `1`, `fetch` returns the items of a page and whether there are more.
+    pub fn by_page<F, Fut>(mut fetch: F) -> Self
+    where
+        F: FnMut(u64) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(Vec<T>, bool)>> + Send + 'static,
+    {
+        Self {
+            fetch: Box::new(move |page| {
+                fetch(page)
+                    .map(move |result| {
+                        result.map(|(items, more)| (items, more.then_some(page + 1)))
+                    })
+                    .boxed()
+            }),
+            next: Some(1),
+        }
+    }
+
+    /// Pages starting at offset `start`, `fetch` returns the items of a page and the offset of
+    /// the next one, `None` on the last page.
+    pub fn by_offset<F, Fut>(start: u64, mut fetch: F) -> Self
+    where
+        F: FnMut(u64) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(Vec<T>, Option<u64>)>> + Send + 'static,
+    {
+        Self {
+            fetch: Box::new(move |offset| fetch(offset).boxed()),
+            next: Some(start),
+        }
+    }
+
+    /// The page number or offset of the next page, `None` once all pages are fetched.
+    pub fn next_cursor(&self) -> Option<u64> {
+        self.next
+    }
+
+    /// Fetch the next page, `None` once all pages are fetched.
+    ///
+    /// The cursor only advances on success, so a failed page can be fetched again.
+    pub async fn next_page(&mut self) -> Option<Result<Vec<T>>> {
+        let cursor = self.next?;
+        match (self.fetch)(cursor).await {
+            Ok((items, next)) => {
+                self.next = next;
+                Some(Ok(items))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Walk the remaining items, requesting pages as they are consumed.
+    ///
+    /// The stream ends after yielding an error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        let pages: BoxStream<'static, Vec<Result<T>>> =
+            stream::unfold(Some(self), |paginated| async move {
+                let mut paginated = paginated?;
+                match paginated.next_page().await? {
+                    Ok(items) => Some((items.into_iter().map(Ok).collect(), Some(paginated))),
+                    Err(e) => Some((vec![Err(e)], None)),
+                }
+            })
+            .boxed();
+        pages.flat_map(stream::iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[tokio::test]
+    async fn test_paginated() {
+        let mut pages = Paginated::by_page(|page| async move { Ok((vec![page], page < 3)) });
+        assert_eq!(pages.next_page().await.unwrap().unwrap(), [1]);
+        assert_eq!(pages.next_cursor(), Some(2));
+        let rest: Vec<_> = pages.into_stream().map(Result::unwrap).collect().await;
+        assert_eq!(rest, [2, 3]);
+
+        let offsets = Paginated::by_offset(10, |offset| async move {
+            if offset == 0 {
+                return Err(Error::UnexpectedResponse("failed".to_string()));
+            }
+            Ok((vec![offset], Some(offset - 5)))
+        });
+        let items: Vec<_> = offsets.into_stream().collect().await;
+        assert_eq!(items.len(), 3);
+        assert!(items[2].is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Header presets, bilibili answers `-412` to requests whose headers do not look like
+//! one of its clients.
+use reqwest::header::{HeaderMap, HeaderValue, ORIGIN, REFERER, USER_AGENT};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// A client whose `User-Agent`, `Referer` and `Origin` requests carry, see
+/// [`ClientBuilder::header_preset`](crate::ClientBuilder::header_preset).
+pub enum HeaderPreset {
+    /// Chrome on Windows browsing the website.
+    #[default]
+    WebChrome,
+    /// The Android app, without `Referer` nor `Origin`.
+    AndroidApp,
+    /// The TV app (云视听小电视), without `Referer` nor `Origin`.
+    TvApp,
+}
+
+impl HeaderPreset {
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            HeaderPreset::WebChrome => {
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+                (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36"
+            }
+            HeaderPreset::AndroidApp => {
+                "Mozilla/5.0 BiliDroid/7.38.0 (bbcallen@gmail.com) os/android \
+                model/Pixel mobi_app/android build/7380300 channel/bili \
+                innerVer/7380300 osVer/12 network/2"
+            }
+            HeaderPreset::TvApp => {
+                "Mozilla/5.0 BiliTV/1.6.6 (bbcallen@gmail.com) os/android \
+                model/MiTV mobi_app/android_tv_yst build/106600 channel/master \
+                innerVer/106600 osVer/9 network/2"
+            }
+        }
+    }
+
+    /// Default headers of the preset, with `user_agent` instead of its own if given.
+    pub(crate) fn headers(&self, user_agent: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let user_agent = user_agent.unwrap_or_else(|| self.user_agent());
+        if let Ok(user_agent) = HeaderValue::from_str(user_agent) {
+            headers.insert(USER_AGENT, user_agent);
+        }
+        if *self == HeaderPreset::WebChrome {
+            headers.insert(
+                REFERER,
+                HeaderValue::from_static("https://www.bilibili.com/"),
+            );
+            headers.insert(ORIGIN, HeaderValue::from_static("https://www.bilibili.com"));
+        }
+        headers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BiliClient;
+
+    #[test]
+    fn test_headers() {
+        let headers = HeaderPreset::WebChrome.headers(None);
+        assert!(headers[USER_AGENT].to_str().unwrap().contains("Chrome"));
+        assert_eq!(headers[ORIGIN], "https://www.bilibili.com");
+        let headers = HeaderPreset::AndroidApp.headers(Some("custom"));
+        assert_eq!(headers[USER_AGENT], "custom");
+        assert!(!headers.contains_key(REFERER));
+
+        let client = BiliClient::builder()
+            .header_preset(HeaderPreset::TvApp)
+            .build()
+            .unwrap();
+        assert_eq!(client.user_agent(), HeaderPreset::TvApp.user_agent());
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::time::{Duration, Instant};
+
+/// How often buckets of hosts no longer requested are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// Token buckets limiting requests sent by a [`BiliClient`](crate::BiliClient).
+pub struct RateLimitConfig {
+    /// Requests per second to all hosts, unlimited if not positive.
+    pub global_rate: f64,
+    /// Requests which can be sent at once to all hosts.
+    pub global_burst: u32,
+    /// Requests per second to a single host, e.g. `api.live.bilibili.com`,
+    /// unlimited if not positive.
+    pub host_rate: f64,
+    /// Requests which can be sent at once to a single host.
+    pub host_burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            global_rate: 20.0,
+            global_burst: 20,
+            host_rate: 5.0,
+            host_burst: 10,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(burst: u32, now: Instant) -> Self {
+        Self {
+            tokens: burst as f64,
+            updated: now,
+        }
+    }
+
+    /// Take a token, returning how long to wait until it is available.
+    ///
+    /// Tokens may go negative so that concurrent callers queue up behind each other.
+    fn reserve(&mut self, rate: f64, burst: u32, now: Instant) -> Duration {
+        if rate.is_nan() || rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(burst as f64) - 1.0;
+        self.updated = now;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+
+    /// Whether the bucket has refilled, so dropping it changes nothing.
+    fn is_full(&self, rate: f64, burst: u32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        rate.is_nan() || rate <= 0.0 || self.tokens + elapsed * rate >= burst as f64
+    }
+}
+
+#[derive(Debug)]
+struct HostBuckets {
+    buckets: HashMap<String, Bucket>,
+    swept: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    global: Mutex<Bucket>,
+    hosts: Mutex<HostBuckets>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            global: Mutex::new(Bucket::new(config.global_burst, Instant::now())),
+            hosts: Mutex::new(HostBuckets {
+                buckets: HashMap::new(),
+                swept: Instant::now(),
+            }),
+        }
+    }
+
+    /// How long to wait before a request to `host` can be sent.
+    pub(crate) fn reserve(&self, host: &str) -> Duration {
+        let config = &self.config;
+        let now = Instant::now();
+        let global =
+            self.global
+                .lock()
+                .unwrap()
+                .reserve(config.global_rate, config.global_burst, now);
+        let mut hosts = self.hosts.lock().unwrap();
+        if now.saturating_duration_since(hosts.swept) >= SWEEP_INTERVAL {
+            hosts
+                .buckets
+                .retain(|_, bucket| !bucket.is_full(config.host_rate, config.host_burst, now));
+            hosts.swept = now;
+        }
+        let host = hosts
+            .buckets
+            .entry(host.to_string())
+            .or_insert_with(|| Bucket::new(config.host_burst, now))
+            .reserve(config.host_rate, config.host_burst, now);
+        global.max(host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket() {
+        let now = Instant::now();
+        let mut bucket = Bucket::new(2, now);
+        assert_eq!(bucket.reserve(2.0, 2, now), Duration::ZERO);
+        assert_eq!(bucket.reserve(2.0, 2, now), Duration::ZERO);
+        assert_eq!(bucket.reserve(2.0, 2, now), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(2.0, 2, now), Duration::from_secs(1));
+        // refilled, but never beyond the burst
+        let later = now + Duration::from_secs(60);
+        assert_eq!(bucket.reserve(2.0, 2, later), Duration::ZERO);
+        assert_eq!(bucket.reserve(2.0, 2, later), Duration::ZERO);
+        assert!(bucket.reserve(2.0, 2, later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let now = Instant::now();
+        let mut bucket = Bucket::new(0, now);
+        for rate in [0.0, -1.0, f64::NAN] {
+            assert_eq!(bucket.reserve(rate, 0, now), Duration::ZERO);
+        }
+        let limiter = RateLimiter::new(RateLimitConfig {
+            global_rate: 0.0,
+            global_burst: 0,
+            host_rate: -5.0,
+            host_burst: 0,
+        });
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve("api.bilibili.com"), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_sweep_idle_hosts() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        limiter.reserve("a.bilibili.com");
+        limiter.reserve("b.bilibili.com");
+        {
+            // pretend both were last requested a while ago
+            let mut hosts = limiter.hosts.lock().unwrap();
+            assert_eq!(hosts.buckets.len(), 2);
+            let past = Instant::now() - SWEEP_INTERVAL;
+            hosts.swept = past;
+            hosts.buckets.values_mut().for_each(|b| b.updated = past);
+        }
+        limiter.reserve("c.bilibili.com");
+        let hosts = limiter.hosts.lock().unwrap();
+        assert_eq!(hosts.buckets.len(), 1);
+        assert!(hosts.buckets.contains_key("c.bilibili.com"));
+    }
+}
@@ -0,0 +1,4 @@
+pub const REPLY: &str = "https://api.bilibili.com/x/v2/reply";
+pub const SUB_REPLY: &str = "https://api.bilibili.com/x/v2/reply/reply";
+pub const ADD_REPLY: &str = "https://api.bilibili.com/x/v2/reply/add";
+pub const LIKE_REPLY: &str = "https://api.bilibili.com/x/v2/reply/action";
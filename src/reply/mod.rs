@@ -0,0 +1,310 @@
+//! Comment (reply) APIs.
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Paginated, Result};
+
+pub mod consts;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Kind of the object comments belong to, `oid` is the id of that object.
+pub enum ReplyType {
+    /// `oid` is the aid.
+    Video,
+    /// `oid` is the id of the dynamic with images.
+    DynamicDraw,
+    /// `oid` is the cvid.
+    Article,
+    /// `oid` is the sid.
+    Audio,
+    /// `oid` is the dynamic id.
+    Dynamic,
+    Other(u32),
+}
+
+impl ReplyType {
+    pub fn code(&self) -> u32 {
+        match self {
+            ReplyType::Video => 1,
+            ReplyType::DynamicDraw => 11,
+            ReplyType::Article => 12,
+            ReplyType::Audio => 14,
+            ReplyType::Dynamic => 17,
+            ReplyType::Other(code) => *code,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Order of comments.
+pub enum ReplySort {
+    Time,
+    Like,
+    Reply,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of comments.
+pub struct ReplyPage {
+    pub page: PageInfo,
+    #[serde(default)]
+    pub replies: Option<Vec<Reply>>,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// Pagination of comments.
+pub struct PageInfo {
+    pub num: u64,
+    pub size: u64,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A comment.
+pub struct Reply {
+    pub rpid: u64,
+    pub oid: u64,
+    pub r#type: u32,
+    pub mid: u64,
+    /// Id of the root comment, `0` if this is a root comment.
+    pub root: u64,
+    /// Id of the replied comment, `0` if this is a root comment.
+    pub parent: u64,
+    /// Number of sub comments.
+    #[serde(default)]
+    pub rcount: u64,
+    pub like: u64,
+    pub ctime: i64,
+    pub member: Member,
+    pub content: ReplyContent,
+    /// A few sub comments attached to root comments.
+    #[serde(default)]
+    pub replies: Option<Vec<Reply>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Author of a comment.
+pub struct Member {
+    #[serde(with = "crate::de::string_or_number")]
+    pub mid: u64,
+    pub uname: String,
+    pub avatar: String,
+    #[serde(default)]
+    pub level_info: MemberLevel,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+/// Level of the author.
+pub struct MemberLevel {
+    pub current_level: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Text of a comment.
+pub struct ReplyContent {
+    pub message: String,
+    /// Emotes used in the message keyed by their text, e.g. `[doge]`.
+    #[serde(default)]
+    pub emote: HashMap<String, Emote>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// An emote.
+pub struct Emote {
+    pub id: u64,
+    pub text: String,
+    pub url: String,
+}
+
+impl ReplyContent {
+    /// Byte ranges of emotes in the message, in order.
+    pub fn emote_spans(&self) -> Vec<(Range<usize>, &Emote)> {
+        let mut spans: Vec<_> = self
+            .emote
+            .iter()
+            .flat_map(|(text, emote)| {
+                self.message
+                    .match_indices(text.as_str())
+                    .map(move |(start, _)| (start..start + text.len(), emote))
+            })
+            .collect();
+        spans.sort_by_key(|(range, _)| range.start);
+        spans
+    }
+}
+
+/// Get a page, starting from `1`, of root comments.
+pub async fn get_replies(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    page: u64,
+    sort: ReplySort,
+) -> Result<ReplyPage> {
+    let sort = match sort {
+        ReplySort::Time => 0,
+        ReplySort::Like => 1,
+        ReplySort::Reply => 2,
+    };
+    client
+        .get(
+            consts::REPLY,
+            &[
+                ("oid", oid),
+                ("type", reply_type.code() as u64),
+                ("pn", page),
+                ("ps", 20),
+                ("sort", sort),
+            ],
+        )
+        .await
+}
+
+/// Get a page, starting from `1`, of comments under the root comment.
+pub async fn get_sub_replies(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    root: u64,
+    page: u64,
+) -> Result<ReplyPage> {
+    client
+        .get(
+            consts::SUB_REPLY,
+            &[
+                ("oid", oid),
+                ("type", reply_type.code() as u64),
+                ("root", root),
+                ("pn", page),
+                ("ps", 20),
+            ],
+        )
+        .await
+}
+
+impl ReplyPage {
+    /// Whether pages follow this one.
+    fn has_more(&self) -> bool {
+        let fetched = self.page.num * self.page.size;
+        self.replies.as_ref().is_some_and(|r| !r.is_empty()) && fetched < self.page.count
+    }
+}
+
+/// Walk the root comments page by page, see [`get_replies`].
+pub fn paginate_replies(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    sort: ReplySort,
+) -> Paginated<Reply> {
+    let client = client.clone();
+    Paginated::by_page(move |page| {
+        let client = client.clone();
+        async move {
+            let replies = get_replies(&client, oid, reply_type, page, sort).await?;
+            let more = replies.has_more();
+            Ok((replies.replies.unwrap_or_default(), more))
+        }
+    })
+}
+
+/// Walk the comments under the root comment page by page, see [`get_sub_replies`].
+pub fn paginate_sub_replies(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    root: u64,
+) -> Paginated<Reply> {
+    let client = client.clone();
+    Paginated::by_page(move |page| {
+        let client = client.clone();
+        async move {
+            let replies = get_sub_replies(&client, oid, reply_type, root, page).await?;
+            let more = replies.has_more();
+            Ok((replies.replies.unwrap_or_default(), more))
+        }
+    })
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Result of posting a comment.
+pub struct AddReplyResult {
+    pub rpid: u64,
+    /// The posted comment.
+    #[serde(default)]
+    pub reply: Option<Reply>,
+}
+
+/// Post a comment, as a sub comment of `parent` if given.
+pub async fn add_reply(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    message: &str,
+    parent: Option<&Reply>,
+) -> Result<AddReplyResult> {
+    let mut form = vec![
+        ("oid", oid.to_string()),
+        ("type", reply_type.code().to_string()),
+        ("message", message.to_string()),
+        ("plat", "1".to_string()),
+        ("csrf", client.csrf()?),
+    ];
+    if let Some(parent) = parent {
+        let root = if parent.root == 0 {
+            parent.rpid
+        } else {
+            parent.root
+        };
+        form.push(("root", root.to_string()));
+        form.push(("parent", parent.rpid.to_string()));
+    }
+    client.post_form(consts::ADD_REPLY, &form).await
+}
+
+/// Like a comment, or cancel the like if `like` is `false`.
+pub async fn like_reply(
+    client: &BiliClient,
+    oid: u64,
+    reply_type: ReplyType,
+    rpid: u64,
+    like: bool,
+) -> Result<()> {
+    client
+        .post_action(
+            consts::LIKE_REPLY,
+            &[
+                ("oid", oid.to_string()),
+                ("type", reply_type.code().to_string()),
+                ("rpid", rpid.to_string()),
+                ("action", (like as u8).to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emote_spans() {
+        let content: ReplyContent = serde_json::from_value(serde_json::json!({
+            "message": "[doge]哈哈[笑哭][doge]",
+            "emote": {
+                "[doge]": {"id": 1, "text": "[doge]", "url": "doge.png"},
+                "[笑哭]": {"id": 2, "text": "[笑哭]", "url": "xiaoku.png"},
+            }
+        }))
+        .unwrap();
+        let spans: Vec<_> = content
+            .emote_spans()
+            .into_iter()
+            .map(|(range, emote)| (range, emote.id))
+            .collect();
+        assert_eq!(spans, vec![(0..6, 1), (12..20, 2), (20..26, 1)]);
+    }
+}
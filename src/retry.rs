@@ -0,0 +1,155 @@
+use std::future::Future;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::Response;
+use tokio::time::Duration;
+
+use crate::error::Error;
+use crate::Result;
+
+#[derive(Copy, Clone, Debug)]
+/// When and how a [`BiliClient`](crate::BiliClient) retries failed requests.
+pub struct RetryPolicy {
+    /// Retries after the first attempt, `0` to disable retrying.
+    pub max_retries: usize,
+    /// Wait before the first retry, doubled on each following one,
+    /// unless the server asks for a wait with `Retry-After`.
+    pub backoff: Duration,
+    /// Longest wait before a retry, also bounding what `Retry-After` asks for.
+    pub max_delay: Duration,
+    /// Whether the error is worth a retry.
+    pub retry_on: fn(&Error) -> bool,
+    /// Also retry POST requests, which may perform an action twice.
+    pub retry_unsafe: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            backoff: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retry_on: RetryPolicy::is_transient,
+            retry_unsafe: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Timeouts, connection failures, HTTP 429 and 5xx, and api codes `-500`, `-503` and `-504`.
+    ///
+    /// Risk control (`-412`) is not transient, retrying only makes it worse.
+    pub fn is_transient(error: &Error) -> bool {
+        match error {
+            Error::Reqwest(e) => e.is_timeout() || e.is_connect(),
+            Error::Status { status, .. } => *status == 429 || *status >= 500,
+            Error::Api { code, .. } => code.is_server_error(),
+            _ => false,
+        }
+    }
+
+    /// Wait before the `attempt`th retry, starting from 1.
+    fn delay(&self, attempt: usize, error: &Error) -> Duration {
+        let delay = match error {
+            Error::Status {
+                retry_after: Some(retry_after),
+                ..
+            } => *retry_after,
+            _ => self
+                .backoff
+                .saturating_mul(2u32.saturating_pow(attempt as u32 - 1)),
+        };
+        delay.min(self.max_delay)
+    }
+
+    /// Run `send` until it succeeds, the error is not retriable or retries run out.
+    ///
+    /// `safe` requests, e.g. GET, can always be retried; others only with `retry_unsafe`.
+    pub(crate) async fn run<T, F, Fut>(&self, safe: bool, mut send: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let max_retries = if safe || self.retry_unsafe {
+            self.max_retries
+        } else {
+            0
+        };
+        let mut attempt = 0;
+        loop {
+            match send().await {
+                Err(e) if attempt < max_retries && (self.retry_on)(&e) => {
+                    attempt += 1;
+                    let delay = self.delay(attempt, &e);
+                    warn!("request failed, retry {} in {:?}: {:?}", attempt, delay, e);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Turn an unsuccessful HTTP status into [`Error::Status`], keeping `Retry-After` in seconds.
+pub(crate) fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs);
+    Err(Error::Status {
+        status: status.as_u16(),
+        retry_after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorCode;
+
+    #[test]
+    fn test_transient() {
+        let api = |code: ErrorCode| Error::Api {
+            code,
+            raw: code.code(),
+            message: String::new(),
+        };
+        assert!(RetryPolicy::is_transient(&api(ErrorCode::ServiceTimeout)));
+        assert!(!RetryPolicy::is_transient(&api(ErrorCode::RateLimited)));
+        let status = Error::Status {
+            status: 503,
+            retry_after: Some(Duration::from_secs(7)),
+        };
+        assert!(RetryPolicy::is_transient(&status));
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1, &status), Duration::from_secs(7));
+        assert_eq!(
+            policy.delay(3, &api(ErrorCode::Overloaded)),
+            Duration::from_secs(2)
+        );
+        // neither a server nor a long backoff may hold a request for hours
+        let forever = Error::Status {
+            status: 429,
+            retry_after: Some(Duration::from_secs(86400)),
+        };
+        assert_eq!(policy.delay(1, &forever), policy.max_delay);
+        assert_eq!(
+            policy.delay(40, &api(ErrorCode::Overloaded)),
+            policy.max_delay
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub const SEARCH_ALL: &str = "https://api.bilibili.com/x/web-interface/wbi/search/all/v2";
+pub const SEARCH_TYPE: &str = "https://api.bilibili.com/x/web-interface/wbi/search/type";
@@ -0,0 +1,195 @@
+//! Search APIs.
+//!
+//! Bilibili rejects search without `buvid3`, see [`BiliClient::init_fingerprint`].
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+pub mod consts;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Results of all types, grouped by type.
+pub struct SearchAll {
+    #[serde(default)]
+    pub seid: String,
+    #[serde(rename = "numResults", default)]
+    pub num_results: u64,
+    pub result: Vec<SearchGroup>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Results of one type, e.g. `video`.
+pub struct SearchGroup {
+    pub result_type: String,
+    #[serde(default)]
+    pub data: Vec<Value>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Type of results to search for.
+pub enum SearchType {
+    Video,
+    LiveRoom,
+    User,
+    Bangumi,
+}
+
+impl SearchType {
+    /// Value of the `search_type` parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchType::Video => "video",
+            SearchType::LiveRoom => "live_room",
+            SearchType::User => "bili_user",
+            SearchType::Bangumi => "media_bangumi",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of results of one type.
+pub struct SearchPage {
+    pub page: u64,
+    #[serde(rename = "numResults", default)]
+    pub num_results: u64,
+    #[serde(rename = "numPages", default)]
+    pub num_pages: u64,
+    pub result: Vec<SearchItem>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A search result.
+pub enum SearchItem {
+    Video(VideoResult),
+    LiveRoom(LiveRoomResult),
+    User(UserResult),
+    Bangumi(BangumiResult),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A video in search results, `title` contains `<em class="keyword">` highlights.
+pub struct VideoResult {
+    #[serde(rename = "id")]
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub author: String,
+    pub mid: u64,
+    pub play: u64,
+    /// Duration like `12:34`.
+    pub duration: String,
+    pub pubdate: i64,
+    pub pic: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tag: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A living room in search results.
+pub struct LiveRoomResult {
+    pub roomid: u64,
+    pub uid: u64,
+    pub uname: String,
+    pub title: String,
+    pub online: u64,
+    pub live_status: u8,
+    #[serde(default)]
+    pub user_cover: String,
+    #[serde(default)]
+    pub cate_name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A user in search results.
+pub struct UserResult {
+    pub mid: u64,
+    pub uname: String,
+    #[serde(default)]
+    pub usign: String,
+    pub fans: u64,
+    pub videos: u64,
+    pub upic: String,
+    pub level: u8,
+    #[serde(default)]
+    pub room_id: u64,
+    #[serde(default)]
+    pub is_live: u8,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A bangumi in search results.
+pub struct BangumiResult {
+    pub season_id: u64,
+    pub media_id: u64,
+    pub title: String,
+    pub cover: String,
+    #[serde(default)]
+    pub areas: String,
+    #[serde(default)]
+    pub styles: String,
+    #[serde(default)]
+    pub pubtime: i64,
+    #[serde(default)]
+    pub ep_size: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct RawSearchPage {
+    page: u64,
+    #[serde(rename = "numResults", default)]
+    num_results: u64,
+    #[serde(rename = "numPages", default)]
+    num_pages: u64,
+    #[serde(default)]
+    result: Vec<Value>,
+}
+
+/// Search videos, users, bangumi etc. at once, only the first page of each type.
+pub async fn search_all(client: &BiliClient, keyword: &str) -> Result<SearchAll> {
+    client
+        .get_wbi(consts::SEARCH_ALL, &[("keyword", keyword.to_string())])
+        .await
+}
+
+/// Get a page, starting from `1`, of search results of one type.
+pub async fn search_by_type(
+    client: &BiliClient,
+    keyword: &str,
+    search_type: SearchType,
+    page: u64,
+) -> Result<SearchPage> {
+    let raw: RawSearchPage = client
+        .get_wbi(
+            consts::SEARCH_TYPE,
+            &[
+                ("keyword", keyword.to_string()),
+                ("search_type", search_type.as_str().to_string()),
+                ("page", page.to_string()),
+            ],
+        )
+        .await?;
+    let result = raw
+        .result
+        .into_iter()
+        .map(|item| {
+            let body = item.to_string();
+            match search_type {
+                SearchType::Video => serde_json::from_value(item).map(SearchItem::Video),
+                SearchType::LiveRoom => serde_json::from_value(item).map(SearchItem::LiveRoom),
+                SearchType::User => serde_json::from_value(item).map(SearchItem::User),
+                SearchType::Bangumi => serde_json::from_value(item).map(SearchItem::Bangumi),
+            }
+            .map_err(|e| Error::decode(consts::SEARCH_TYPE, &body, e))
+        })
+        .collect::<Result<_>>()?;
+    Ok(SearchPage {
+        page: raw.page,
+        num_results: raw.num_results,
+        num_pages: raw.num_pages,
+        result,
+    })
+}
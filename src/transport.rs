@@ -0,0 +1,196 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::BoxFuture;
+use reqwest::header::RANGE;
+use reqwest::{Request, Response, ResponseBuilderExt, Url};
+
+use crate::Result;
+
+/// Responses keyed by the url without query.
+type Routes = HashMap<String, Route>;
+
+#[derive(Debug, Default)]
+struct Route {
+    /// Served in turn before `always`.
+    once: VecDeque<MockResponse>,
+    always: Option<MockResponse>,
+}
+
+#[derive(Clone, Debug)]
+struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Whether range requests are answered with parts of the body, telling the total size in
+    /// `Content-Range` if `true`.
+    ranged: Option<bool>,
+}
+
+impl MockResponse {
+    fn new(status: u16, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+            ranged: None,
+        }
+    }
+
+    /// Answer the `Range` header, e.g. `bytes=0-99`.
+    fn range(mut self, range: Option<&str>) -> Self {
+        let (Some(known_total), Some(range)) = (self.ranged, range) else {
+            return self;
+        };
+        let len = self.body.len() as u64;
+        let (start, end) = range
+            .strip_prefix("bytes=")
+            .and_then(|range| range.split_once('-'))
+            .map(|(start, end)| (start.parse().unwrap_or(0), end.parse().unwrap_or(u64::MAX)))
+            .unwrap_or((0, u64::MAX));
+        if start >= len {
+            return Self::new(416, Vec::new());
+        }
+        let end = end.min(len - 1);
+        let total = if known_total {
+            len.to_string()
+        } else {
+            "*".to_string()
+        };
+        self.status = 206;
+        self.headers.push((
+            "content-range".to_string(),
+            format!("bytes {}-{}/{}", start, end, total),
+        ));
+        self.body = self.body[start as usize..=end as usize].to_vec();
+        self
+    }
+}
+
+/// Sends the HTTP requests of a [`BiliClient`](crate::BiliClient), `reqwest` by default.
+///
+/// Replace it, e.g. with a [`MockTransport`], to serve canned responses in tests.
+pub trait ApiTransport: Send + Sync {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>>;
+}
+
+impl ApiTransport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move { Ok(reqwest::Client::execute(self, request).await?) })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// Transport answering from canned responses, keyed by the url without query.
+///
+/// Unknown urls get `404 Not Found`.
+pub struct MockTransport {
+    routes: Arc<Mutex<Routes>>,
+    requests: Arc<Mutex<Vec<Url>>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer requests to `url` with `body` as json.
+    pub fn json(self, url: &str, body: serde_json::Value) -> Self {
+        self.respond(url, 200, body.to_string().into_bytes())
+    }
+
+    /// Answer requests to `url` with `status` and raw `body`.
+    pub fn respond(self, url: &str, status: u16, body: Vec<u8>) -> Self {
+        self.route(url, MockResponse::new(status, body))
+    }
+
+    /// Answer the next request to `url` with `status` and raw `body`, before the responses
+    /// set by the other methods, e.g. to fail once.
+    pub fn respond_once(self, url: &str, status: u16, body: Vec<u8>) -> Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_default()
+            .once
+            .push_back(MockResponse::new(status, body));
+        self
+    }
+
+    /// Answer requests to `url` with `302 Found` to `location`.
+    pub fn redirect(self, url: &str, location: &str) -> Self {
+        let mut response = MockResponse::new(302, Vec::new());
+        response
+            .headers
+            .push(("location".to_string(), location.to_string()));
+        self.route(url, response)
+    }
+
+    /// Answer range requests to `url` with `206 Partial Content` of `body`, the total size
+    /// in `Content-Range` being `*` unless `known_total`.
+    pub fn ranged(self, url: &str, body: Vec<u8>, known_total: bool) -> Self {
+        let mut response = MockResponse::new(200, body);
+        response.ranged = Some(known_total);
+        self.route(url, response)
+    }
+
+    fn route(self, url: &str, response: MockResponse) -> Self {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_default()
+            .always = Some(response);
+        self
+    }
+
+    /// Urls requested so far, with query.
+    pub fn requests(&self) -> Vec<Url> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl ApiTransport for MockTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        let url = request.url().clone();
+        self.requests.lock().unwrap().push(url.clone());
+        let mut route = url.clone();
+        route.set_query(None);
+        route.set_fragment(None);
+        let response = self
+            .routes
+            .lock()
+            .unwrap()
+            .get_mut(route.as_str())
+            .and_then(|route| route.once.pop_front().or_else(|| route.always.clone()))
+            .unwrap_or_else(|| MockResponse::new(404, Vec::new()));
+        let range = request
+            .headers()
+            .get(RANGE)
+            .and_then(|range| range.to_str().ok());
+        let MockResponse {
+            status,
+            headers,
+            body,
+            ..
+        } = response.range(range);
+        let mut response = http::Response::builder()
+            .url(url)
+            .status(status)
+            .header("content-type", "application/json");
+        for (name, value) in headers {
+            response = response.header(name, value);
+        }
+        let response = response.body(body).expect("invalid mock response");
+        Box::pin(async move { Ok(Response::from(response)) })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Transport(pub(crate) Arc<dyn ApiTransport>);
+
+impl std::fmt::Debug for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Transport")
+    }
+}
@@ -0,0 +1,3 @@
+pub const PREUPLOAD: &str = "https://member.bilibili.com/preupload";
+pub const SUBMIT: &str = "https://member.bilibili.com/x/vu/web/add/v3";
+pub const COVER_UP: &str = "https://member.bilibili.com/x/vu/web/cover/up";
@@ -0,0 +1,50 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Deserialize)]
+struct CoverUp {
+    url: String,
+}
+
+/// Guess the mime type of an image from its magic bytes, jpeg by default.
+fn image_mime(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"\x89PNG") {
+        "image/png"
+    } else if bytes.starts_with(b"GIF8") {
+        "image/gif"
+    } else if bytes.len() > 12 && &bytes[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
+/// Upload a cover image, returning its hosted url.
+pub async fn upload_cover(client: &BiliClient, bytes: &[u8]) -> Result<String> {
+    let cover = format!(
+        "data:{};base64,{}",
+        image_mime(bytes),
+        STANDARD.encode(bytes)
+    );
+    let csrf = client.csrf()?;
+    let response: CoverUp = client
+        .post_form(consts::COVER_UP, &[("cover", cover), ("csrf", csrf)])
+        .await?;
+    Ok(response.url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_mime() {
+        assert_eq!(image_mime(b"\x89PNG\r\n\x1a\n"), "image/png");
+        assert_eq!(image_mime(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(image_mime(b"\xff\xd8\xff"), "image/jpeg");
+    }
+}
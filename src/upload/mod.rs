@@ -0,0 +1,109 @@
+//! Video submission APIs.
+use serde::{Deserialize, Serialize};
+
+use crate::{BiliClient, Result};
+
+pub mod consts;
+mod cover;
+mod upos;
+
+pub use cover::upload_cover;
+pub use upos::{upload_video, UploadedVideo};
+
+#[derive(Clone, Debug)]
+/// A video to submit, made of parts uploaded by [`upload_video`].
+pub struct SubmitRequest {
+    pub title: String,
+    /// Partition id, e.g. `171` for single-player games.
+    pub tid: u32,
+    pub tags: Vec<String>,
+    pub desc: String,
+    /// Url of the cover, see [`upload_cover`].
+    pub cover: String,
+    /// Url of the original work for reposts, `None` for self-made.
+    pub source: Option<String>,
+    /// Text of the dynamic posted along.
+    pub dynamic: String,
+    /// Parts with their titles.
+    pub videos: Vec<(UploadedVideo, String)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Result of submitting a video, which is under review then.
+pub struct SubmitResult {
+    pub aid: u64,
+    pub bvid: String,
+}
+
+impl SubmitRequest {
+    pub fn new(title: &str, tid: u32) -> Self {
+        Self {
+            title: title.to_string(),
+            tid,
+            tags: Vec::new(),
+            desc: String::new(),
+            cover: String::new(),
+            source: None,
+            dynamic: String::new(),
+            videos: Vec::new(),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        let videos = self
+            .videos
+            .iter()
+            .map(|(video, title)| {
+                serde_json::json!({
+                    "filename": video.filename,
+                    "title": title,
+                    "desc": "",
+                    "cid": video.biz_id,
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({
+            // 1 self-made, 2 repost
+            "copyright": if self.source.is_some() { 2 } else { 1 },
+            "source": self.source.as_deref().unwrap_or_default(),
+            "tid": self.tid,
+            "cover": self.cover,
+            "title": self.title,
+            "tag": self.tags.join(","),
+            "desc": self.desc,
+            "dynamic": self.dynamic,
+            "videos": videos,
+            "no_reprint": 1,
+        })
+    }
+}
+
+/// Submit a video with its uploaded parts.
+pub async fn submit(client: &BiliClient, request: &SubmitRequest) -> Result<SubmitResult> {
+    let csrf = client.csrf()?;
+    client
+        .post_json(consts::SUBMIT, &[("csrf", csrf)], &request.to_json())
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submit_json() {
+        let mut request = SubmitRequest::new("title", 171);
+        request.tags = vec!["a".to_string(), "b".to_string()];
+        request.videos.push((
+            UploadedVideo {
+                filename: "n221215abc".to_string(),
+                biz_id: 123,
+            },
+            "P1".to_string(),
+        ));
+        let json = request.to_json();
+        assert_eq!(json["copyright"], 1);
+        assert_eq!(json["tag"], "a,b");
+        assert_eq!(json["videos"][0]["cid"], 123);
+    }
+}
@@ -0,0 +1,264 @@
+use std::io::SeekFrom;
+
+use futures_util::{stream, TryStreamExt};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use tokio::time::Duration;
+
+use super::consts;
+use crate::client::json;
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+const MAX_RETRIES: usize = 3;
+
+#[derive(Clone, Debug, Deserialize)]
+/// Upload target assigned by preupload.
+struct Preupload {
+    #[serde(rename = "OK")]
+    ok: i32,
+    auth: String,
+    biz_id: u64,
+    chunk_size: usize,
+    /// e.g. `//upos-cs-upcdnbda2.bilivideo.com`.
+    endpoint: String,
+    threads: usize,
+    /// e.g. `upos://ugcboss/n221215xxx.mp4`.
+    upos_uri: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct UploadInit {
+    upload_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A video file uploaded by [`upload_video`], to reference in a submission.
+pub struct UploadedVideo {
+    /// Name of the file on upos without extension.
+    pub filename: String,
+    /// Becomes the cid of the part.
+    pub biz_id: u64,
+}
+
+impl Preupload {
+    fn url(&self) -> String {
+        format!(
+            "https:{}/{}",
+            self.endpoint,
+            self.upos_uri.trim_start_matches("upos://")
+        )
+    }
+
+    fn filename(&self) -> String {
+        let name = self.upos_uri.rsplit('/').next().unwrap_or_default();
+        name.split('.').next().unwrap_or_default().to_string()
+    }
+}
+
+/// Read up to `len` bytes, fewer only at the end.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(len);
+    reader.take(len as u64).read_to_end(&mut chunk).await?;
+    Ok(chunk)
+}
+
+/// Upload a video file with upos, in parallel chunks which are retried on failure.
+///
+/// The whole of `reader` is uploaded from its start, chunks are read as they are sent, so
+/// only as many as uploaded at once are held in memory.
+pub async fn upload_video<R>(
+    client: &BiliClient,
+    name: &str,
+    mut reader: R,
+) -> Result<UploadedVideo>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    let size = reader.seek(SeekFrom::End(0)).await?;
+    reader.seek(SeekFrom::Start(0)).await?;
+    debug!("preupload {} ({} bytes)", name, size);
+    let request = client.request(Method::GET, consts::PREUPLOAD).query(&[
+        ("name", name),
+        ("size", &size.to_string()),
+        ("r", "upos"),
+        ("profile", "ugcupos/bup"),
+        ("ssl", "0"),
+        ("version", "2.14.0"),
+        ("build", "2140000"),
+    ]);
+    let pre: Preupload = json(client.send(request).await?).await?;
+    if pre.ok != 1 {
+        return Err(Error::UnexpectedResponse("preupload rejected".to_string()));
+    }
+    let url = pre.url();
+    let request = client
+        .request(Method::POST, &url)
+        .query(&[("uploads", ""), ("output", "json")])
+        .header("X-Upos-Auth", &pre.auth);
+    let init: UploadInit = json(client.send(request).await?.error_for_status()?).await?;
+
+    let chunk_size = pre.chunk_size.max(1);
+    let count = size.div_ceil(chunk_size as u64);
+    let chunks = stream::try_unfold((&mut reader, 0usize), |(reader, index)| async move {
+        let chunk = read_chunk(reader, chunk_size).await?;
+        if chunk.is_empty() {
+            return Ok::<_, Error>(None);
+        }
+        Ok(Some(((index, chunk), (reader, index + 1))))
+    });
+    let mut parts = chunks
+        .map_ok(|(index, chunk)| {
+            let start = index * chunk_size;
+            let query = [
+                ("partNumber", (index + 1).to_string()),
+                ("uploadId", init.upload_id.clone()),
+                ("chunk", index.to_string()),
+                ("chunks", count.to_string()),
+                ("size", chunk.len().to_string()),
+                ("start", start.to_string()),
+                ("end", (start + chunk.len()).to_string()),
+                ("total", size.to_string()),
+            ];
+            let (url, auth) = (&url, &pre.auth);
+            async move {
+                let mut attempt = 0;
+                loop {
+                    let request = client
+                        .request(Method::PUT, url)
+                        .query(&query)
+                        .header("X-Upos-Auth", auth)
+                        .body(chunk.clone());
+                    let result = match client.send(request).await {
+                        Ok(response) => response.error_for_status().map_err(Error::from),
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(_) => return Ok(index + 1),
+                        Err(e) if attempt < MAX_RETRIES => {
+                            attempt += 1;
+                            warn!("chunk {} failed, retry {}: {:?}", index, attempt, e);
+                            tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        })
+        .try_buffer_unordered(pre.threads.max(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+    parts.sort_unstable();
+
+    let parts = parts
+        .into_iter()
+        .map(|part| json!({"partNumber": part, "eTag": "etag"}))
+        .collect::<Vec<_>>();
+    let request = client
+        .request(Method::POST, &url)
+        .query(&[
+            ("output", "json".to_string()),
+            ("name", name.to_string()),
+            ("profile", "ugcupos/bup".to_string()),
+            ("uploadId", init.upload_id.clone()),
+            ("biz_id", pre.biz_id.to_string()),
+        ])
+        .header("X-Upos-Auth", &pre.auth)
+        .json(&json!({ "parts": parts }));
+    let done: serde_json::Value = json(client.send(request).await?.error_for_status()?).await?;
+    if done["OK"].as_i64() != Some(1) {
+        return Err(Error::UnexpectedResponse(done.to_string()));
+    }
+    Ok(UploadedVideo {
+        filename: pre.filename(),
+        biz_id: pre.biz_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    #[test]
+    fn test_upos_url() {
+        let pre: Preupload = serde_json::from_value(json!({
+            "OK": 1,
+            "auth": "auth",
+            "biz_id": 123,
+            "chunk_size": 10485760,
+            "endpoint": "//upos-cs-upcdnbda2.bilivideo.com",
+            "threads": 3,
+            "upos_uri": "upos://ugcboss/n221215abc.mp4",
+        }))
+        .unwrap();
+        assert_eq!(
+            pre.url(),
+            "https://upos-cs-upcdnbda2.bilivideo.com/ugcboss/n221215abc.mp4"
+        );
+        assert_eq!(pre.filename(), "n221215abc");
+    }
+
+    #[tokio::test]
+    async fn test_upload_video() {
+        let upos = "https://upos-cs-upcdnbda2.bilivideo.com/ugcboss/n221215abc.mp4";
+        let transport = MockTransport::new()
+            .json(
+                consts::PREUPLOAD,
+                json!({
+                    "OK": 1,
+                    "auth": "auth",
+                    "biz_id": 123,
+                    "chunk_size": 4,
+                    "endpoint": "//upos-cs-upcdnbda2.bilivideo.com",
+                    "threads": 1,
+                    "upos_uri": "upos://ugcboss/n221215abc.mp4",
+                }),
+            )
+            // init, then the first chunk fails once
+            .respond_once(upos, 200, br#"{"upload_id":"upload"}"#.to_vec())
+            .respond_once(upos, 500, Vec::new())
+            .json(upos, json!({"OK": 1}));
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let video = upload_video(
+            &client,
+            "a.mp4",
+            std::io::Cursor::new(b"0123456789".to_vec()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            video,
+            UploadedVideo {
+                filename: "n221215abc".to_string(),
+                biz_id: 123,
+            }
+        );
+
+        let requests = transport.requests();
+        let query = |index: usize, key: &str| {
+            requests[index]
+                .query_pairs()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.into_owned())
+        };
+        assert_eq!(requests.len(), 7);
+        assert_eq!(query(0, "size").as_deref(), Some("10"));
+        assert_eq!(query(1, "uploads").as_deref(), Some(""));
+        let parts: Vec<_> = (2..6)
+            .map(|i| (query(i, "partNumber").unwrap(), query(i, "size").unwrap()))
+            .collect();
+        assert_eq!(
+            parts,
+            [("1", "4"), ("1", "4"), ("2", "4"), ("3", "2")]
+                .map(|(part, size)| (part.to_string(), size.to_string()))
+        );
+        assert_eq!(query(5, "end").as_deref(), Some("10"));
+        assert_eq!(query(6, "biz_id").as_deref(), Some("123"));
+    }
+}
@@ -0,0 +1,7 @@
+pub const MY_MEDALS: &str = "https://api.live.bilibili.com/xlive/app-ucenter/v1/user/GetMyMedals";
+pub const WEAR_MEDAL: &str = "https://api.live.bilibili.com/xlive/web-room/v1/fansMedal/wear";
+pub const TAKE_OFF_MEDAL: &str =
+    "https://api.live.bilibili.com/xlive/web-room/v1/fansMedal/take_off";
+pub const RELATION_MODIFY: &str = "https://api.bilibili.com/x/relation/modify";
+pub const RELATION_BATCH_MODIFY: &str = "https://api.bilibili.com/x/relation/batch/modify";
+pub const SPACE_VIDEOS: &str = "https://api.bilibili.com/x/space/wbi/arc/search";
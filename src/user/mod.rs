@@ -0,0 +1,100 @@
+//! APIs about the logged-in account and other users.
+use serde::{Deserialize, Serialize};
+
+use crate::live::{FanMedal, GuardLevel};
+use crate::{BiliClient, Result};
+
+pub mod consts;
+mod relation;
+mod video;
+
+pub use relation::{batch_follow, modify_relation, BatchFollow, RelationAction};
+pub use video::{
+    get_videos, paginate_videos, video_stream, SpaceVideo, SpaceVideoList, VideoOrder,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A page of fan medals of the account.
+pub struct MedalList {
+    pub items: Vec<Medal>,
+    pub page_info: MedalPageInfo,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+/// Pagination of the medal list.
+pub struct MedalPageInfo {
+    pub cur_page: u64,
+    pub total_page: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A fan medal owned by the account.
+pub struct Medal {
+    pub medal_id: u64,
+    pub medal_name: String,
+    pub level: u32,
+    pub intimacy: u64,
+    pub next_intimacy: u64,
+    #[serde(default)]
+    pub today_feed: u64,
+    #[serde(default)]
+    pub day_limit: u64,
+    /// Uid of the streamer.
+    pub target_id: u64,
+    #[serde(default)]
+    pub target_name: String,
+    #[serde(default)]
+    pub roomid: u64,
+    #[serde(default)]
+    pub is_lighted: u8,
+    #[serde(default)]
+    pub guard_level: GuardLevel,
+}
+
+impl Medal {
+    /// The medal in the shape shared by live APIs and events.
+    pub fn fan_medal(&self) -> FanMedal {
+        FanMedal {
+            level: self.level,
+            name: self.medal_name.clone(),
+            target_id: self.target_id,
+            anchor_uname: self.target_name.clone(),
+            room_id: self.roomid,
+            guard_level: self.guard_level,
+            is_lighted: self.is_lighted == 1,
+        }
+    }
+}
+
+/// Get a page, starting from `1`, of fan medals of the account.
+pub async fn get_medals(client: &BiliClient, page: u64) -> Result<MedalList> {
+    client
+        .get(consts::MY_MEDALS, &[("page", page), ("page_size", 10)])
+        .await
+}
+
+/// Wear a fan medal, which is shown alongside danmaku sent afterwards.
+pub async fn wear_medal(client: &BiliClient, medal_id: u64) -> Result<()> {
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::WEAR_MEDAL,
+            &[
+                ("medal_id", medal_id.to_string()),
+                ("csrf_token", csrf.clone()),
+                ("csrf", csrf),
+            ],
+        )
+        .await
+}
+
+/// Take off the worn fan medal.
+pub async fn take_off_medal(client: &BiliClient) -> Result<()> {
+    let csrf = client.csrf()?;
+    client
+        .post_action(
+            consts::TAKE_OFF_MEDAL,
+            &[("csrf_token", csrf.clone()), ("csrf", csrf)],
+        )
+        .await
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Change of the relation to another user.
+pub enum RelationAction {
+    Follow,
+    Unfollow,
+    Block,
+    Unblock,
+    /// Make the user stop following the account.
+    RemoveFan,
+}
+
+impl RelationAction {
+    /// Value of the `act` parameter.
+    pub fn code(&self) -> u8 {
+        match self {
+            RelationAction::Follow => 1,
+            RelationAction::Unfollow => 2,
+            RelationAction::Block => 5,
+            RelationAction::Unblock => 6,
+            RelationAction::RemoveFan => 7,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// Result of [`batch_follow`].
+pub struct BatchFollow {
+    /// Uids which could not be followed, e.g. blocked ones.
+    #[serde(default)]
+    pub failed_fids: Vec<u64>,
+}
+
+/// Follow, unfollow, block or unblock the user `uid`.
+pub async fn modify_relation(client: &BiliClient, uid: u64, action: RelationAction) -> Result<()> {
+    client
+        .post_action(
+            consts::RELATION_MODIFY,
+            &[
+                ("fid", uid.to_string()),
+                ("act", action.code().to_string()),
+                ("re_src", "11".to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
+
+/// Follow all of `uids` at once.
+pub async fn batch_follow(client: &BiliClient, uids: &[u64]) -> Result<BatchFollow> {
+    let fids = uids.iter().map(u64::to_string).collect::<Vec<_>>();
+    client
+        .post_form(
+            consts::RELATION_BATCH_MODIFY,
+            &[
+                ("fids", fids.join(",")),
+                ("act", RelationAction::Follow.code().to_string()),
+                ("re_src", "11".to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
@@ -0,0 +1,179 @@
+use futures_util::stream::Stream;
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Paginated, Result};
+
+/// Videos per page, the most the api allows.
+const PAGE_SIZE: u64 = 50;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Order of the videos of a user.
+pub enum VideoOrder {
+    /// Newest first.
+    #[default]
+    Pubdate,
+    /// Most played first.
+    Click,
+    /// Most favorited first.
+    Stow,
+}
+
+impl VideoOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            VideoOrder::Pubdate => "pubdate",
+            VideoOrder::Click => "click",
+            VideoOrder::Stow => "stow",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+/// A video uploaded by a user.
+pub struct SpaceVideo {
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub description: String,
+    /// Url of the cover.
+    pub pic: String,
+    /// Unix timestamp in seconds.
+    pub created: i64,
+    /// e.g. `03:21`.
+    pub length: String,
+    /// Views, `0` if hidden.
+    #[serde(deserialize_with = "crate::de::number_or_hidden")]
+    pub play: u64,
+    pub comment: u64,
+    /// Danmaku count.
+    pub video_review: u64,
+    pub mid: u64,
+    pub author: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+/// A page of videos of a user.
+pub struct SpaceVideoList {
+    pub videos: Vec<SpaceVideo>,
+    /// Starting from `1`.
+    pub page: u64,
+    pub page_size: u64,
+    /// Videos of the user in total.
+    pub count: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SpaceSearch {
+    list: SpaceSearchList,
+    page: SpaceSearchPage,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct SpaceSearchList {
+    #[serde(deserialize_with = "crate::de::null_default")]
+    vlist: Vec<SpaceVideo>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SpaceSearchPage {
+    pn: u64,
+    ps: u64,
+    count: u64,
+}
+
+/// Get a page, starting from `1`, of videos uploaded by the user `mid`.
+pub async fn get_videos(
+    client: &BiliClient,
+    mid: u64,
+    page: u64,
+    order: VideoOrder,
+) -> Result<SpaceVideoList> {
+    let search: SpaceSearch = client
+        .get_wbi(
+            consts::SPACE_VIDEOS,
+            &[
+                ("mid", mid.to_string()),
+                ("pn", page.to_string()),
+                ("ps", PAGE_SIZE.to_string()),
+                ("order", order.as_str().to_string()),
+            ],
+        )
+        .await?;
+    Ok(SpaceVideoList {
+        videos: search.list.vlist,
+        page: search.page.pn,
+        page_size: search.page.ps,
+        count: search.page.count,
+    })
+}
+
+/// Walk the videos uploaded by the user `mid` page by page, see [`get_videos`].
+pub fn paginate_videos(client: &BiliClient, mid: u64, order: VideoOrder) -> Paginated<SpaceVideo> {
+    let client = client.clone();
+    Paginated::by_page(move |page| {
+        let client = client.clone();
+        async move {
+            let list = get_videos(&client, mid, page, order).await?;
+            let more = !list.videos.is_empty() && page * list.page_size < list.count;
+            Ok((list.videos, more))
+        }
+    })
+}
+
+/// Walk all videos uploaded by the user `mid`, requesting pages as they are consumed.
+///
+/// The stream ends after yielding an error.
+pub fn video_stream(
+    client: &BiliClient,
+    mid: u64,
+    order: VideoOrder,
+) -> impl Stream<Item = Result<SpaceVideo>> {
+    paginate_videos(client, mid, order).into_stream()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_video_stream() {
+        let transport = MockTransport::new()
+            .json(
+                crate::auth::consts::NAV,
+                json!({"code": -101, "data": {"isLogin": false, "wbi_img": {
+                    "img_url": "https://i0.hdslb.com/bfs/wbi/7cd084941338484aae1ad9425b84077c.png",
+                    "sub_url": "https://i0.hdslb.com/bfs/wbi/4932caff0ff746eab6f01bf08b70ac45.png",
+                }}}),
+            )
+            .json(
+                consts::SPACE_VIDEOS,
+                json!({"code": 0, "data": {
+                    "list": {"vlist": [
+                        {"aid": 170001, "bvid": "BV17x411w7KC", "title": "a", "play": 100},
+                        {"aid": 170002, "bvid": "BV1xx411c7X3", "title": "b", "play": "--"},
+                    ]},
+                    "page": {"pn": 1, "ps": 50, "count": 2},
+                }}),
+            );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let videos: Vec<_> = video_stream(&client, 2, VideoOrder::Pubdate)
+            .collect()
+            .await;
+        assert_eq!(videos.len(), 2);
+        let video = videos[1].as_ref().unwrap();
+        assert_eq!(video.title, "b");
+        assert_eq!(video.play, 0);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].query().unwrap().contains("w_rid="));
+    }
+}
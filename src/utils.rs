@@ -0,0 +1,202 @@
+//! Helpers for links shared by users, e.g. to a chat bot.
+use reqwest::header::LOCATION;
+use reqwest::{Method, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::video::aid_to_bvid;
+use crate::{BiliClient, Result};
+
+/// Hosts of short links.
+const SHORT_LINK_HOSTS: [&str; 2] = ["b23.tv", "bili2233.cn"];
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// What a link points to.
+pub enum BiliTarget {
+    /// A video, `av` ids are converted to bvid.
+    Video { bvid: String },
+    /// A living room, the id may be a short one.
+    LiveRoom { room_id: u64 },
+    /// A dynamic, or an opus which shares its id.
+    Dynamic { id: u64 },
+    /// The space of a user.
+    User { mid: u64 },
+}
+
+impl BiliTarget {
+    /// Parse a link, with or without scheme, or a bare `BV...`/`av...` video id.
+    ///
+    /// Short links need requests, see [`resolve_short_link`].
+    pub fn parse(link: &str) -> Option<Self> {
+        let link = link.trim();
+        if let Some(target) = video(link) {
+            return Some(target);
+        }
+        Self::from_url(&parse_url(link)?)
+    }
+
+    fn from_url(url: &Url) -> Option<Self> {
+        let host = url.host_str()?;
+        let segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+        match (host, segments.as_slice()) {
+            ("live.bilibili.com", ["h5", room_id, ..]) | ("live.bilibili.com", [room_id, ..]) => {
+                Some(BiliTarget::LiveRoom {
+                    room_id: room_id.parse().ok()?,
+                })
+            }
+            ("t.bilibili.com", [id, ..]) => Some(BiliTarget::Dynamic {
+                id: id.parse().ok()?,
+            }),
+            ("space.bilibili.com", [mid, ..]) | ("m.bilibili.com", ["space", mid, ..]) => {
+                Some(BiliTarget::User {
+                    mid: mid.parse().ok()?,
+                })
+            }
+            (host, ["video", id, ..]) if is_bilibili(host) => video(id),
+            (host, ["opus", id, ..]) | (host, ["dynamic", id, ..]) if is_bilibili(host) => {
+                Some(BiliTarget::Dynamic {
+                    id: id.parse().ok()?,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Whether `host` is `bilibili.com` or a subdomain of it.
+fn is_bilibili(host: &str) -> bool {
+    host == "bilibili.com" || host.ends_with(".bilibili.com")
+}
+
+/// Parse a link, assuming https without scheme.
+fn parse_url(link: &str) -> Option<Url> {
+    if link.contains("://") {
+        Url::parse(link).ok()
+    } else {
+        Url::parse(&format!("https://{}", link)).ok()
+    }
+}
+
+/// A video by `BV...` or `av...` id.
+fn video(id: &str) -> Option<BiliTarget> {
+    if !id.is_ascii() {
+        return None;
+    }
+    let bvid = if id.len() == 12 && id[..2].eq_ignore_ascii_case("BV") {
+        id.to_string()
+    } else if id.len() > 2 && id[..2].eq_ignore_ascii_case("av") {
+        aid_to_bvid(id[2..].parse().ok()?)
+    } else {
+        return None;
+    };
+    Some(BiliTarget::Video { bvid })
+}
+
+/// Follow a short link, e.g. `https://b23.tv/xxxxxxx`, to what it points to.
+///
+/// Links which are not short are parsed without requests, like [`BiliTarget::parse`].
+pub async fn resolve_short_link(client: &BiliClient, url: &str) -> Result<BiliTarget> {
+    let invalid = |url: &str| Error::UnexpectedResponse(format!("no target in link {}", url));
+    let mut parsed = parse_url(url.trim()).ok_or_else(|| invalid(url))?;
+    if parsed
+        .host_str()
+        .is_some_and(|host| SHORT_LINK_HOSTS.contains(&host))
+    {
+        debug!("GET {}", url);
+        let response = client.send(client.request(Method::GET, parsed)).await?;
+        // redirects are followed by reqwest, but not by every transport
+        parsed = match response.headers().get(LOCATION) {
+            Some(location) => location
+                .to_str()
+                .ok()
+                .and_then(|location| response.url().join(location).ok())
+                .ok_or_else(|| invalid(url))?,
+            None => response.url().clone(),
+        };
+    }
+    BiliTarget::from_url(&parsed).ok_or_else(|| invalid(parsed.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    #[test]
+    fn test_parse() {
+        let video = BiliTarget::Video {
+            bvid: "BV17x411w7KC".to_string(),
+        };
+        assert_eq!(BiliTarget::parse("BV17x411w7KC"), Some(video.clone()));
+        assert_eq!(BiliTarget::parse("av170001"), Some(video.clone()));
+        assert_eq!(
+            BiliTarget::parse("https://m.bilibili.com/video/BV17x411w7KC?p=2#reply"),
+            Some(video)
+        );
+        assert_eq!(
+            BiliTarget::parse("live.bilibili.com/1017?visit_id=x"),
+            Some(BiliTarget::LiveRoom { room_id: 1017 })
+        );
+        assert_eq!(
+            BiliTarget::parse("https://t.bilibili.com/1234567890"),
+            Some(BiliTarget::Dynamic { id: 1234567890 })
+        );
+        assert_eq!(
+            BiliTarget::parse("https://space.bilibili.com/2/video"),
+            Some(BiliTarget::User { mid: 2 })
+        );
+        assert_eq!(BiliTarget::parse("space.bilibili.com/name"), None);
+        assert_eq!(
+            BiliTarget::parse("https://notbilibili.com/video/BV17x411w7KC"),
+            None
+        );
+        assert_eq!(BiliTarget::parse("不是链接"), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_short_link() {
+        let client = BiliClient::new();
+        let target = resolve_short_link(
+            &client,
+            "https://www.bilibili.com/video/av170001/?share_source=copy_web",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            target,
+            BiliTarget::Video {
+                bvid: "BV17x411w7KC".to_string()
+            }
+        );
+        let target = resolve_short_link(&client, "https://live.bilibili.com/h5/1017")
+            .await
+            .unwrap();
+        assert_eq!(target, BiliTarget::LiveRoom { room_id: 1017 });
+        let target = resolve_short_link(&client, "https://www.bilibili.com/opus/1234567890")
+            .await
+            .unwrap();
+        assert_eq!(target, BiliTarget::Dynamic { id: 1234567890 });
+        assert!(
+            resolve_short_link(&client, "https://example.com/video/BV17x411w7KC")
+                .await
+                .is_err()
+        );
+
+        let transport = MockTransport::new().redirect(
+            "https://b23.tv/xxxxxxx",
+            "https://www.bilibili.com/video/BV17x411w7KC?share_source=copy_web",
+        );
+        let client = BiliClient::builder()
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let target = resolve_short_link(&client, "b23.tv/xxxxxxx").await.unwrap();
+        assert_eq!(
+            target,
+            BiliTarget::Video {
+                bvid: "BV17x411w7KC".to_string()
+            }
+        );
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
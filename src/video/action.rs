@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use super::{bvid_to_aid, consts};
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// What a [`triple`] did, each part may fail on its own, e.g. out of coins.
+pub struct Triple {
+    pub like: bool,
+    pub coin: bool,
+    pub fav: bool,
+    /// Coins given.
+    pub multiply: u8,
+}
+
+async fn set_like(client: &BiliClient, bvid: &str, like: bool) -> Result<()> {
+    client
+        .post_action(
+            consts::LIKE,
+            &[
+                ("bvid", bvid.to_string()),
+                ("like", if like { "1" } else { "2" }.to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
+
+/// Like a video.
+pub async fn like(client: &BiliClient, bvid: &str) -> Result<()> {
+    set_like(client, bvid, true).await
+}
+
+/// Take back the like of a video.
+pub async fn unlike(client: &BiliClient, bvid: &str) -> Result<()> {
+    set_like(client, bvid, false).await
+}
+
+/// Give `count` coins, `1` or `2`, to a video.
+pub async fn coin(client: &BiliClient, bvid: &str, count: u8) -> Result<()> {
+    client
+        .post_action(
+            consts::COIN,
+            &[
+                ("bvid", bvid.to_string()),
+                ("multiply", count.to_string()),
+                ("select_like", "0".to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
+
+/// Add a video into the favorite folders `fav_ids`.
+pub async fn favorite(client: &BiliClient, bvid: &str, fav_ids: &[u64]) -> Result<()> {
+    let aid =
+        bvid_to_aid(bvid).ok_or_else(|| Error::UnexpectedResponse(format!("bad bvid {}", bvid)))?;
+    crate::fav::deal(client, aid, fav_ids, &[]).await
+}
+
+/// Like, give two coins and favorite into the default folder at once.
+pub async fn triple(client: &BiliClient, bvid: &str) -> Result<Triple> {
+    client
+        .post_form(
+            consts::TRIPLE,
+            &[("bvid", bvid.to_string()), ("csrf", client.csrf()?)],
+        )
+        .await
+}
@@ -0,0 +1,427 @@
+//! Video APIs of the mobile app, see [`ClientBackend::App`](crate::ClientBackend::App).
+use prost::Message;
+
+use super::bvid::{aid_to_bvid, bvid_to_aid};
+use super::danmaku::{DmSegMobileReply, VideoDanmaku};
+use super::playurl::{Dash, DashStream, Durl, VideoPlayUrl};
+use super::stat::VideoStat;
+use super::view::{VideoOwner, VideoPage, VideoView};
+use crate::error::Error;
+use crate::grpc::{self, consts};
+use crate::{BiliClient, Result};
+
+#[derive(Clone, PartialEq, Message)]
+struct ViewReq {
+    #[prost(int64, tag = "1")]
+    aid: i64,
+    #[prost(string, tag = "2")]
+    bvid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ViewReply {
+    #[prost(message, optional, tag = "1")]
+    arc: Option<Arc>,
+    #[prost(message, repeated, tag = "2")]
+    pages: Vec<ViewPage>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Arc {
+    #[prost(int64, tag = "1")]
+    aid: i64,
+    #[prost(string, tag = "6")]
+    pic: String,
+    #[prost(string, tag = "7")]
+    title: String,
+    #[prost(int64, tag = "8")]
+    pubdate: i64,
+    #[prost(string, tag = "10")]
+    desc: String,
+    #[prost(int64, tag = "16")]
+    duration: i64,
+    #[prost(message, optional, tag = "22")]
+    author: Option<Author>,
+    #[prost(message, optional, tag = "23")]
+    stat: Option<Stat>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Author {
+    #[prost(int64, tag = "1")]
+    mid: i64,
+    #[prost(string, tag = "2")]
+    name: String,
+    #[prost(string, tag = "3")]
+    face: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Stat {
+    #[prost(int32, tag = "2")]
+    view: i32,
+    #[prost(int32, tag = "3")]
+    danmaku: i32,
+    #[prost(int32, tag = "4")]
+    reply: i32,
+    #[prost(int32, tag = "5")]
+    fav: i32,
+    #[prost(int32, tag = "6")]
+    coin: i32,
+    #[prost(int32, tag = "7")]
+    share: i32,
+    #[prost(int32, tag = "8")]
+    now_rank: i32,
+    #[prost(int32, tag = "9")]
+    his_rank: i32,
+    #[prost(int32, tag = "10")]
+    like: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ViewPage {
+    #[prost(message, optional, tag = "1")]
+    page: Option<Page>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Page {
+    #[prost(int64, tag = "1")]
+    cid: i64,
+    #[prost(int32, tag = "2")]
+    page: i32,
+    #[prost(string, tag = "4")]
+    part: String,
+    #[prost(int64, tag = "5")]
+    duration: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PlayViewReq {
+    #[prost(int64, tag = "1")]
+    aid: i64,
+    #[prost(int64, tag = "2")]
+    cid: i64,
+    #[prost(int64, tag = "3")]
+    qn: i64,
+    #[prost(int32, tag = "4")]
+    fnver: i32,
+    #[prost(int32, tag = "5")]
+    fnval: i32,
+    #[prost(bool, tag = "8")]
+    fourk: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct PlayViewReply {
+    #[prost(message, optional, tag = "1")]
+    video_info: Option<VideoInfo>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct VideoInfo {
+    #[prost(uint32, tag = "1")]
+    quality: u32,
+    #[prost(uint64, tag = "3")]
+    timelength: u64,
+    #[prost(message, repeated, tag = "5")]
+    stream_list: Vec<Stream>,
+    #[prost(message, repeated, tag = "6")]
+    dash_audio: Vec<DashItem>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Stream {
+    #[prost(message, optional, tag = "1")]
+    stream_info: Option<StreamInfo>,
+    #[prost(message, optional, tag = "2")]
+    dash_video: Option<DashVideo>,
+    #[prost(message, optional, tag = "3")]
+    segment_video: Option<SegmentVideo>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct StreamInfo {
+    #[prost(uint32, tag = "1")]
+    quality: u32,
+    #[prost(string, tag = "3")]
+    description: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct DashVideo {
+    #[prost(string, tag = "1")]
+    base_url: String,
+    #[prost(string, repeated, tag = "2")]
+    backup_url: Vec<String>,
+    #[prost(uint32, tag = "3")]
+    bandwidth: u32,
+    #[prost(uint32, tag = "4")]
+    codecid: u32,
+    #[prost(int32, tag = "10")]
+    width: i32,
+    #[prost(int32, tag = "11")]
+    height: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct DashItem {
+    #[prost(uint32, tag = "1")]
+    id: u32,
+    #[prost(string, tag = "2")]
+    base_url: String,
+    #[prost(string, repeated, tag = "3")]
+    backup_url: Vec<String>,
+    #[prost(uint32, tag = "4")]
+    bandwidth: u32,
+    #[prost(uint32, tag = "5")]
+    codecid: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct SegmentVideo {
+    #[prost(message, repeated, tag = "1")]
+    segment: Vec<ResponseUrl>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ResponseUrl {
+    #[prost(uint32, tag = "1")]
+    order: u32,
+    #[prost(uint64, tag = "2")]
+    length: u64,
+    #[prost(uint64, tag = "3")]
+    size: u64,
+    #[prost(string, tag = "4")]
+    url: String,
+    #[prost(string, repeated, tag = "5")]
+    backup_url: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct DmSegMobileReq {
+    #[prost(int64, tag = "1")]
+    pid: i64,
+    #[prost(int64, tag = "2")]
+    oid: i64,
+    #[prost(int32, tag = "3")]
+    r#type: i32,
+    #[prost(int64, tag = "4")]
+    segment_index: i64,
+}
+
+fn aid_of(bvid: &str) -> Result<u64> {
+    bvid_to_aid(bvid).ok_or_else(|| Error::UnexpectedResponse(format!("invalid bvid {}", bvid)))
+}
+
+impl From<ViewReply> for VideoView {
+    fn from(reply: ViewReply) -> Self {
+        let arc = reply.arc.unwrap_or_default();
+        let author = arc.author.unwrap_or_default();
+        let stat = arc.stat.unwrap_or_default();
+        let aid = arc.aid as u64;
+        Self {
+            aid,
+            bvid: aid_to_bvid(aid),
+            title: arc.title,
+            desc: arc.desc,
+            pic: arc.pic,
+            duration: arc.duration as u64,
+            pubdate: arc.pubdate,
+            owner: VideoOwner {
+                mid: author.mid as u64,
+                name: author.name,
+                face: author.face,
+            },
+            stat: VideoStat {
+                aid,
+                bvid: aid_to_bvid(aid),
+                view: stat.view as u64,
+                danmaku: stat.danmaku as u64,
+                reply: stat.reply as u64,
+                favorite: stat.fav as u64,
+                coin: stat.coin as u64,
+                share: stat.share as u64,
+                like: stat.like as u64,
+                now_rank: stat.now_rank as u64,
+                his_rank: stat.his_rank as u64,
+            },
+            pages: reply
+                .pages
+                .into_iter()
+                .filter_map(|page| page.page)
+                .map(|page| VideoPage {
+                    cid: page.cid as u64,
+                    page: page.page as u32,
+                    part: page.part,
+                    duration: page.duration as u64,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<VideoInfo> for VideoPlayUrl {
+    fn from(info: VideoInfo) -> Self {
+        let timelength = info.timelength;
+        let mut accept_quality = Vec::new();
+        let mut accept_description = Vec::new();
+        let mut video = Vec::new();
+        let mut durl = Vec::new();
+        // every quality is listed, only the playable ones carry a stream
+        for stream in info.stream_list {
+            let stream_info = stream.stream_info.unwrap_or_default();
+            accept_quality.push(stream_info.quality);
+            accept_description.push(stream_info.description);
+            if let Some(dash) = stream.dash_video {
+                video.push(DashStream {
+                    id: stream_info.quality,
+                    base_url: dash.base_url,
+                    backup_url: Some(dash.backup_url),
+                    bandwidth: dash.bandwidth as u64,
+                    mime_type: String::new(),
+                    codecs: String::new(),
+                    width: dash.width as u32,
+                    height: dash.height as u32,
+                    codecid: dash.codecid,
+                });
+            }
+            if let Some(segments) = stream.segment_video {
+                durl.extend(segments.segment.into_iter().map(|segment| Durl {
+                    order: segment.order,
+                    length: segment.length,
+                    size: segment.size,
+                    url: segment.url,
+                    backup_url: Some(segment.backup_url),
+                }));
+            }
+        }
+        let audio = info
+            .dash_audio
+            .into_iter()
+            .map(|audio| DashStream {
+                id: audio.id,
+                base_url: audio.base_url,
+                backup_url: Some(audio.backup_url),
+                bandwidth: audio.bandwidth as u64,
+                mime_type: String::new(),
+                codecs: String::new(),
+                width: 0,
+                height: 0,
+                codecid: audio.codecid,
+            })
+            .collect();
+        Self {
+            quality: info.quality,
+            timelength,
+            accept_quality,
+            accept_description,
+            dash: (!video.is_empty()).then_some(Dash {
+                duration: timelength / 1000,
+                video,
+                audio: Some(audio),
+            }),
+            durl: (!durl.is_empty()).then_some(durl),
+        }
+    }
+}
+
+pub(super) async fn get_view(client: &BiliClient, bvid: &str) -> Result<VideoView> {
+    let request = ViewReq {
+        aid: aid_of(bvid)? as i64,
+        bvid: bvid.to_string(),
+    };
+    let reply: ViewReply = grpc::call(client, consts::VIEW, &request).await?;
+    Ok(reply.into())
+}
+
+pub(super) async fn get_play_url(
+    client: &BiliClient,
+    bvid: &str,
+    cid: u64,
+    qn: u32,
+) -> Result<VideoPlayUrl> {
+    let request = PlayViewReq {
+        aid: aid_of(bvid)? as i64,
+        cid: cid as i64,
+        qn: qn as i64,
+        fnver: 0,
+        fnval: 4048,
+        fourk: true,
+    };
+    let reply: PlayViewReply = grpc::call(client, consts::PLAY_VIEW, &request).await?;
+    let info = reply
+        .video_info
+        .ok_or_else(|| Error::UnexpectedResponse("no video info in play view".to_string()))?;
+    Ok(info.into())
+}
+
+pub(super) async fn get_danmaku(
+    client: &BiliClient,
+    cid: u64,
+    segment: u32,
+) -> Result<Vec<VideoDanmaku>> {
+    let request = DmSegMobileReq {
+        pid: 0,
+        oid: cid as i64,
+        r#type: 1,
+        segment_index: segment as i64,
+    };
+    let reply: DmSegMobileReply = grpc::call(client, consts::DM_SEG_MOBILE, &request).await?;
+    Ok(reply.elems.into_iter().map(Into::into).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_view() {
+        let info = VideoInfo {
+            quality: 80,
+            timelength: 10_000,
+            stream_list: vec![
+                Stream {
+                    stream_info: Some(StreamInfo {
+                        quality: 116,
+                        description: "1080P 60帧".to_string(),
+                    }),
+                    dash_video: None,
+                    segment_video: None,
+                },
+                Stream {
+                    stream_info: Some(StreamInfo {
+                        quality: 80,
+                        description: "1080P 高清".to_string(),
+                    }),
+                    dash_video: Some(DashVideo {
+                        base_url: "v80".to_string(),
+                        backup_url: vec!["b80".to_string()],
+                        bandwidth: 2000,
+                        codecid: 7,
+                        width: 1920,
+                        height: 1080,
+                    }),
+                    segment_video: None,
+                },
+            ],
+            dash_audio: vec![DashItem {
+                id: 30280,
+                base_url: "a".to_string(),
+                backup_url: Vec::new(),
+                bandwidth: 320000,
+                codecid: 0,
+            }],
+        };
+        let decoded = VideoInfo::decode(info.encode_to_vec().as_slice()).unwrap();
+        let play_url = VideoPlayUrl::from(decoded);
+        assert_eq!(play_url.accept_quality, [116, 80]);
+        let dash = play_url.dash.unwrap();
+        assert_eq!(dash.duration, 10);
+        let (video, audio) = dash.best_pair(None).unwrap();
+        assert_eq!(video.id, 80);
+        assert_eq!(video.to_task().urls, ["v80", "b80"]);
+        assert_eq!(audio.id, 30280);
+        assert!(play_url.durl.is_none());
+    }
+}
@@ -0,0 +1,54 @@
+//! Conversion between aid (`av` number) and bvid.
+use std::convert::TryInto;
+
+const XOR_CODE: u64 = 23442827791579;
+const MASK_CODE: u64 = 2251799813685247;
+const MAX_AID: u64 = 1 << 51;
+const BASE: u64 = 58;
+const ALPHABET: &[u8] = b"FcwAPNKTMug3GV5Lj7EJnHpWsx4tb8haYeviqBz6rkCy12mUSDQX9RdoZf";
+
+/// Convert an aid to its bvid, e.g. `2` to `BV1xx411c7mD`.
+pub fn aid_to_bvid(aid: u64) -> String {
+    let mut bytes = *b"BV1000000000";
+    let mut tmp = (MAX_AID | aid) ^ XOR_CODE;
+    let mut index = bytes.len() - 1;
+    while tmp > 0 {
+        bytes[index] = ALPHABET[(tmp % BASE) as usize];
+        tmp /= BASE;
+        index -= 1;
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Convert a bvid to its aid, `None` if it is malformed.
+pub fn bvid_to_aid(bvid: &str) -> Option<u64> {
+    let mut bytes: [u8; 12] = bvid.as_bytes().try_into().ok()?;
+    if !bytes[..3].eq_ignore_ascii_case(b"BV1") {
+        return None;
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+    let mut tmp = 0u64;
+    for byte in &bytes[3..] {
+        let digit = ALPHABET.iter().position(|c| c == byte)? as u64;
+        tmp = tmp * BASE + digit;
+    }
+    Some((tmp & MASK_CODE) ^ XOR_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        for (aid, bvid) in [(2, "BV1xx411c7mD"), (111298867365120, "BV1L9Uoa9EUx")] {
+            assert_eq!(aid_to_bvid(aid), bvid);
+            assert_eq!(bvid_to_aid(bvid), Some(aid));
+        }
+        assert_eq!(bvid_to_aid("BV1xx411c7m"), None);
+        assert_eq!(bvid_to_aid("BV1xx411c7m0"), None);
+    }
+}
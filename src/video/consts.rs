@@ -0,0 +1,14 @@
+pub const DANMAKU_SEG: &str = "https://api.bilibili.com/x/v2/dm/web/seg.so";
+pub const DANMAKU_XML: &str = "https://comment.bilibili.com";
+pub const DANMAKU_POST: &str = "https://api.bilibili.com/x/v2/dm/post";
+pub const PLAY_URL: &str = "https://api.bilibili.com/x/player/wbi/playurl";
+pub const ARCHIVE_STAT: &str = "https://api.bilibili.com/x/web-interface/archive/stat";
+pub const ONLINE_TOTAL: &str = "https://api.bilibili.com/x/player/online/total";
+pub const LIKE: &str = "https://api.bilibili.com/x/web-interface/archive/like";
+pub const COIN: &str = "https://api.bilibili.com/x/web-interface/coin/add";
+pub const TRIPLE: &str = "https://api.bilibili.com/x/web-interface/archive/like/triple";
+pub const VIEW: &str = "https://api.bilibili.com/x/web-interface/view";
+pub const PLAYER_V2: &str = "https://api.bilibili.com/x/player/wbi/v2";
+pub const VIDEOSHOT: &str = "https://api.bilibili.com/x/player/videoshot";
+pub const PAGELIST: &str = "https://api.bilibili.com/x/player/pagelist";
+pub const STEIN_EDGE: &str = "https://api.bilibili.com/x/stein/edgeinfo_v2";
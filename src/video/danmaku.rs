@@ -0,0 +1,262 @@
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, ClientBackend, Result};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A danmaku of a video.
+pub struct VideoDanmaku {
+    pub id: u64,
+    /// Offset in the video.
+    pub progress_ms: u32,
+    /// `1` scroll, `4` bottom, `5` top, `7` advanced.
+    pub mode: u8,
+    pub font_size: u32,
+    /// RGB color, e.g. `0xffffff`.
+    pub color: u32,
+    /// CRC32 of the sender's uid in hex.
+    pub mid_hash: String,
+    pub content: String,
+    /// Unix timestamp in seconds the danmaku was sent.
+    pub ctime: i64,
+    /// Shielding weight, from `0` to `10`.
+    pub weight: u8,
+    /// `0` normal, `1` subtitle, `2` special.
+    pub pool: u8,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct DmSegMobileReply {
+    #[prost(message, repeated, tag = "1")]
+    pub(super) elems: Vec<DanmakuElem>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct DanmakuElem {
+    #[prost(int64, tag = "1")]
+    id: i64,
+    #[prost(int32, tag = "2")]
+    progress: i32,
+    #[prost(int32, tag = "3")]
+    mode: i32,
+    #[prost(int32, tag = "4")]
+    fontsize: i32,
+    #[prost(uint32, tag = "5")]
+    color: u32,
+    #[prost(string, tag = "6")]
+    mid_hash: String,
+    #[prost(string, tag = "7")]
+    content: String,
+    #[prost(int64, tag = "8")]
+    ctime: i64,
+    #[prost(int32, tag = "9")]
+    weight: i32,
+    #[prost(int32, tag = "11")]
+    pool: i32,
+}
+
+impl From<DanmakuElem> for VideoDanmaku {
+    fn from(elem: DanmakuElem) -> Self {
+        Self {
+            id: elem.id as u64,
+            progress_ms: elem.progress as u32,
+            mode: elem.mode as u8,
+            font_size: elem.fontsize as u32,
+            color: elem.color,
+            mid_hash: elem.mid_hash,
+            content: elem.content,
+            ctime: elem.ctime,
+            weight: elem.weight as u8,
+            pool: elem.pool as u8,
+        }
+    }
+}
+
+/// Get danmaku of a 6-minute segment, starting from `1`, of the video part with the backend
+/// of the client.
+pub async fn get_danmaku(client: &BiliClient, cid: u64, segment: u32) -> Result<Vec<VideoDanmaku>> {
+    get_danmaku_with(client, cid, segment, client.backend()).await
+}
+
+/// Get danmaku of a 6-minute segment, starting from `1`, of the video part with `backend`.
+pub async fn get_danmaku_with(
+    client: &BiliClient,
+    cid: u64,
+    segment: u32,
+    backend: ClientBackend,
+) -> Result<Vec<VideoDanmaku>> {
+    match backend {
+        ClientBackend::Web => {}
+        #[cfg(feature = "grpc")]
+        ClientBackend::App => return super::app::get_danmaku(client, cid, segment).await,
+    }
+    let bytes = client
+        .get_bytes(
+            consts::DANMAKU_SEG,
+            &[("type", 1), ("oid", cid), ("segment_index", segment as u64)],
+        )
+        .await?;
+    let reply = DmSegMobileReply::decode(bytes.as_slice())
+        .map_err(|e| crate::Error::UnexpectedResponse(format!("invalid danmaku segment: {}", e)))?;
+    Ok(reply.elems.into_iter().map(Into::into).collect())
+}
+
+/// Get danmaku of the video part from the legacy XML endpoint, which holds a limited
+/// number of the latest danmaku but needs no segmenting.
+pub async fn get_danmaku_xml(client: &BiliClient, cid: u64) -> Result<Vec<VideoDanmaku>> {
+    let bytes = client
+        .get_bytes(&format!("{}/{}.xml", consts::DANMAKU_XML, cid), &())
+        .await?;
+    // the body is raw deflate without `Content-Encoding` most of the time
+    let mut xml = String::new();
+    if DeflateDecoder::new(bytes.as_slice())
+        .read_to_string(&mut xml)
+        .is_err()
+    {
+        xml = String::from_utf8_lossy(&bytes).into_owned();
+    }
+    Ok(parse_danmaku_xml(&xml))
+}
+
+/// Parse `<d p="...">` elements of a bilibili danmaku XML, skipping malformed ones.
+pub fn parse_danmaku_xml(xml: &str) -> Vec<VideoDanmaku> {
+    xml.split("<d p=\"")
+        .skip(1)
+        .filter_map(|element| {
+            let (attrs, rest) = element.split_once("\">")?;
+            let content = rest.split("</d>").next()?;
+            let attrs: Vec<&str> = attrs.split(',').collect();
+            Some(VideoDanmaku {
+                progress_ms: (attrs.first()?.parse::<f64>().ok()? * 1000.0) as u32,
+                mode: attrs.get(1)?.parse().ok()?,
+                font_size: attrs.get(2)?.parse().ok()?,
+                color: attrs.get(3)?.parse().ok()?,
+                ctime: attrs.get(4)?.parse().ok()?,
+                pool: attrs.get(5)?.parse().ok()?,
+                mid_hash: attrs.get(6)?.to_string(),
+                id: attrs.get(7)?.parse().ok()?,
+                weight: attrs
+                    .get(8)
+                    .and_then(|w| w.parse().ok())
+                    .unwrap_or_default(),
+                content: unescape_xml(content),
+            })
+        })
+        .collect()
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A danmaku to send to a video.
+pub struct DanmakuDraft {
+    pub msg: String,
+    /// Offset in the video in milliseconds.
+    pub progress: u32,
+    /// `1` scroll, `4` bottom, `5` top.
+    pub mode: u8,
+    /// RGB color, e.g. `0xffffff`.
+    pub color: u32,
+    /// `25` normal, `18` small.
+    pub fontsize: u32,
+}
+
+impl DanmakuDraft {
+    /// A white scrolling danmaku at `progress` milliseconds.
+    pub fn new(msg: &str, progress: u32) -> Self {
+        Self {
+            msg: msg.to_string(),
+            progress,
+            mode: 1,
+            color: 0xffffff,
+            fontsize: 25,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Result of sending a danmaku.
+pub struct SentDanmaku {
+    pub dmid: u64,
+}
+
+/// Send a danmaku to the video part `cid` of the video `aid`.
+pub async fn send_danmaku(
+    client: &BiliClient,
+    cid: u64,
+    aid: u64,
+    draft: DanmakuDraft,
+) -> Result<SentDanmaku> {
+    let rnd = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros();
+    client
+        .post_form(
+            consts::DANMAKU_POST,
+            &[
+                ("type", "1".to_string()),
+                ("oid", cid.to_string()),
+                ("aid", aid.to_string()),
+                ("msg", draft.msg),
+                ("progress", draft.progress.to_string()),
+                ("mode", draft.mode.to_string()),
+                ("color", draft.color.to_string()),
+                ("fontsize", draft.fontsize.to_string()),
+                ("pool", "0".to_string()),
+                ("rnd", rnd.to_string()),
+                ("plat", "1".to_string()),
+                ("csrf", client.csrf()?),
+            ],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_segment() {
+        let reply = DmSegMobileReply {
+            elems: vec![DanmakuElem {
+                id: 1,
+                progress: 1500,
+                mode: 1,
+                fontsize: 25,
+                color: 0xffffff,
+                mid_hash: "abcdef12".to_string(),
+                content: "hello".to_string(),
+                ctime: 1639000000,
+                weight: 10,
+                pool: 0,
+            }],
+        };
+        let decoded = DmSegMobileReply::decode(reply.encode_to_vec().as_slice()).unwrap();
+        let danmaku: VideoDanmaku = decoded.elems[0].clone().into();
+        assert_eq!(danmaku.progress_ms, 1500);
+        assert_eq!(danmaku.content, "hello");
+    }
+
+    #[test]
+    fn test_parse_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?><i><chatid>1</chatid>
+            <d p="12.345,1,25,16777215,1639000000,0,abcdef12,123456,10">a&amp;b</d>
+            <d p="broken">x</d></i>"#;
+        let danmaku = parse_danmaku_xml(xml);
+        assert_eq!(danmaku.len(), 1);
+        assert_eq!(danmaku[0].progress_ms, 12345);
+        assert_eq!(danmaku[0].id, 123456);
+        assert_eq!(danmaku[0].content, "a&b");
+    }
+}
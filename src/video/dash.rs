@@ -0,0 +1,110 @@
+use std::sync::Mutex;
+
+use tokio::io::AsyncWrite;
+
+use super::download::{download, Progress};
+use super::playurl::DashStream;
+use crate::{BiliClient, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Progress of downloading a DASH pair.
+pub struct DashProgress {
+    pub video: Progress,
+    pub audio: Progress,
+}
+
+impl DashProgress {
+    /// Bytes downloaded of both streams.
+    pub fn downloaded(&self) -> u64 {
+        self.video.downloaded + self.audio.downloaded
+    }
+
+    /// Total bytes of both streams, if known for both.
+    pub fn total(&self) -> Option<u64> {
+        Some(self.video.total? + self.audio.total?)
+    }
+}
+
+/// Download matching video and audio representations concurrently into separate writers,
+/// e.g. two files to mux afterwards or a muxer's inputs.
+///
+/// Returns the bytes written of video and audio.
+pub async fn download_dash<VW, AW, F>(
+    client: &BiliClient,
+    video: &DashStream,
+    audio: &DashStream,
+    video_writer: &mut VW,
+    audio_writer: &mut AW,
+    on_progress: F,
+) -> Result<(u64, u64)>
+where
+    VW: AsyncWrite + Unpin,
+    AW: AsyncWrite + Unpin,
+    F: FnMut(DashProgress),
+{
+    let empty = Progress {
+        downloaded: 0,
+        total: None,
+    };
+    let state = Mutex::new((
+        DashProgress {
+            video: empty,
+            audio: empty,
+        },
+        on_progress,
+    ));
+    let report = |update: &dyn Fn(&mut DashProgress)| {
+        let mut state = state.lock().unwrap();
+        let (progress, on_progress) = &mut *state;
+        update(progress);
+        on_progress(*progress);
+    };
+    tokio::try_join!(
+        download(client, video.to_task(), video_writer, |progress| report(
+            &|combined| combined.video = progress
+        )),
+        download(client, audio.to_task(), audio_writer, |progress| report(
+            &|combined| combined.audio = progress
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_download_dash() {
+        let video_url = "https://upos-sz-mirrorcos.bilivideo.com/video.m4s";
+        let audio_url = "https://upos-sz-mirrorcos.bilivideo.com/audio.m4s";
+        let transport = MockTransport::new()
+            .ranged(video_url, b"video data".to_vec(), true)
+            .ranged(audio_url, b"audio".to_vec(), true);
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let stream = |url: &str| -> DashStream {
+            serde_json::from_value(json!({"id": 80, "base_url": url, "bandwidth": 1})).unwrap()
+        };
+        let (mut video, mut audio) = (Vec::new(), Vec::new());
+        let mut last = None;
+        let written = download_dash(
+            &client,
+            &stream(video_url),
+            &stream(audio_url),
+            &mut video,
+            &mut audio,
+            |progress| last = Some(progress),
+        )
+        .await
+        .unwrap();
+        assert_eq!(written, (10, 5));
+        assert_eq!(
+            (video.as_slice(), audio.as_slice()),
+            (&b"video data"[..], &b"audio"[..])
+        );
+        let last = last.unwrap();
+        assert_eq!(last.downloaded(), 15);
+        assert_eq!(last.total(), Some(15));
+    }
+}
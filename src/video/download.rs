@@ -0,0 +1,234 @@
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::{Method, StatusCode};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::time::Duration;
+
+use crate::error::Error;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug)]
+/// A stream to download, e.g. a `durl` entry or a DASH representation.
+pub struct DownloadTask {
+    /// The main url followed by backup urls, tried in turn on failure.
+    pub urls: Vec<String>,
+    /// Bytes already downloaded, to resume a previous download.
+    pub start: u64,
+    /// Bytes requested per range request.
+    pub chunk_size: u64,
+    /// Retries of a chunk before giving up.
+    pub max_retries: usize,
+}
+
+impl DownloadTask {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            urls,
+            start: 0,
+            chunk_size: 8 * 1024 * 1024,
+            max_retries: 3,
+        }
+    }
+
+    /// Resume from `start` bytes.
+    pub fn resume_from(mut self, start: u64) -> Self {
+        self.start = start;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Progress of a download.
+pub struct Progress {
+    /// Bytes written, including the resumed part.
+    pub downloaded: u64,
+    /// Total bytes, if the server tells.
+    pub total: Option<u64>,
+}
+
+/// Parse the total size from `bytes 0-99/1000`.
+fn parse_total(content_range: &str) -> Option<u64> {
+    content_range.rsplit('/').next()?.parse().ok()
+}
+
+/// Download the stream into `writer` with range requests, returning the total bytes written.
+///
+/// Each chunk is retried on failure from where it broke off, switching to the next url.
+/// When the server doesn't tell the total size, ranges are requested until one comes back
+/// short.
+pub async fn download<W, F>(
+    client: &BiliClient,
+    task: DownloadTask,
+    writer: &mut W,
+    mut on_progress: F,
+) -> Result<u64>
+where
+    W: AsyncWrite + Unpin,
+    F: FnMut(Progress),
+{
+    if task.urls.is_empty() {
+        return Err(Error::UnexpectedResponse("no url to download".to_string()));
+    }
+    let mut offset = task.start;
+    let mut total = None;
+    let mut attempt = 0usize;
+    // stays on the url which last worked
+    let mut url_index = 0;
+    loop {
+        if matches!(total, Some(total) if offset >= total) {
+            break;
+        }
+        let url = &task.urls[url_index % task.urls.len()];
+        let end = offset + task.chunk_size - 1;
+        let result = async {
+            let request = client
+                .request(Method::GET, url)
+                .header(RANGE, format!("bytes={}-{}", offset, end));
+            let response = client.send(request).await?;
+            if total.is_none() && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                // the size was a multiple of the chunk size
+                return Ok(Some(0));
+            }
+            let mut response = response.error_for_status()?;
+            let ranged = response.status() == StatusCode::PARTIAL_CONTENT;
+            if ranged {
+                total = response
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|range| range.to_str().ok())
+                    .and_then(parse_total);
+            } else if offset == 0 {
+                // the server ignored the range and sends everything
+                total = response.content_length();
+            } else {
+                return Err(Error::UnexpectedResponse(
+                    "server does not support resuming".to_string(),
+                ));
+            }
+            let mut received = 0;
+            while let Some(chunk) = response.chunk().await? {
+                writer.write_all(&chunk).await?;
+                offset += chunk.len() as u64;
+                received += chunk.len() as u64;
+                on_progress(Progress {
+                    downloaded: offset,
+                    total,
+                });
+            }
+            Ok(ranged.then_some(received))
+        }
+        .await;
+        match result {
+            Ok(Some(_)) if total.is_some() => attempt = 0,
+            // without a total, the end is the first chunk shorter than requested
+            Ok(Some(received)) if received == task.chunk_size => attempt = 0,
+            Ok(_) => {
+                total = Some(offset);
+            }
+            Err(e) if attempt < task.max_retries => {
+                attempt += 1;
+                url_index += 1;
+                warn!(
+                    "download failed at {} bytes, retry {}: {:?}",
+                    offset, attempt, e
+                );
+                tokio::time::sleep(Duration::from_secs(attempt as u64)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    writer.flush().await?;
+    Ok(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+
+    #[test]
+    fn test_parse_total() {
+        assert_eq!(parse_total("bytes 0-99/1000"), Some(1000));
+        assert_eq!(parse_total("bytes 0-99/*"), None);
+    }
+
+    const URL: &str = "https://upos-sz-mirrorcos.bilivideo.com/video.m4s";
+    const BACKUP: &str = "https://upos-sz-mirrorali.bilivideo.com/video.m4s";
+
+    fn task(urls: &[&str]) -> DownloadTask {
+        DownloadTask {
+            chunk_size: 4,
+            ..DownloadTask::new(urls.iter().map(|url| url.to_string()).collect())
+        }
+    }
+
+    async fn run(transport: MockTransport, task: DownloadTask) -> (Vec<u8>, Vec<Progress>) {
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let mut output = Vec::new();
+        let mut progress = Vec::new();
+        let written = download(&client, task, &mut output, |p| progress.push(p))
+            .await
+            .unwrap();
+        assert_eq!(written, progress.last().unwrap().downloaded);
+        (output, progress)
+    }
+
+    #[tokio::test]
+    async fn test_unknown_total() {
+        for body in [&b"0123456789"[..], b"01234567"] {
+            let transport = MockTransport::new().ranged(URL, body.to_vec(), false);
+            let (output, progress) = run(transport.clone(), task(&[URL])).await;
+            assert_eq!(output, body);
+            assert!(progress.iter().all(|p| p.total.is_none()));
+            assert_eq!(transport.requests().len(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume() {
+        let transport = MockTransport::new().ranged(URL, b"0123456789".to_vec(), true);
+        let (output, progress) = run(transport, task(&[URL]).resume_from(3)).await;
+        assert_eq!(output, b"3456789");
+        assert_eq!(
+            progress.last(),
+            Some(&Progress {
+                downloaded: 10,
+                total: Some(10),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let transport = MockTransport::new().respond(URL, 404, Vec::new()).ranged(
+            BACKUP,
+            b"0123456789".to_vec(),
+            true,
+        );
+        let (output, _) = run(transport.clone(), task(&[URL, BACKUP])).await;
+        assert_eq!(output, b"0123456789");
+        let hosts: Vec<_> = transport
+            .requests()
+            .iter()
+            .map(|url| url.host_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            hosts,
+            [
+                "upos-sz-mirrorcos.bilivideo.com",
+                "upos-sz-mirrorali.bilivideo.com",
+                "upos-sz-mirrorali.bilivideo.com",
+                "upos-sz-mirrorali.bilivideo.com",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let transport = MockTransport::new()
+            .ranged(URL, b"0123456789".to_vec(), true)
+            .respond_once(URL, 500, Vec::new());
+        let (output, _) = run(transport.clone(), task(&[URL])).await;
+        assert_eq!(output, b"0123456789");
+        assert_eq!(transport.requests().len(), 4);
+    }
+}
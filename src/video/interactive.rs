@@ -0,0 +1,169 @@
+use std::collections::{HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A node of an interactive video, a part played before the next choice.
+pub struct InteractiveNode {
+    pub edge_id: u64,
+    pub title: String,
+    /// Questions asked at the end of the part, empty on a leaf.
+    #[serde(rename = "edges", deserialize_with = "questions")]
+    pub questions: Vec<InteractiveQuestion>,
+    /// `1` if the node ends the video.
+    pub is_leaf: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InteractiveQuestion {
+    pub id: u64,
+    /// Shown above the choices.
+    pub title: String,
+    /// Milliseconds the choices stay, `-1` until one is made.
+    pub duration: i64,
+    pub choices: Vec<InteractiveChoice>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A choice, leading to the node `id`.
+pub struct InteractiveChoice {
+    pub id: u64,
+    /// Cid of the part the node plays.
+    pub cid: u64,
+    /// Text of the choice.
+    pub option: String,
+    /// Condition on hidden variables to show the choice, empty if always shown.
+    pub condition: String,
+    /// Changes of hidden variables when chosen.
+    pub native_action: String,
+    /// `1` if chosen when time runs out.
+    pub is_default: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+/// All nodes of an interactive video reachable from its start.
+pub struct InteractiveGraph {
+    /// The start first, then in breadth-first order.
+    pub nodes: Vec<InteractiveNode>,
+}
+
+impl InteractiveGraph {
+    /// Choices as `(from, to)` node ids.
+    pub fn edges(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.nodes.iter().flat_map(|node| {
+            node.questions
+                .iter()
+                .flat_map(|q| &q.choices)
+                .map(move |choice| (node.edge_id, choice.id))
+        })
+    }
+}
+
+/// `edges` is `{"questions": [...]}`, absent on a leaf.
+fn questions<'de, D>(deserializer: D) -> std::result::Result<Vec<InteractiveQuestion>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Default, Deserialize)]
+    struct Edges {
+        #[serde(default)]
+        questions: Vec<InteractiveQuestion>,
+    }
+
+    Ok(Option::<Edges>::deserialize(deserializer)?
+        .unwrap_or_default()
+        .questions)
+}
+
+/// Get the version of the graph of an interactive video, from its first part `cid`.
+pub async fn get_graph_version(client: &BiliClient, bvid: &str, cid: u64) -> Result<u64> {
+    #[derive(Deserialize)]
+    struct Player {
+        #[serde(default)]
+        interaction: Interaction,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct Interaction {
+        #[serde(default)]
+        graph_version: u64,
+    }
+
+    let player: Player = client
+        .get_wbi(
+            consts::PLAYER_V2,
+            &[("bvid", bvid.to_string()), ("cid", cid.to_string())],
+        )
+        .await?;
+    Ok(player.interaction.graph_version)
+}
+
+/// Get a node of an interactive video, `None` for the start.
+pub async fn get_interactive_node(
+    client: &BiliClient,
+    bvid: &str,
+    graph_version: u64,
+    edge_id: Option<u64>,
+) -> Result<InteractiveNode> {
+    let mut query = vec![
+        ("bvid", bvid.to_string()),
+        ("graph_version", graph_version.to_string()),
+    ];
+    if let Some(edge_id) = edge_id {
+        query.push(("edge_id", edge_id.to_string()));
+    }
+    client.get(consts::STEIN_EDGE, &query).await
+}
+
+/// Crawl every node reachable from the start of an interactive video, one request each.
+pub async fn get_interactive_graph(
+    client: &BiliClient,
+    bvid: &str,
+    graph_version: u64,
+) -> Result<InteractiveGraph> {
+    let start = get_interactive_node(client, bvid, graph_version, None).await?;
+    let mut visited = HashSet::from([start.edge_id]);
+    let mut queue = VecDeque::from([start]);
+    let mut nodes = Vec::new();
+    while let Some(node) = queue.pop_front() {
+        for choice in node.questions.iter().flat_map(|q| &q.choices) {
+            if visited.insert(choice.id) {
+                let next = get_interactive_node(client, bvid, graph_version, Some(choice.id));
+                queue.push_back(next.await?);
+            }
+        }
+        nodes.push(node);
+    }
+    Ok(InteractiveGraph { nodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_node() {
+        let node: InteractiveNode = serde_json::from_value(serde_json::json!({
+            "title": "start", "edge_id": 1, "is_leaf": 0,
+            "edges": {"questions": [{"id": 10, "type": 0, "duration": -1, "title": "", "choices": [
+                {"id": 2, "cid": 100, "option": "left", "is_default": 1},
+                {"id": 3, "cid": 101, "option": "right", "condition": "$a>1"},
+            ]}]},
+        }))
+        .unwrap();
+        let graph = InteractiveGraph { nodes: vec![node] };
+        assert_eq!(graph.edges().collect::<Vec<_>>(), [(1, 2), (1, 3)]);
+        assert_eq!(graph.nodes[0].questions[0].choices[0].is_default, 1);
+
+        let leaf: InteractiveNode =
+            serde_json::from_value(serde_json::json!({"edge_id": 4, "is_leaf": 1})).unwrap();
+        assert_eq!(leaf.is_leaf, 1);
+        assert!(leaf.questions.is_empty());
+    }
+}
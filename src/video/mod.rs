@@ -0,0 +1,35 @@
+//! Video (archive) APIs.
+mod action;
+#[cfg(feature = "grpc")]
+mod app;
+mod bvid;
+pub mod consts;
+mod danmaku;
+mod dash;
+mod download;
+mod interactive;
+mod playurl;
+mod snapshot;
+mod stat;
+mod subtitle;
+mod view;
+
+pub use action::{coin, favorite, like, triple, unlike, Triple};
+pub use bvid::{aid_to_bvid, bvid_to_aid};
+pub use danmaku::{
+    get_danmaku, get_danmaku_with, get_danmaku_xml, parse_danmaku_xml, send_danmaku, DanmakuDraft,
+    SentDanmaku, VideoDanmaku,
+};
+pub use dash::{download_dash, DashProgress};
+pub use download::{download, DownloadTask, Progress};
+pub use interactive::{
+    get_graph_version, get_interactive_graph, get_interactive_node, InteractiveChoice,
+    InteractiveGraph, InteractiveNode, InteractiveQuestion,
+};
+pub use playurl::{get_play_url, get_play_url_with, Dash, DashStream, Durl, VideoPlayUrl};
+pub use snapshot::{get_snapshots, SnapshotFrame, VideoSnapshots};
+pub use stat::{get_online_count, get_stat, OnlineCount, VideoStat};
+pub use subtitle::{fetch_subtitle, get_subtitles, Subtitle, SubtitleInfo, SubtitleLine};
+pub use view::{
+    get_pagelist, get_view, get_view_with, resolve_cid, VideoOwner, VideoPage, VideoView,
+};
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use super::download::DownloadTask;
+use crate::{BiliClient, ClientBackend, Result};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Playback urls of a video part.
+pub struct VideoPlayUrl {
+    /// Quality actually returned.
+    pub quality: u32,
+    /// Milliseconds.
+    pub timelength: u64,
+    pub accept_quality: Vec<u32>,
+    #[serde(default)]
+    pub accept_description: Vec<String>,
+    /// Separate audio and video streams, most videos have these.
+    #[serde(default)]
+    pub dash: Option<Dash>,
+    /// Muxed flv/mp4 segments, for old videos or when DASH is not requested.
+    #[serde(default)]
+    pub durl: Option<Vec<Durl>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// DASH representations.
+pub struct Dash {
+    /// Seconds.
+    pub duration: u64,
+    pub video: Vec<DashStream>,
+    #[serde(default)]
+    pub audio: Option<Vec<DashStream>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A DASH representation.
+pub struct DashStream {
+    /// Quality, `qn` for video.
+    pub id: u32,
+    #[serde(alias = "baseUrl")]
+    pub base_url: String,
+    #[serde(default, alias = "backupUrl")]
+    pub backup_url: Option<Vec<String>>,
+    pub bandwidth: u64,
+    #[serde(default, alias = "mimeType")]
+    pub mime_type: String,
+    #[serde(default)]
+    pub codecs: String,
+    #[serde(default)]
+    pub width: u32,
+    #[serde(default)]
+    pub height: u32,
+    /// `7` AVC, `12` HEVC, `13` AV1.
+    #[serde(default)]
+    pub codecid: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A muxed segment.
+pub struct Durl {
+    pub order: u32,
+    /// Milliseconds.
+    pub length: u64,
+    pub size: u64,
+    pub url: String,
+    #[serde(default)]
+    pub backup_url: Option<Vec<String>>,
+}
+
+impl DashStream {
+    /// Download task of the main url followed by backup urls.
+    pub fn to_task(&self) -> DownloadTask {
+        let mut urls = vec![self.base_url.clone()];
+        urls.extend(self.backup_url.iter().flatten().cloned());
+        DownloadTask::new(urls)
+    }
+}
+
+impl Durl {
+    /// Download task of the main url followed by backup urls.
+    pub fn to_task(&self) -> DownloadTask {
+        let mut urls = vec![self.url.clone()];
+        urls.extend(self.backup_url.iter().flatten().cloned());
+        DownloadTask::new(urls)
+    }
+}
+
+impl Dash {
+    /// Pick the best video not above `max_qn` (any if `None`) and the audio with the highest
+    /// bandwidth, returns `None` if there is no matching video or no audio.
+    pub fn best_pair(&self, max_qn: Option<u32>) -> Option<(&DashStream, &DashStream)> {
+        let video = self
+            .video
+            .iter()
+            .filter(|video| max_qn.is_none_or(|max| video.id <= max))
+            .max_by_key(|video| (video.id, video.bandwidth))?;
+        let audio = self
+            .audio
+            .as_ref()?
+            .iter()
+            .max_by_key(|audio| audio.bandwidth)?;
+        Some((video, audio))
+    }
+}
+
+/// Get playback urls of the video part with DASH, `qn` is the preferred quality, e.g. `80` 1080P,
+/// with the backend of the client.
+pub async fn get_play_url(
+    client: &BiliClient,
+    bvid: &str,
+    cid: u64,
+    qn: u32,
+) -> Result<VideoPlayUrl> {
+    get_play_url_with(client, bvid, cid, qn, client.backend()).await
+}
+
+/// Get playback urls of the video part with DASH with `backend`, see [`get_play_url`].
+pub async fn get_play_url_with(
+    client: &BiliClient,
+    bvid: &str,
+    cid: u64,
+    qn: u32,
+    backend: ClientBackend,
+) -> Result<VideoPlayUrl> {
+    match backend {
+        ClientBackend::Web => {}
+        #[cfg(feature = "grpc")]
+        ClientBackend::App => return super::app::get_play_url(client, bvid, cid, qn).await,
+    }
+    client
+        .get_wbi(
+            consts::PLAY_URL,
+            &[
+                ("bvid", bvid.to_string()),
+                ("cid", cid.to_string()),
+                ("qn", qn.to_string()),
+                ("fnval", "4048".to_string()),
+                ("fourk", "1".to_string()),
+            ],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_pair() {
+        let dash: Dash = serde_json::from_value(serde_json::json!({
+            "duration": 10,
+            "video": [
+                {"id": 80, "baseUrl": "v80", "bandwidth": 2000},
+                {"id": 64, "base_url": "v64", "backupUrl": ["b64"], "bandwidth": 1000},
+            ],
+            "audio": [
+                {"id": 30216, "baseUrl": "a1", "bandwidth": 60000},
+                {"id": 30280, "baseUrl": "a2", "bandwidth": 190000},
+            ],
+        }))
+        .unwrap();
+        let (video, audio) = dash.best_pair(Some(64)).unwrap();
+        assert_eq!(video.to_task().urls, ["v64", "b64"]);
+        assert_eq!(audio.id, 30280);
+        assert_eq!(dash.best_pair(None).unwrap().0.id, 80);
+        assert!(dash.best_pair(Some(16)).is_none());
+    }
+}
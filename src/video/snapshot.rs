@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Preview sprites of a part of a video, each image a grid of frames.
+pub struct VideoSnapshots {
+    /// Url of the binary index, without scheme.
+    pub pvdata: String,
+    /// Frames per row of an image.
+    pub img_x_len: u32,
+    /// Frames per column of an image.
+    pub img_y_len: u32,
+    /// Width of a frame.
+    pub img_x_size: u32,
+    /// Height of a frame.
+    pub img_y_size: u32,
+    /// Urls of the images, without scheme.
+    pub image: Vec<String>,
+    /// Second of each frame, in order through the images.
+    pub index: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Where a frame is within the images.
+pub struct SnapshotFrame<'a> {
+    pub image: &'a str,
+    /// Left of the frame, in pixels.
+    pub x: u32,
+    /// Top of the frame, in pixels.
+    pub y: u32,
+}
+
+impl VideoSnapshots {
+    /// The last frame at or before `seconds`, `None` if there is none.
+    pub fn frame_at(&self, seconds: u64) -> Option<SnapshotFrame<'_>> {
+        let n = self
+            .index
+            .partition_point(|&t| t <= seconds)
+            .checked_sub(1)?;
+        self.frame(n)
+    }
+
+    /// The `n`-th frame, from `0`.
+    pub fn frame(&self, n: usize) -> Option<SnapshotFrame<'_>> {
+        let per_row = self.img_x_len as usize;
+        let per_image = per_row * self.img_y_len as usize;
+        if per_image == 0 {
+            return None;
+        }
+        let image = self.image.get(n / per_image)?;
+        let cell = n % per_image;
+        Some(SnapshotFrame {
+            image,
+            x: (cell % per_row) as u32 * self.img_x_size,
+            y: (cell / per_row) as u32 * self.img_y_size,
+        })
+    }
+}
+
+/// Get the preview sprites of the part `cid` of a video.
+pub async fn get_snapshots(client: &BiliClient, bvid: &str, cid: u64) -> Result<VideoSnapshots> {
+    client
+        .get(
+            consts::VIDEOSHOT,
+            &[("bvid", bvid), ("cid", &cid.to_string()), ("index", "1")],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_snapshots() {
+        let transport = MockTransport::new().json(
+            consts::VIDEOSHOT,
+            json!({"code": 0, "data": {
+                "pvdata": "//i0.hdslb.com/bfs/videoshot/1.bin",
+                "img_x_len": 2, "img_y_len": 2, "img_x_size": 160, "img_y_size": 90,
+                "image": ["//i0.hdslb.com/bfs/videoshot/1.jpg", "//i0.hdslb.com/bfs/videoshot/1-1.jpg"],
+                "index": [0, 5, 10, 15, 20],
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let snapshots = get_snapshots(&client, "BV17x411w7KC", 1).await.unwrap();
+        let frame = snapshots.frame_at(17).unwrap();
+        assert_eq!((frame.x, frame.y), (160, 90));
+        assert_eq!(frame.image, "//i0.hdslb.com/bfs/videoshot/1.jpg");
+        let frame = snapshots.frame_at(100).unwrap();
+        assert_eq!((frame.x, frame.y), (0, 0));
+        assert_eq!(frame.image, "//i0.hdslb.com/bfs/videoshot/1-1.jpg");
+        assert_eq!(snapshots.frame(8), None);
+    }
+}
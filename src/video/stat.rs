@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Counters of a video.
+pub struct VideoStat {
+    pub aid: u64,
+    pub bvid: String,
+    pub view: u64,
+    pub danmaku: u64,
+    pub reply: u64,
+    pub favorite: u64,
+    pub coin: u64,
+    pub share: u64,
+    pub like: u64,
+    /// Current rank, `0` if not ranked.
+    pub now_rank: u64,
+    /// Highest rank ever reached, `0` if never ranked.
+    pub his_rank: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Viewers watching a video right now.
+pub struct OnlineCount {
+    /// Viewers on all platforms, rounded above a thousand, e.g. `1000+`.
+    pub total: String,
+    /// Viewers on web.
+    #[serde(with = "crate::de::string_or_number")]
+    pub count: u64,
+}
+
+/// Get the view, like, coin and other counters of a video.
+pub async fn get_stat(client: &BiliClient, aid: u64) -> Result<VideoStat> {
+    client.get(consts::ARCHIVE_STAT, &[("aid", aid)]).await
+}
+
+/// Get how many viewers are watching a part of a video.
+pub async fn get_online_count(client: &BiliClient, bvid: &str, cid: u64) -> Result<OnlineCount> {
+    client
+        .get(
+            consts::ONLINE_TOTAL,
+            &[("bvid", bvid.to_string()), ("cid", cid.to_string())],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_online_count() {
+        let transport = MockTransport::new().json(
+            consts::ONLINE_TOTAL,
+            json!({"code": 0, "data": {
+                "total": "1000+", "count": "23",
+                "show_switch": {"total": true, "count": true},
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let online = get_online_count(&client, "BV1xx411c7mD", 1).await.unwrap();
+        assert_eq!(online.total, "1000+");
+        assert_eq!(online.count, 23);
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use crate::{BiliClient, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// A CC track available for a part of a video.
+pub struct SubtitleInfo {
+    pub id: u64,
+    /// e.g. `zh-CN`, `ai-zh`.
+    pub lan: String,
+    /// e.g. `中文（中国）`.
+    pub lan_doc: String,
+    pub is_lock: bool,
+    /// Url of the json body, often without scheme, see [`fetch_subtitle`].
+    pub subtitle_url: String,
+    /// `0` uploaded, `1` generated.
+    pub r#type: u8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Body of a CC track.
+pub struct Subtitle {
+    pub lang: String,
+    pub body: Vec<SubtitleLine>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// A line of a CC track.
+pub struct SubtitleLine {
+    /// Seconds.
+    pub from: f64,
+    /// Seconds.
+    pub to: f64,
+    /// `2` bottom, `8` top.
+    pub location: u8,
+    pub content: String,
+}
+
+/// List CC tracks of the part `cid` of a video.
+pub async fn get_subtitles(client: &BiliClient, bvid: &str, cid: u64) -> Result<Vec<SubtitleInfo>> {
+    #[derive(Deserialize)]
+    struct Player {
+        #[serde(default)]
+        subtitle: Subtitles,
+    }
+
+    #[derive(Default, Deserialize)]
+    struct Subtitles {
+        #[serde(default)]
+        subtitles: Vec<SubtitleInfo>,
+    }
+
+    let player: Player = client
+        .get_wbi(
+            consts::PLAYER_V2,
+            &[("bvid", bvid.to_string()), ("cid", cid.to_string())],
+        )
+        .await?;
+    Ok(player.subtitle.subtitles)
+}
+
+/// Download and parse the body of a CC track at `url`, see [`SubtitleInfo::subtitle_url`].
+pub async fn fetch_subtitle(client: &BiliClient, url: &str) -> Result<Subtitle> {
+    let url = if url.starts_with("//") {
+        format!("https:{}", url)
+    } else {
+        url.to_string()
+    };
+    let bytes = client.get_bytes(&url, &()).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+impl Subtitle {
+    /// Export as SubRip.
+    pub fn to_srt(&self) -> String {
+        let mut srt = String::new();
+        for (i, line) in self.body.iter().enumerate() {
+            srt += &format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                timestamp(line.from, ','),
+                timestamp(line.to, ','),
+                line.content
+            );
+        }
+        srt
+    }
+
+    /// Export as WebVTT.
+    pub fn to_vtt(&self) -> String {
+        let mut vtt = String::from("WEBVTT\n\n");
+        for line in &self.body {
+            vtt += &format!(
+                "{} --> {}\n{}\n\n",
+                timestamp(line.from, '.'),
+                timestamp(line.to, '.'),
+                line.content
+            );
+        }
+        vtt
+    }
+}
+
+/// Format seconds as `hh:mm:ss` followed by `separator` and milliseconds.
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_export() {
+        let subtitle: Subtitle = serde_json::from_value(json!({
+            "font_size": 0.4, "lang": "zh-CN", "version": "v1.6.0.4",
+            "body": [
+                {"from": 0.5, "to": 2.25, "sid": 1, "location": 2, "content": "hello", "music": 0.0},
+                {"from": 3661.0, "to": 3662.999, "sid": 2, "location": 2, "content": "world"},
+            ],
+        }))
+        .unwrap();
+        assert_eq!(
+            subtitle.to_srt(),
+            "1\n00:00:00,500 --> 00:00:02,250\nhello\n\n2\n01:01:01,000 --> 01:01:02,999\nworld\n\n"
+        );
+        assert!(subtitle
+            .to_vtt()
+            .starts_with("WEBVTT\n\n00:00:00.500 --> 00:00:02.250\nhello\n"));
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use super::consts;
+use super::stat::VideoStat;
+use crate::error::Error;
+use crate::{BiliClient, ClientBackend, Result};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+/// Details of a video.
+pub struct VideoView {
+    pub aid: u64,
+    pub bvid: String,
+    pub title: String,
+    pub desc: String,
+    /// Url of the cover.
+    pub pic: String,
+    /// Seconds, of all parts.
+    pub duration: u64,
+    /// Unix timestamp in seconds.
+    pub pubdate: i64,
+    pub owner: VideoOwner,
+    pub stat: VideoStat,
+    pub pages: Vec<VideoPage>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// Uploader of a video.
+pub struct VideoOwner {
+    pub mid: u64,
+    pub name: String,
+    pub face: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+/// A part of a video.
+pub struct VideoPage {
+    pub cid: u64,
+    /// Starting from `1`.
+    pub page: u32,
+    /// Title of the part.
+    pub part: String,
+    /// Seconds.
+    pub duration: u64,
+}
+
+/// Get details of a video with the backend of the client.
+pub async fn get_view(client: &BiliClient, bvid: &str) -> Result<VideoView> {
+    get_view_with(client, bvid, client.backend()).await
+}
+
+/// Get details of a video with `backend`.
+pub async fn get_view_with(
+    client: &BiliClient,
+    bvid: &str,
+    backend: ClientBackend,
+) -> Result<VideoView> {
+    match backend {
+        ClientBackend::Web => client.get(consts::VIEW, &[("bvid", bvid)]).await,
+        #[cfg(feature = "grpc")]
+        ClientBackend::App => super::app::get_view(client, bvid).await,
+    }
+}
+
+/// Get the parts of a video, lighter than [`get_view`].
+pub async fn get_pagelist(client: &BiliClient, bvid: &str) -> Result<Vec<VideoPage>> {
+    client.get(consts::PAGELIST, &[("bvid", bvid)]).await
+}
+
+/// Get the cid of the part `page`, starting from `1`, of a video.
+pub async fn resolve_cid(client: &BiliClient, bvid: &str, page: u32) -> Result<u64> {
+    get_pagelist(client, bvid)
+        .await?
+        .into_iter()
+        .find(|p| p.page == page)
+        .map(|p| p.cid)
+        .ok_or_else(|| Error::UnexpectedResponse(format!("{} has no part {}", bvid, page)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockTransport;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_view() {
+        let transport = MockTransport::new().json(
+            consts::VIEW,
+            json!({"code": 0, "data": {
+                "aid": 170001, "bvid": "BV17x411w7KC", "title": "title", "duration": 300,
+                "owner": {"mid": 2, "name": "碧诗", "face": ""},
+                "stat": {"aid": 170001, "view": 100, "like": 10},
+                "pages": [{"cid": 279786, "page": 1, "part": "p1", "duration": 300}],
+            }}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        let view = get_view(&client, "BV17x411w7KC").await.unwrap();
+        assert_eq!(view.owner.mid, 2);
+        assert_eq!(view.stat.view, 100);
+        assert_eq!(view.pages[0].cid, 279786);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_cid() {
+        let transport = MockTransport::new().json(
+            consts::PAGELIST,
+            json!({"code": 0, "data": [
+                {"cid": 279786, "page": 1, "part": "p1", "duration": 300},
+                {"cid": 279787, "page": 2, "part": "p2", "duration": 200},
+            ]}),
+        );
+        let client = BiliClient::builder().transport(transport).build().unwrap();
+        assert_eq!(
+            resolve_cid(&client, "BV17x411w7KC", 2).await.unwrap(),
+            279787
+        );
+        assert!(resolve_cid(&client, "BV17x411w7KC", 3).await.is_err());
+    }
+}